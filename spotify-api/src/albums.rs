@@ -0,0 +1,94 @@
+//! [Albums](https://developer.spotify.com/documentation/web-api/reference/get-an-album) API
+
+use crate::{artists::SimplifiedArtist, model::*};
+
+/// The type of an album
+#[derive(
+    Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum AlbumType {
+    /// A full length album
+    #[serde(alias = "ALBUM")]
+    Album,
+    /// A single or EP
+    #[serde(alias = "SINGLE")]
+    Single,
+    /// A compilation of tracks
+    #[serde(alias = "COMPILATION")]
+    Compilation,
+}
+
+/// [Simplified Album Object](https://developer.spotify.com/documentation/web-api/reference/get-track)
+///
+/// Albums of local files only have a `name`.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, serde::Serialize, serde::Deserialize)]
+pub struct SimplifiedAlbum {
+    /// The type of the album
+    #[serde(default)]
+    pub album_type: Option<AlbumType>,
+    /// The number of tracks in the album
+    #[serde(default)]
+    pub total_tracks: u32,
+    /// The [ISO 3166-1 alpha-2](https://en.wikipedia.org/wiki/ISO_3166-1_alpha-2) country codes the album is available in
+    #[serde(default)]
+    pub available_markets: Vec<String>,
+    /// Known external URLs for this album
+    #[serde(default)]
+    pub external_urls: ExternalUrls,
+    /// A link to the Web API endpoint providing full details of the album
+    #[serde(default)]
+    pub href: Option<String>,
+    /// The Spotify ID for the album
+    #[serde(default)]
+    pub id: Option<AlbumId>,
+    /// The cover art for the album in various sizes, widest first
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub images: Vec<Image>,
+    /// The name of the album
+    pub name: String,
+    /// The date the album was first released, e.g. `1981-12-15`
+    #[serde(default)]
+    pub release_date: Option<String>,
+    /// The precision with which `release_date` is known
+    #[serde(default)]
+    pub release_date_precision: Option<ReleaseDatePrecision>,
+    /// Included when a content restriction is applied
+    #[serde(default)]
+    pub restrictions: Option<Restrictions>,
+    /// The Spotify URI for the album
+    #[serde(default)]
+    pub uri: Option<String>,
+    /// The artists of the album
+    #[serde(default)]
+    pub artists: Vec<SimplifiedArtist>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    fn test_simplified_album_deserialize() {
+        let album: SimplifiedAlbum = serde_json::from_str(
+            r#"{"album_type":"ALBUM","total_tracks":9,"available_markets":["AU"],"external_urls":{"spotify":"https://open.spotify.com/album/2up3OPMp9Tb4dAKM2erWXQ"},"href":"https://api.spotify.com/v1/albums/2up3OPMp9Tb4dAKM2erWXQ","id":"2up3OPMp9Tb4dAKM2erWXQ","images":[],"name":"Album","release_date":"1981-12","release_date_precision":"month","type":"album","uri":"spotify:album:2up3OPMp9Tb4dAKM2erWXQ","artists":[]}"#,
+        )
+        .unwrap();
+        assert_eq!(album.album_type, Some(AlbumType::Album));
+        assert_eq!(album.release_date.as_deref(), Some("1981-12"));
+        assert_eq!(
+            album.release_date_precision,
+            Some(ReleaseDatePrecision::Month)
+        );
+
+        let local: SimplifiedAlbum = serde_json::from_str(
+            r#"{"album_type":null,"available_markets":[],"external_urls":{},"href":null,"id":null,"images":[],"name":"Local Album","release_date":null,"release_date_precision":null,"type":"album","uri":null,"artists":[]}"#,
+        )
+        .unwrap();
+        assert_eq!(local.id, None);
+        assert_eq!(local.album_type, None);
+    }
+}
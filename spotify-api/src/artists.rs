@@ -0,0 +1,123 @@
+//! [Artists](https://developer.spotify.com/documentation/web-api/reference/get-an-artist) API
+
+use crate::{client::Query, model::*, Client};
+use utils::request::Result;
+
+/// [Simplified Artist Object](https://developer.spotify.com/documentation/web-api/reference/get-track)
+///
+/// Artists of local files have no `href`, `id` or `uri`.
+#[derive(
+    Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, serde::Serialize, serde::Deserialize,
+)]
+pub struct SimplifiedArtist {
+    /// Known external URLs for this artist
+    #[serde(default)]
+    pub external_urls: ExternalUrls,
+    /// A link to the Web API endpoint providing full details of the artist
+    #[serde(default)]
+    pub href: Option<String>,
+    /// The Spotify ID for the artist
+    #[serde(default)]
+    pub id: Option<ArtistId>,
+    /// The name of the artist
+    pub name: String,
+    /// The Spotify URI for the artist
+    #[serde(default)]
+    pub uri: Option<String>,
+}
+
+/// [Artist Object](https://developer.spotify.com/documentation/web-api/reference/get-an-artist)
+#[derive(Clone, Eq, PartialEq, Hash, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Artist {
+    /// Known external URLs for this artist
+    pub external_urls: ExternalUrls,
+    /// Information about the followers of the artist
+    #[serde(default)]
+    pub followers: Option<Followers>,
+    /// The genres the artist is associated with, empty if not yet classified
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub genres: Vec<String>,
+    /// A link to the Web API endpoint providing full details of the artist
+    pub href: String,
+    /// The Spotify ID for the artist
+    pub id: ArtistId,
+    /// Images of the artist in various sizes, widest first
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub images: Vec<Image>,
+    /// The name of the artist
+    pub name: String,
+    /// The popularity of the artist from 0 to 100, calculated from the popularity of all their tracks
+    #[serde(default)]
+    pub popularity: u32,
+    /// The Spotify URI for the artist
+    pub uri: String,
+}
+
+impl Client {
+    /// [Get Artist](https://developer.spotify.com/documentation/web-api/reference/get-an-artist)
+    pub async fn artist(&mut self, id: &ArtistId) -> Result<Artist> {
+        self.get(&["artists", id.as_str()], &Query::default()).await
+    }
+
+    /// [Get Several Artists](https://developer.spotify.com/documentation/web-api/reference/get-multiple-artists)
+    ///
+    /// Accepts a maximum of 50 IDs, artists that can't be found are returned as `None`.
+    pub async fn artists(&mut self, ids: &[ArtistId]) -> Result<Vec<Option<Artist>>> {
+        #[derive(serde::Deserialize)]
+        struct Response {
+            artists: Vec<Option<Artist>>,
+        }
+
+        let res: Response = self
+            .get(
+                &["artists"],
+                &Query {
+                    ids: Some(&join_ids(ids)),
+                    ..Default::default()
+                },
+            )
+            .await?;
+        Ok(res.artists)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    fn test_simplified_artist_deserialize() {
+        let artist: SimplifiedArtist = serde_json::from_str(
+            r#"{"external_urls":{"spotify":"https://open.spotify.com/artist/0TnOYISbd1XYRBk9myaseg"},"href":"https://api.spotify.com/v1/artists/0TnOYISbd1XYRBk9myaseg","id":"0TnOYISbd1XYRBk9myaseg","name":"Pitbull","type":"artist","uri":"spotify:artist:0TnOYISbd1XYRBk9myaseg"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            artist.id,
+            Some(ArtistId::new("0TnOYISbd1XYRBk9myaseg").unwrap())
+        );
+
+        let local: SimplifiedArtist = serde_json::from_str(
+            r#"{"external_urls":{},"href":null,"id":null,"name":"Local Artist","type":"artist","uri":null}"#,
+        )
+        .unwrap();
+        assert_eq!(local.id, None);
+        assert_eq!(local.name, "Local Artist");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_artist_deserialize() {
+        let artist: Artist = serde_json::from_str(
+            r#"{"external_urls":{"spotify":"https://open.spotify.com/artist/0TnOYISbd1XYRBk9myaseg"},"followers":{"href":null,"total":10000000},"genres":["dance pop","miami hip hop"],"href":"https://api.spotify.com/v1/artists/0TnOYISbd1XYRBk9myaseg","id":"0TnOYISbd1XYRBk9myaseg","images":[],"name":"Pitbull","popularity":80,"type":"artist","uri":"spotify:artist:0TnOYISbd1XYRBk9myaseg"}"#,
+        )
+        .unwrap();
+        assert_eq!(artist.genres, ["dance pop", "miami hip hop"]);
+        assert_eq!(
+            artist.followers.map(|followers| followers.total),
+            Some(10000000)
+        );
+        assert_eq!(artist.popularity, 80);
+    }
+}
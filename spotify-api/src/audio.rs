@@ -0,0 +1,381 @@
+//! [Audio features](https://developer.spotify.com/documentation/web-api/reference/get-audio-features) and
+//! [audio analysis](https://developer.spotify.com/documentation/web-api/reference/get-audio-analysis) of tracks
+
+use crate::{
+    batch::MAX_IDS_100, client::Query, model::*, recommendations::TunableAttribute, Client,
+};
+use utils::request::Result;
+
+/// The number of requests for audio features in flight at a time when batching them
+const FEATURES_CONCURRENCY: usize = 2;
+
+/// [Audio Features Object](https://developer.spotify.com/documentation/web-api/reference/get-audio-features)
+#[derive(Clone, PartialEq, Debug, serde::Serialize, serde::Deserialize)]
+pub struct AudioFeatures {
+    /// Confidence from 0.0 to 1.0 of whether the track is acoustic
+    pub acousticness: f64,
+    /// A URL to access the full audio analysis of the track
+    pub analysis_url: String,
+    /// Suitability from 0.0 to 1.0 of the track for dancing
+    pub danceability: f64,
+    /// The duration of the track in milliseconds
+    pub duration_ms: u32,
+    /// Perceptual measure from 0.0 to 1.0 of intensity and activity
+    pub energy: f64,
+    /// The Spotify ID for the track
+    pub id: TrackId,
+    /// Confidence from 0.0 to 1.0 that the track contains no vocals
+    pub instrumentalness: f64,
+    /// The [pitch class](https://en.wikipedia.org/wiki/Pitch_class) of the track from 0 (C) to 11 (B), -1 if no key was detected
+    pub key: i32,
+    /// Confidence from 0.0 to 1.0 that the track was performed live
+    pub liveness: f64,
+    /// The overall loudness of the track in decibels, typically between -60 and 0
+    pub loudness: f64,
+    /// The modality of the track, 0 for minor and 1 for major
+    pub mode: u32,
+    /// Presence from 0.0 to 1.0 of spoken words in the track
+    pub speechiness: f64,
+    /// The overall estimated tempo of the track in beats per minute
+    pub tempo: f64,
+    /// The estimated time signature of the track from 3 to 7, as beats per bar
+    pub time_signature: u32,
+    /// A link to the Web API endpoint providing full details of the track
+    pub track_href: String,
+    /// The Spotify URI for the track
+    pub uri: String,
+    /// Musical positiveness from 0.0 to 1.0 conveyed by the track
+    pub valence: f64,
+}
+
+impl AudioFeatures {
+    /// The value of the `attribute` for the track.
+    ///
+    /// [`TunableAttribute::Popularity`] isn't an audio feature and is always `None`.
+    pub fn value(&self, attribute: TunableAttribute) -> Option<f64> {
+        use TunableAttribute::*;
+        Some(match attribute {
+            Acousticness => self.acousticness,
+            Danceability => self.danceability,
+            DurationMs => self.duration_ms.into(),
+            Energy => self.energy,
+            Instrumentalness => self.instrumentalness,
+            Key => self.key.into(),
+            Liveness => self.liveness,
+            Loudness => self.loudness,
+            Mode => self.mode.into(),
+            Popularity => return None,
+            Speechiness => self.speechiness,
+            Tempo => self.tempo,
+            TimeSignature => self.time_signature.into(),
+            Valence => self.valence,
+        })
+    }
+
+    /// The position of the track's key on the [Camelot wheel](https://mixedinkey.com/camelot-wheel/),
+    /// e.g. `(8, 'B')` for C major and `(8, 'A')` for A minor, or `None` if no key was detected
+    ///
+    /// Keys with adjacent numbers and the same letter, or the same number, mix harmonically.
+    pub fn camelot(&self) -> Option<(u32, char)> {
+        let key = u32::try_from(self.key).ok().filter(|key| *key < 12)?;
+        // Each step clockwise is a fifth, so 7 semitones, with C major at 8B and its relative minor A at 8A
+        match self.mode {
+            1 => Some(((7 * key + 7) % 12 + 1, 'B')),
+            _ => Some(((7 * key + 4) % 12 + 1, 'A')),
+        }
+    }
+}
+
+/// A time interval of an [`AudioAnalysis`] in seconds
+#[derive(
+    Copy, Clone, PartialEq, PartialOrd, Debug, Default, serde::Serialize, serde::Deserialize,
+)]
+pub struct TimeInterval {
+    /// The starting point of the interval
+    pub start: f64,
+    /// The duration of the interval
+    pub duration: f64,
+    /// Confidence from 0.0 to 1.0 of the reliability of the interval
+    pub confidence: f64,
+}
+
+impl TimeInterval {
+    /// Returns if the interval contains the `time` in seconds
+    pub fn contains(&self, time: f64) -> bool {
+        (self.start..self.start + self.duration).contains(&time)
+    }
+}
+
+/// A large variation in rhythm or timbre, e.g. a chorus, verse or bridge
+#[derive(Clone, PartialEq, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct Section {
+    /// The time interval of the section
+    #[serde(flatten)]
+    pub interval: TimeInterval,
+    /// The overall loudness of the section in decibels
+    pub loudness: f64,
+    /// The overall estimated tempo of the section in beats per minute
+    pub tempo: f64,
+    /// Confidence from 0.0 to 1.0 of the reliability of the `tempo`
+    pub tempo_confidence: f64,
+    /// The estimated key of the section from 0 (C) to 11 (B), -1 if no key was detected
+    pub key: i32,
+    /// Confidence from 0.0 to 1.0 of the reliability of the `key`
+    pub key_confidence: f64,
+    /// The modality of the section, 0 for minor, 1 for major and -1 if no mode was detected
+    pub mode: i32,
+    /// Confidence from 0.0 to 1.0 of the reliability of the `mode`
+    pub mode_confidence: f64,
+    /// The estimated time signature of the section from 3 to 7, as beats per bar
+    pub time_signature: i32,
+    /// Confidence from 0.0 to 1.0 of the reliability of the `time_signature`
+    pub time_signature_confidence: f64,
+}
+
+/// A segment of roughly consistent sound throughout its duration
+#[derive(Clone, PartialEq, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct Segment {
+    /// The time interval of the segment
+    #[serde(flatten)]
+    pub interval: TimeInterval,
+    /// The onset loudness of the segment in decibels
+    pub loudness_start: f64,
+    /// The peak loudness of the segment in decibels
+    pub loudness_max: f64,
+    /// The offset within the segment of the peak loudness in seconds
+    pub loudness_max_time: f64,
+    /// The offset loudness of the segment in decibels, only provided for the last segment
+    #[serde(default)]
+    pub loudness_end: Option<f64>,
+    /// The relative dominance from 0.0 to 1.0 of the 12 pitch classes, C to B
+    pub pitches: Vec<f64>,
+    /// The 12 unbounded timbre coefficients of the segment, centered around 0
+    pub timbre: Vec<f64>,
+}
+
+/// Track wide results of an [`AudioAnalysis`]
+#[derive(Clone, PartialEq, Debug, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct AnalysisTrack {
+    /// The number of samples analysed
+    pub num_samples: u64,
+    /// The length of the track in seconds
+    pub duration: f64,
+    /// The sample rate used to analyse the track
+    pub analysis_sample_rate: u32,
+    /// The number of channels used to analyse the track
+    pub analysis_channels: u32,
+    /// The end time of the fade-in period of the track in seconds
+    pub end_of_fade_in: f64,
+    /// The start time of the fade out period of the track in seconds
+    pub start_of_fade_out: f64,
+    /// The overall loudness of the track in decibels
+    pub loudness: f64,
+    /// The overall estimated tempo of the track in beats per minute
+    pub tempo: f64,
+    /// Confidence from 0.0 to 1.0 of the reliability of the `tempo`
+    pub tempo_confidence: f64,
+    /// The estimated time signature of the track from 3 to 7, as beats per bar
+    pub time_signature: i32,
+    /// Confidence from 0.0 to 1.0 of the reliability of the `time_signature`
+    pub time_signature_confidence: f64,
+    /// The estimated key of the track from 0 (C) to 11 (B), -1 if no key was detected
+    pub key: i32,
+    /// Confidence from 0.0 to 1.0 of the reliability of the `key`
+    pub key_confidence: f64,
+    /// The modality of the track, 0 for minor, 1 for major and -1 if no mode was detected
+    pub mode: i32,
+    /// Confidence from 0.0 to 1.0 of the reliability of the `mode`
+    pub mode_confidence: f64,
+}
+
+/// [Audio Analysis Object](https://developer.spotify.com/documentation/web-api/reference/get-audio-analysis)
+#[derive(Clone, PartialEq, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct AudioAnalysis {
+    /// Track wide results of the analysis
+    pub track: AnalysisTrack,
+    /// Bars (or measures) of the track, each a group of beats
+    pub bars: Vec<TimeInterval>,
+    /// The basic time units of the track
+    pub beats: Vec<TimeInterval>,
+    /// Large variations in rhythm or timbre of the track
+    pub sections: Vec<Section>,
+    /// Parts of the track with roughly consistent sound
+    pub segments: Vec<Segment>,
+    /// The lowest regular pulse train that a listener intuitively infers from the timing of perceived musical events
+    pub tatums: Vec<TimeInterval>,
+}
+
+/// Finds the item whose interval contains `time`, `items` must be sorted by start time
+fn find_at<T>(items: &[T], time: f64, interval: fn(&T) -> &TimeInterval) -> Option<&T> {
+    let index = items
+        .partition_point(|item| interval(item).start <= time)
+        .checked_sub(1)?;
+    items
+        .get(index)
+        .filter(|item| interval(item).contains(time))
+}
+
+impl AudioAnalysis {
+    /// The bar playing at `time` seconds into the track
+    pub fn bar_at(&self, time: f64) -> Option<&TimeInterval> {
+        find_at(&self.bars, time, |bar| bar)
+    }
+
+    /// The beat playing at `time` seconds into the track
+    pub fn beat_at(&self, time: f64) -> Option<&TimeInterval> {
+        find_at(&self.beats, time, |beat| beat)
+    }
+
+    /// The section playing at `time` seconds into the track
+    pub fn section_at(&self, time: f64) -> Option<&Section> {
+        find_at(&self.sections, time, |section| &section.interval)
+    }
+
+    /// The segment playing at `time` seconds into the track
+    pub fn segment_at(&self, time: f64) -> Option<&Segment> {
+        find_at(&self.segments, time, |segment| &segment.interval)
+    }
+
+    /// The tatum playing at `time` seconds into the track
+    pub fn tatum_at(&self, time: f64) -> Option<&TimeInterval> {
+        find_at(&self.tatums, time, |tatum| tatum)
+    }
+}
+
+impl Client {
+    /// [Get Track's Audio Features](https://developer.spotify.com/documentation/web-api/reference/get-audio-features)
+    pub async fn audio_features(&mut self, id: &TrackId) -> Result<AudioFeatures> {
+        self.get(&["audio-features", id.as_str()], &Query::default())
+            .await
+    }
+
+    /// [Get Tracks' Audio Features](https://developer.spotify.com/documentation/web-api/reference/get-several-audio-features)
+    ///
+    /// Accepts a maximum of 100 IDs, tracks without audio features are returned as `None`.
+    pub async fn audio_features_many(
+        &mut self,
+        ids: &[TrackId],
+    ) -> Result<Vec<Option<AudioFeatures>>> {
+        #[derive(serde::Deserialize)]
+        struct Response {
+            audio_features: Vec<Option<AudioFeatures>>,
+        }
+
+        let res: Response = self
+            .get(
+                &["audio-features"],
+                &Query {
+                    ids: Some(&join_ids(ids)),
+                    ..Default::default()
+                },
+            )
+            .await?;
+        Ok(res.audio_features)
+    }
+
+    /// Returns the audio features of each of the tracks, `None` for tracks without an ID or audio features,
+    /// requesting them in [batches](Client::batch())
+    pub(crate) async fn audio_features_batched(
+        &mut self,
+        ids: &[Option<TrackId>],
+    ) -> Result<Vec<Option<AudioFeatures>>> {
+        let len = ids.len();
+        let (positions, ids): (Vec<usize>, Vec<TrackId>) = ids
+            .iter()
+            .enumerate()
+            .filter_map(|(position, id)| Some((position, id.clone()?)))
+            .unzip();
+        let features = self
+            .batch(
+                &ids,
+                MAX_IDS_100,
+                FEATURES_CONCURRENCY,
+                |mut client, ids| async move { client.audio_features_many(ids).await },
+            )
+            .await
+            .into_result()?;
+        let mut aligned = vec![None; len];
+        for (position, features) in positions.into_iter().zip(features) {
+            aligned[position] = features;
+        }
+        Ok(aligned)
+    }
+
+    /// [Get Track's Audio Analysis](https://developer.spotify.com/documentation/web-api/reference/get-audio-analysis)
+    pub async fn audio_analysis(&mut self, id: &TrackId) -> Result<AudioAnalysis> {
+        self.get(&["audio-analysis", id.as_str()], &Query::default())
+            .await
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    /// Audio features of the track `id` for tests of audio feature based features
+    pub(crate) fn audio_features(
+        id: &str,
+        energy: f64,
+        tempo: f64,
+        key: i32,
+        mode: u32,
+    ) -> AudioFeatures {
+        serde_json::from_str(&format!(
+            r#"{{"acousticness":0.00242,"analysis_url":"https://api.spotify.com/v1/audio-analysis/{id}","danceability":0.585,"duration_ms":237040,"energy":{energy},"id":"{id}","instrumentalness":0.00686,"key":{key},"liveness":0.0866,"loudness":-5.883,"mode":{mode},"speechiness":0.0556,"tempo":{tempo},"time_signature":4,"track_href":"https://api.spotify.com/v1/tracks/{id}","type":"audio_features","uri":"spotify:track:{id}","valence":0.428}}"#
+        ))
+        .expect("Valid test audio features")
+    }
+
+    #[wasm_bindgen_test]
+    fn test_audio_features() {
+        let features = audio_features("2takcwOaAZWiXQijPHIx7B", 0.842, 118.211, 9, 0);
+        assert_eq!(features.id, TrackId::new("2takcwOaAZWiXQijPHIx7B").unwrap());
+        assert_eq!(features.value(TunableAttribute::Energy), Some(0.842));
+        assert_eq!(features.value(TunableAttribute::Key), Some(9.0));
+        assert_eq!(features.value(TunableAttribute::DurationMs), Some(237040.0));
+        assert_eq!(features.value(TunableAttribute::Popularity), None);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_camelot() {
+        let camelot =
+            |key, mode| audio_features("2takcwOaAZWiXQijPHIx7B", 0.5, 120.0, key, mode).camelot();
+        assert_eq!(camelot(0, 1), Some((8, 'B')));
+        assert_eq!(camelot(7, 1), Some((9, 'B')));
+        assert_eq!(camelot(11, 1), Some((1, 'B')));
+        assert_eq!(camelot(9, 0), Some((8, 'A')));
+        assert_eq!(camelot(4, 0), Some((9, 'A')));
+        assert_eq!(camelot(-1, 1), None);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_audio_analysis() {
+        let analysis: AudioAnalysis = serde_json::from_str(
+            r#"{"meta":{"analyzer_version":"4.0.0","status_code":0},"track":{"num_samples":4585515,"duration":207.95985,"sample_md5":"","analysis_sample_rate":22050,"analysis_channels":1,"end_of_fade_in":0,"start_of_fade_out":201.13705,"loudness":-14.938,"tempo":123.065,"tempo_confidence":0.53,"time_signature":4,"time_signature_confidence":1,"key":7,"key_confidence":0.468,"mode":1,"mode_confidence":0.415,"codestring":"eJx"},
+            "bars":[{"start":0.49567,"duration":2.18749,"confidence":0.925},{"start":2.68316,"duration":2.1965,"confidence":0.8}],
+            "beats":[{"start":0.49567,"duration":0.54617,"confidence":0.5},{"start":1.04184,"duration":0.54617,"confidence":0.6}],
+            "sections":[{"start":0,"duration":6.97092,"confidence":1,"loudness":-14.938,"tempo":113.178,"tempo_confidence":0.647,"key":9,"key_confidence":0.297,"mode":-1,"mode_confidence":0.471,"time_signature":4,"time_signature_confidence":1}],
+            "segments":[{"start":0.70154,"duration":0.19891,"confidence":0.435,"loudness_start":-23.053,"loudness_max":-14.25,"loudness_max_time":0.07305,"loudness_end":0,"pitches":[0.212,0.141,0.294,0.277,0.257,0.262,0.282,0.176,0.266,0.148,0.331,0.191],"timbre":[42.115,64.373,-0.233,-2.131,16.209,-1.112,-18.441,-4.237,3.637,4.289,-5.203,-8.498]}],
+            "tatums":[{"start":0.49567,"duration":0.27308,"confidence":0.779}]}"#,
+        )
+        .unwrap();
+        assert_eq!(analysis.track.key, 7);
+        assert_eq!(analysis.sections[0].mode, -1);
+        assert_eq!(analysis.segments[0].pitches.len(), 12);
+        assert_eq!(analysis.segments[0].timbre.len(), 12);
+        assert_eq!(analysis.segments[0].loudness_end, Some(0.0));
+
+        assert_eq!(analysis.beat_at(0.0), None);
+        assert_eq!(analysis.beat_at(0.5), analysis.beats.first());
+        assert_eq!(analysis.beat_at(1.05), analysis.beats.get(1));
+        assert_eq!(analysis.beat_at(1.6), None);
+        assert_eq!(analysis.bar_at(3.0), analysis.bars.get(1));
+        assert_eq!(analysis.section_at(6.0), analysis.sections.first());
+        assert_eq!(analysis.segment_at(0.8), analysis.segments.first());
+        assert_eq!(analysis.tatum_at(0.6), analysis.tatums.first());
+    }
+}
@@ -0,0 +1,306 @@
+//! [Audiobooks](https://developer.spotify.com/documentation/web-api/reference/get-an-audiobook) API
+//!
+//! Audiobooks are only available in some markets, the API answers with no audiobook for any other market.
+
+use crate::{
+    chapters::SimplifiedChapter,
+    client::{not_found_as_none, Query},
+    model::*,
+    Client,
+};
+use utils::request::Result;
+
+/// An author of an audiobook
+#[derive(
+    Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, serde::Serialize, serde::Deserialize,
+)]
+pub struct Author {
+    /// The name of the author
+    pub name: String,
+}
+
+/// A narrator of an audiobook
+#[derive(
+    Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, serde::Serialize, serde::Deserialize,
+)]
+pub struct Narrator {
+    /// The name of the narrator
+    pub name: String,
+}
+
+/// [Audiobook Object](https://developer.spotify.com/documentation/web-api/reference/get-an-audiobook) with its first page of chapters
+#[derive(Clone, Eq, PartialEq, Hash, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Audiobook {
+    /// The authors of the audiobook
+    pub authors: Vec<Author>,
+    /// The [ISO 3166-1 alpha-2](https://en.wikipedia.org/wiki/ISO_3166-1_alpha-2) country codes the audiobook is available in
+    pub available_markets: Vec<String>,
+    /// The copyright statements of the audiobook
+    pub copyrights: Vec<Copyright>,
+    /// A description of the audiobook with HTML tags stripped
+    pub description: String,
+    /// A description of the audiobook which may contain HTML tags
+    pub html_description: String,
+    /// The edition of the audiobook
+    #[serde(default)]
+    pub edition: Option<String>,
+    /// Whether or not the audiobook has explicit content
+    pub explicit: bool,
+    /// External URLs for this audiobook
+    pub external_urls: ExternalUrls,
+    /// A link to the Web API endpoint providing full details of the audiobook
+    pub href: String,
+    /// The Spotify ID for the audiobook
+    pub id: AudiobookId,
+    /// The cover art for the audiobook in various sizes, widest first
+    pub images: Vec<Image>,
+    /// The [ISO 639](https://en.wikipedia.org/wiki/ISO_639) codes of the languages used in the audiobook
+    pub languages: Vec<String>,
+    /// The media type of the audiobook
+    pub media_type: String,
+    /// The name of the audiobook
+    pub name: String,
+    /// The narrators of the audiobook
+    pub narrators: Vec<Narrator>,
+    /// The publisher of the audiobook
+    pub publisher: String,
+    /// The Spotify URI for the audiobook
+    pub uri: String,
+    /// The number of chapters in the audiobook
+    pub total_chapters: u32,
+    /// The first page of the audiobook's chapters
+    pub chapters: Page<SimplifiedChapter>,
+}
+
+/// [Simplified Audiobook Object](https://developer.spotify.com/documentation/web-api/reference/get-multiple-audiobooks)
+#[derive(Clone, Eq, PartialEq, Hash, Debug, serde::Serialize, serde::Deserialize)]
+pub struct SimplifiedAudiobook {
+    /// The authors of the audiobook
+    pub authors: Vec<Author>,
+    /// The [ISO 3166-1 alpha-2](https://en.wikipedia.org/wiki/ISO_3166-1_alpha-2) country codes the audiobook is available in
+    pub available_markets: Vec<String>,
+    /// The copyright statements of the audiobook
+    pub copyrights: Vec<Copyright>,
+    /// A description of the audiobook with HTML tags stripped
+    pub description: String,
+    /// A description of the audiobook which may contain HTML tags
+    pub html_description: String,
+    /// The edition of the audiobook
+    #[serde(default)]
+    pub edition: Option<String>,
+    /// Whether or not the audiobook has explicit content
+    pub explicit: bool,
+    /// External URLs for this audiobook
+    pub external_urls: ExternalUrls,
+    /// A link to the Web API endpoint providing full details of the audiobook
+    pub href: String,
+    /// The Spotify ID for the audiobook
+    pub id: AudiobookId,
+    /// The cover art for the audiobook in various sizes, widest first
+    pub images: Vec<Image>,
+    /// The [ISO 639](https://en.wikipedia.org/wiki/ISO_639) codes of the languages used in the audiobook
+    pub languages: Vec<String>,
+    /// The media type of the audiobook
+    pub media_type: String,
+    /// The name of the audiobook
+    pub name: String,
+    /// The narrators of the audiobook
+    pub narrators: Vec<Narrator>,
+    /// The publisher of the audiobook
+    pub publisher: String,
+    /// The Spotify URI for the audiobook
+    pub uri: String,
+    /// The number of chapters in the audiobook
+    pub total_chapters: u32,
+}
+
+/// An audiobook saved in the current user's library
+#[derive(Clone, Eq, PartialEq, Hash, Debug, serde::Serialize, serde::Deserialize)]
+pub struct SavedAudiobook {
+    /// The [ISO 8601](https://en.wikipedia.org/wiki/ISO_8601) UTC timestamp of when the audiobook was saved
+    pub added_at: String,
+    /// The saved audiobook
+    pub audiobook: SimplifiedAudiobook,
+}
+
+impl Client {
+    /// [Get an Audiobook](https://developer.spotify.com/documentation/web-api/reference/get-an-audiobook)
+    ///
+    /// Returns `None` if the audiobook can't be found or isn't available in the `market`.
+    pub async fn audiobook(
+        &mut self,
+        id: &AudiobookId,
+        market: Option<&str>,
+    ) -> Result<Option<Audiobook>> {
+        let market = self.resolve_market(market);
+        self.get(
+            &["audiobooks", id.as_str()],
+            &Query {
+                market: market.as_deref(),
+                ..Default::default()
+            },
+        )
+        .await
+        .map(Some)
+        .or_else(not_found_as_none)
+    }
+
+    /// [Get Several Audiobooks](https://developer.spotify.com/documentation/web-api/reference/get-multiple-audiobooks)
+    ///
+    /// Accepts a maximum of 50 IDs, audiobooks that can't be found are returned as `None`.
+    pub async fn audiobooks(
+        &mut self,
+        ids: &[AudiobookId],
+        market: Option<&str>,
+    ) -> Result<Vec<Option<SimplifiedAudiobook>>> {
+        #[derive(serde::Deserialize)]
+        struct Response {
+            audiobooks: Vec<Option<SimplifiedAudiobook>>,
+        }
+
+        let market = self.resolve_market(market);
+        let res: Response = self
+            .get(
+                &["audiobooks"],
+                &Query {
+                    ids: Some(&join_ids(ids)),
+                    market: market.as_deref(),
+                    ..Default::default()
+                },
+            )
+            .await?;
+        Ok(res.audiobooks)
+    }
+
+    /// [Get Audiobook Chapters](https://developer.spotify.com/documentation/web-api/reference/get-audiobook-chapters)
+    ///
+    /// `limit` is between 1 and 50, defaulting to 20.
+    /// Returns `None` if the audiobook can't be found or isn't available in the `market`.
+    pub async fn audiobook_chapters(
+        &mut self,
+        id: &AudiobookId,
+        market: Option<&str>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<Option<Page<SimplifiedChapter>>> {
+        let market = self.resolve_market(market);
+        self.get(
+            &["audiobooks", id.as_str(), "chapters"],
+            &Query {
+                market: market.as_deref(),
+                limit,
+                offset,
+                ..Default::default()
+            },
+        )
+        .await
+        .map(Some)
+        .or_else(not_found_as_none)
+    }
+
+    /// [Get User's Saved Audiobooks](https://developer.spotify.com/documentation/web-api/reference/get-users-saved-audiobooks)
+    ///
+    /// Requires [`Scopes::UserLibraryRead`](crate::authorisation::Scopes::UserLibraryRead),
+    /// `limit` is between 1 and 50, defaulting to 20.
+    pub async fn saved_audiobooks(
+        &mut self,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<Page<SavedAudiobook>> {
+        self.get(
+            &["me", "audiobooks"],
+            &Query {
+                limit,
+                offset,
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// [Save Audiobooks for Current User](https://developer.spotify.com/documentation/web-api/reference/save-audiobooks-user)
+    ///
+    /// Requires [`Scopes::UserLibraryModify`](crate::authorisation::Scopes::UserLibraryModify),
+    /// accepts a maximum of 50 IDs.
+    pub async fn save_audiobooks(&mut self, ids: &[AudiobookId]) -> Result<()> {
+        self.send_no_content(
+            reqwest::Method::PUT,
+            &["me", "audiobooks"],
+            &Query {
+                ids: Some(&join_ids(ids)),
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// [Remove User's Saved Audiobooks](https://developer.spotify.com/documentation/web-api/reference/remove-audiobooks-user)
+    ///
+    /// Requires [`Scopes::UserLibraryModify`](crate::authorisation::Scopes::UserLibraryModify),
+    /// accepts a maximum of 50 IDs.
+    pub async fn remove_saved_audiobooks(&mut self, ids: &[AudiobookId]) -> Result<()> {
+        self.send_no_content(
+            reqwest::Method::DELETE,
+            &["me", "audiobooks"],
+            &Query {
+                ids: Some(&join_ids(ids)),
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// [Check User's Saved Audiobooks](https://developer.spotify.com/documentation/web-api/reference/check-users-saved-audiobooks)
+    ///
+    /// Requires [`Scopes::UserLibraryRead`](crate::authorisation::Scopes::UserLibraryRead),
+    /// accepts a maximum of 50 IDs and returns whether each audiobook is saved in the same order.
+    pub async fn check_saved_audiobooks(&mut self, ids: &[AudiobookId]) -> Result<Vec<bool>> {
+        self.get(
+            &["me", "audiobooks", "contains"],
+            &Query {
+                ids: Some(&join_ids(ids)),
+                ..Default::default()
+            },
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    fn test_simplified_audiobook_deserialize() {
+        let audiobook: SimplifiedAudiobook = serde_json::from_str(
+            r#"{"authors":[{"name":"Author"}],"available_markets":["US"],"copyrights":[],"description":"A book","html_description":"<p>A book</p>","edition":"Unabridged","explicit":false,"external_urls":{"spotify":"https://open.spotify.com/show/7iHfbu1YPACw6oZPAFJtqe"},"href":"https://api.spotify.com/v1/audiobooks/7iHfbu1YPACw6oZPAFJtqe","id":"7iHfbu1YPACw6oZPAFJtqe","images":[],"languages":["en"],"media_type":"audio","name":"Audiobook","narrators":[{"name":"Narrator"}],"publisher":"Publisher","type":"audiobook","uri":"spotify:show:7iHfbu1YPACw6oZPAFJtqe","total_chapters":3}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            audiobook.id,
+            AudiobookId::new("7iHfbu1YPACw6oZPAFJtqe").unwrap()
+        );
+        assert_eq!(
+            AudiobookId::from_uri(&audiobook.uri),
+            Ok(audiobook.id.clone())
+        );
+        assert_eq!(audiobook.id.uri(), audiobook.uri);
+        assert_eq!(
+            audiobook.authors,
+            [Author {
+                name: "Author".to_string()
+            }]
+        );
+        assert_eq!(
+            audiobook.narrators,
+            [Narrator {
+                name: "Narrator".to_string()
+            }]
+        );
+        assert_eq!(audiobook.edition.as_deref(), Some("Unabridged"));
+        assert_eq!(audiobook.total_chapters, 3);
+    }
+}
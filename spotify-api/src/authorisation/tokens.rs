@@ -381,7 +381,8 @@ mod tests {
                     body: Some(
                         r#"{"error":"invalid_client","error_description":"Invalid client"}"#
                             .to_string()
-                    )
+                    ),
+                    retry_after: None,
                 }
             )))
         );
@@ -0,0 +1,317 @@
+//! Chunked execution of requests for more IDs than an endpoint accepts at once
+//!
+//! Endpoints taking several IDs cap how many are accepted per request, e.g. 20 albums, 50 shows or 100 audio features.
+//! [`Client::batch()`] splits any list of IDs into chunks of at most that size,
+//! sends a bounded number of chunks at a time and merges their results in input order.
+//! Each chunk succeeds or fails on its own, so one failed request doesn't discard the rest of the batch.
+//!
+//! ```ignore
+//! let shows = client
+//!     .batch(&ids, MAX_IDS_50, 4, |mut client, ids| async move {
+//!         client.shows(ids, None).await
+//!     })
+//!     .await
+//!     .into_result()?;
+//! ```
+
+use crate::{
+    client::REQUEST_DURATION,
+    limiter::{MAX_RETRIES, MAX_RETRY_AFTER},
+    Client,
+};
+use instant::Duration;
+use std::{future::Future, ops::Range, pin::Pin, task::Poll};
+use utils::request::{Error, JSONError, Result};
+
+/// The maximum number of IDs accepted by e.g. [Get Several Albums](https://developer.spotify.com/documentation/web-api/reference/get-multiple-albums)
+pub const MAX_IDS_20: usize = 20;
+/// The maximum number of IDs accepted by e.g. [Get Several Shows](https://developer.spotify.com/documentation/web-api/reference/get-multiple-shows)
+pub const MAX_IDS_50: usize = 50;
+/// The maximum number of IDs accepted by e.g. [Get Tracks' Audio Features](https://developer.spotify.com/documentation/web-api/reference/get-several-audio-features)
+pub const MAX_IDS_100: usize = 100;
+
+/// The longest a batch's access token is refreshed to stay valid for, well within the hour tokens last
+const MAX_BATCH_DURATION: Duration = Duration::from_secs(30 * 60);
+
+/// The outcome of the request for one chunk of a batch
+#[derive(Debug)]
+pub struct Chunk<T> {
+    /// The indices of the chunk's IDs in the batch
+    pub range: Range<usize>,
+    /// The items returned for the chunk's IDs, in the same order, or the error making the request
+    pub result: Result<Vec<T>>,
+}
+
+/// The outcome of a [batch](Client::batch()) of requests, with a [`Chunk`] per request in input order
+#[derive(Debug)]
+pub struct BatchResult<T> {
+    /// The outcome of each chunk in input order
+    pub chunks: Vec<Chunk<T>>,
+}
+
+impl<T> BatchResult<T> {
+    /// Returns if every chunk succeeded
+    pub fn is_ok(&self) -> bool {
+        self.chunks.iter().all(|chunk| chunk.result.is_ok())
+    }
+
+    /// The indices of the IDs and error of each chunk that failed
+    pub fn errors(&self) -> impl Iterator<Item = (&Range<usize>, &utils::request::Error)> {
+        self.chunks
+            .iter()
+            .filter_map(|chunk| chunk.result.as_ref().err().map(|err| (&chunk.range, err)))
+    }
+
+    /// The merged items of every chunk, with an item per ID in input order
+    ///
+    /// The items of failed chunks, or missing from a chunk's response, are `None`.
+    pub fn into_items(self) -> Vec<Option<T>> {
+        let mut items = Vec::new();
+        for chunk in self.chunks {
+            let start = items.len();
+            if let Ok(chunk_items) = chunk.result {
+                items.extend(chunk_items.into_iter().take(chunk.range.len()).map(Some));
+            }
+            items.resize_with(start + chunk.range.len(), || None);
+        }
+        items
+    }
+
+    /// The merged items of every chunk in input order, or the error of the first failed chunk
+    ///
+    /// A chunk that didn't return an item per ID fails with [`Error::Body`],
+    /// as the items can't be matched to their IDs.
+    pub fn into_result(self) -> Result<Vec<T>> {
+        let mut items = Vec::new();
+        for chunk in self.chunks {
+            let chunk_items = chunk.result?;
+            if chunk_items.len() != chunk.range.len() {
+                let expected = format!("an item per ID of {:?}", chunk.range);
+                return Err(Error::Body(JSONError {
+                    error: serde::de::Error::invalid_length(chunk_items.len(), &expected.as_str()),
+                    body: String::new(),
+                }));
+            }
+            items.extend(chunk_items);
+        }
+        Ok(items)
+    }
+}
+
+impl Client {
+    /// Makes a request with `request` for each chunk of at most `chunk_size` of the `ids`,
+    /// with at most `concurrency` requests in flight at a time
+    ///
+    /// `request` is given a clone of the client, sharing its cache, limiter and metrics,
+    /// and should return an item per ID of its chunk in the same order.
+    /// The access token is refreshed before the batch starts to last the expected duration of the batch,
+    /// including waiting to retry rate limited requests, so the clones don't each refresh it.
+    pub async fn batch<'a, I, T, F, Fut>(
+        &mut self,
+        ids: &'a [I],
+        chunk_size: usize,
+        concurrency: usize,
+        request: F,
+    ) -> BatchResult<T>
+    where
+        F: Fn(Client, &'a [I]) -> Fut,
+        Fut: Future<Output = Result<Vec<T>>>,
+    {
+        let chunk_size = chunk_size.max(1);
+        let concurrency = concurrency.max(1);
+        let ranges: Vec<Range<usize>> = (0..ids.len())
+            .step_by(chunk_size)
+            .map(|start| start..ids.len().min(start + chunk_size))
+            .collect();
+
+        let duration = batch_duration(ranges.len(), concurrency, self.limiter().is_some());
+        if let Err(err) = self.get_valid_token_for(duration).await {
+            // Each chunk retries the refresh and reports its own error
+            log::warn!("refreshing the access token for a batch failed, {err}");
+        }
+
+        let mut futures: Vec<Option<Pin<Box<Fut>>>> = ranges
+            .iter()
+            .map(|range| Some(Box::pin(request(self.clone(), &ids[range.clone()]))))
+            .collect();
+        let mut results: Vec<Option<Result<Vec<T>>>> = futures.iter().map(|_| None).collect();
+        // Futures are only started once polled, so polling the first `concurrency` unfinished futures
+        // in order bounds the requests in flight
+        std::future::poll_fn(|cx| {
+            let mut in_flight = 0;
+            for (slot, result) in futures.iter_mut().zip(results.iter_mut()) {
+                if in_flight == concurrency {
+                    break;
+                }
+                if let Some(future) = slot {
+                    match future.as_mut().poll(cx) {
+                        Poll::Ready(res) => {
+                            *result = Some(res);
+                            *slot = None;
+                        }
+                        Poll::Pending => in_flight += 1,
+                    }
+                }
+            }
+            if in_flight == 0 {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await;
+
+        BatchResult {
+            chunks: ranges
+                .into_iter()
+                .zip(results)
+                .map(|(range, result)| Chunk {
+                    range,
+                    result: result.expect("Every chunk to be complete"),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// The expected time for a batch of `chunks` requests with `concurrency` in flight at a time,
+/// allowing each to wait to be retried if `limited`, at most [`MAX_BATCH_DURATION`]
+fn batch_duration(chunks: usize, concurrency: usize, limited: bool) -> Duration {
+    let request = match limited {
+        true => REQUEST_DURATION + MAX_RETRY_AFTER * MAX_RETRIES,
+        false => REQUEST_DURATION,
+    };
+    let rounds = chunks.div_ceil(concurrency).max(1);
+    request
+        .saturating_mul(rounds.try_into().unwrap_or(u32::MAX))
+        .min(MAX_BATCH_DURATION)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authorisation::AccessToken;
+    use std::{
+        cell::Cell,
+        rc::Rc,
+        task::{Context, Waker},
+    };
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    /// A future that's pending on its first poll
+    struct YieldOnce(bool);
+
+    impl Future for YieldOnce {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<()> {
+            if self.0 {
+                Poll::Ready(())
+            } else {
+                self.0 = true;
+                Poll::Pending
+            }
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = Box::pin(future);
+        loop {
+            if let Poll::Ready(output) = future
+                .as_mut()
+                .poll(&mut Context::from_waker(Waker::noop()))
+            {
+                return output;
+            }
+        }
+    }
+
+    fn client() -> Client {
+        let mut token = AccessToken::default();
+        token.expires_at += 2 * MAX_BATCH_DURATION; // pretend to be valid
+        Client::new(token)
+    }
+
+    #[wasm_bindgen_test]
+    fn test_batch() {
+        let ids: Vec<String> = (0..7).map(|i| i.to_string()).collect();
+        let in_flight = Rc::new(Cell::new(0));
+        let max_in_flight = Rc::new(Cell::new(0));
+        let res = block_on(client().batch(&ids, 3, 2, |_, chunk| {
+            let in_flight = Rc::clone(&in_flight);
+            let max_in_flight = Rc::clone(&max_in_flight);
+            async move {
+                in_flight.set(in_flight.get() + 1);
+                max_in_flight.set(max_in_flight.get().max(in_flight.get()));
+                YieldOnce(false).await;
+                in_flight.set(in_flight.get() - 1);
+                if chunk.contains(&"4".to_owned()) {
+                    Err(Error::Timeout)
+                } else {
+                    Ok(chunk.iter().map(|id| id.repeat(2)).collect())
+                }
+            }
+        }));
+
+        assert_eq!(max_in_flight.get(), 2);
+        let ranges: Vec<_> = res.chunks.iter().map(|chunk| chunk.range.clone()).collect();
+        assert_eq!(ranges, [0..3, 3..6, 6..7]);
+        assert!(!res.is_ok());
+        let errors: Vec<_> = res.errors().collect();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, &(3..6));
+        assert!(matches!(errors[0].1, Error::Timeout));
+        assert_eq!(
+            res.into_items(),
+            [
+                Some("00".to_owned()),
+                Some("11".to_owned()),
+                Some("22".to_owned()),
+                None,
+                None,
+                None,
+                Some("66".to_owned())
+            ]
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_batch_duration() {
+        assert_eq!(batch_duration(0, 2, false), REQUEST_DURATION);
+        assert_eq!(batch_duration(5, 2, false), 3 * REQUEST_DURATION);
+        assert_eq!(batch_duration(1, 2, true), Duration::from_secs(210));
+        assert_eq!(batch_duration(1000, 1, true), MAX_BATCH_DURATION);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_batch_result() {
+        let res = block_on(client().batch(&[1, 2, 3], 2, 1, |_, chunk| async move {
+            Ok(chunk.iter().map(|i| i * 10).take(1).collect())
+        }));
+        assert!(res.is_ok());
+        // Missing items are padded in place so later items keep their position
+        assert_eq!(res.into_items(), [Some(10), None, Some(30)]);
+
+        let res = block_on(client().batch(&[1, 2, 3], 2, 1, |_, chunk| async move {
+            Ok(chunk.iter().map(|i| i * 10).take(1).collect())
+        }));
+        assert!(matches!(res.into_result(), Err(Error::Body(_))));
+
+        let res =
+            block_on(client().batch(
+                &[1, 2, 3],
+                2,
+                1,
+                |_, chunk| async move { Ok(chunk.to_vec()) },
+            ));
+        assert_eq!(res.into_result().unwrap(), [1, 2, 3]);
+
+        let res = block_on(
+            client().batch::<u8, u8, _, _>(&[], 2, 1, |_, _| async { Err(Error::Cancelled) }),
+        );
+        assert!(res.chunks.is_empty());
+        assert!(res.into_result().unwrap().is_empty());
+    }
+}
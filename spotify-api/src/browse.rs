@@ -0,0 +1,205 @@
+//! Browse API: [categories](https://developer.spotify.com/documentation/web-api/reference/get-categories),
+//! [featured playlists](https://developer.spotify.com/documentation/web-api/reference/get-featured-playlists),
+//! [markets](https://developer.spotify.com/documentation/web-api/reference/get-available-markets) and
+//! [genre seeds](https://developer.spotify.com/documentation/web-api/reference/get-recommendation-genres)
+//!
+//! `country` is an [ISO 3166-1 alpha-2](https://en.wikipedia.org/wiki/ISO_3166-1_alpha-2) country code and
+//! `locale` is an [ISO 639-1](https://en.wikipedia.org/wiki/ISO_639-1) language code joined with a `country`
+//! by an underscore, e.g. `es_MX`.
+
+use crate::{client::Query, model::*, playlists::SimplifiedPlaylist, Client};
+use utils::request::Result;
+
+/// [Category Object](https://developer.spotify.com/documentation/web-api/reference/get-a-category) used to tag items in Spotify
+#[derive(Clone, Eq, PartialEq, Hash, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Category {
+    /// A link to the Web API endpoint returning full details of the category
+    pub href: String,
+    /// The category icon in various sizes
+    pub icons: Vec<Image>,
+    /// The Spotify category ID of the category, e.g. `dinner`
+    pub id: String,
+    /// The name of the category
+    pub name: String,
+}
+
+/// A page of playlists selected by Spotify with an optional message for the user
+#[derive(Clone, Eq, PartialEq, Hash, Debug, serde::Serialize, serde::Deserialize)]
+pub struct BrowsePlaylists {
+    /// The localised message of the selection, e.g. "Good Morning"
+    #[serde(default)]
+    pub message: Option<String>,
+    /// The selected playlists
+    pub playlists: Page<SimplifiedPlaylist>,
+}
+
+impl Client {
+    /// [Get Several Browse Categories](https://developer.spotify.com/documentation/web-api/reference/get-categories)
+    ///
+    /// `limit` is between 1 and 50, defaulting to 20.
+    pub async fn categories(
+        &mut self,
+        country: Option<&str>,
+        locale: Option<&str>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<Page<Category>> {
+        #[derive(serde::Deserialize)]
+        struct Response {
+            categories: Page<Category>,
+        }
+
+        let country = self.resolve_country(country);
+        let locale = self.resolve_locale(locale);
+        let res: Response = self
+            .get(
+                &["browse", "categories"],
+                &Query {
+                    country: country.as_deref(),
+                    locale: locale.as_deref(),
+                    limit,
+                    offset,
+                    ..Default::default()
+                },
+            )
+            .await?;
+        Ok(res.categories)
+    }
+
+    /// [Get Single Browse Category](https://developer.spotify.com/documentation/web-api/reference/get-a-category)
+    pub async fn category(
+        &mut self,
+        id: &str,
+        country: Option<&str>,
+        locale: Option<&str>,
+    ) -> Result<Category> {
+        let country = self.resolve_country(country);
+        let locale = self.resolve_locale(locale);
+        self.get(
+            &["browse", "categories", id],
+            &Query {
+                country: country.as_deref(),
+                locale: locale.as_deref(),
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// [Get Category's Playlists](https://developer.spotify.com/documentation/web-api/reference/get-a-categories-playlists)
+    ///
+    /// `limit` is between 1 and 50, defaulting to 20.
+    pub async fn category_playlists(
+        &mut self,
+        id: &str,
+        country: Option<&str>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<BrowsePlaylists> {
+        let country = self.resolve_country(country);
+        self.get(
+            &["browse", "categories", id, "playlists"],
+            &Query {
+                country: country.as_deref(),
+                limit,
+                offset,
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// [Get Featured Playlists](https://developer.spotify.com/documentation/web-api/reference/get-featured-playlists)
+    ///
+    /// `timestamp` is an [ISO 8601](https://en.wikipedia.org/wiki/ISO_8601) local time, e.g. `2014-10-23T09:00:00`,
+    /// used to tailor the selection to the user's time of day, defaulting to the current UTC time.
+    /// `limit` is between 1 and 50, defaulting to 20.
+    pub async fn featured_playlists(
+        &mut self,
+        country: Option<&str>,
+        locale: Option<&str>,
+        timestamp: Option<&str>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<BrowsePlaylists> {
+        let country = self.resolve_country(country);
+        let locale = self.resolve_locale(locale);
+        self.get(
+            &["browse", "featured-playlists"],
+            &Query {
+                country: country.as_deref(),
+                locale: locale.as_deref(),
+                timestamp,
+                limit,
+                offset,
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// [Get Available Markets](https://developer.spotify.com/documentation/web-api/reference/get-available-markets)
+    ///
+    /// Returns the [ISO 3166-1 alpha-2](https://en.wikipedia.org/wiki/ISO_3166-1_alpha-2) country codes where Spotify is available.
+    pub async fn available_markets(&mut self) -> Result<Vec<String>> {
+        #[derive(serde::Deserialize)]
+        struct Response {
+            markets: Vec<String>,
+        }
+
+        let res: Response = self.get(&["markets"], &Query::default()).await?;
+        Ok(res.markets)
+    }
+
+    /// [Get Available Genre Seeds](https://developer.spotify.com/documentation/web-api/reference/get-recommendation-genres)
+    ///
+    /// Returns the genres that can be used as recommendation seeds.
+    pub async fn available_genre_seeds(&mut self) -> Result<Vec<String>> {
+        #[derive(serde::Deserialize)]
+        struct Response {
+            genres: Vec<String>,
+        }
+
+        let res: Response = self
+            .get(
+                &["recommendations", "available-genre-seeds"],
+                &Query::default(),
+            )
+            .await?;
+        Ok(res.genres)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    fn test_category_deserialize() {
+        let category: Category = serde_json::from_str(
+            r#"{"href":"https://api.spotify.com/v1/browse/categories/dinner","icons":[{"height":274,"url":"https://t.scdn.co/media/original/dinner_1b6506abba0ba52c54e6d695c8571078_274x274.jpg","width":274}],"id":"dinner","name":"Dinner"}"#,
+        )
+        .unwrap();
+        assert_eq!(category.id, "dinner");
+        assert_eq!(category.icons[0].width, Some(274));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_browse_playlists_deserialize() {
+        let playlists: BrowsePlaylists = serde_json::from_str(
+            r#"{"message":"Popular playlists","playlists":{"href":"https://api.spotify.com/v1/browse/featured-playlists?offset=0&limit=20","items":[],"limit":20,"next":null,"offset":0,"previous":null,"total":0}}"#,
+        )
+        .unwrap();
+        assert_eq!(playlists.message.as_deref(), Some("Popular playlists"));
+        assert!(playlists.playlists.items.is_empty());
+
+        let playlists: BrowsePlaylists = serde_json::from_str(
+            r#"{"playlists":{"href":"https://api.spotify.com/v1/browse/categories/dinner/playlists","items":[],"limit":20,"next":null,"offset":0,"previous":null,"total":0}}"#,
+        )
+        .unwrap();
+        assert_eq!(playlists.message, None);
+    }
+}
@@ -0,0 +1,97 @@
+//! Opt-in [ETag](https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/ETag) response cache for the [`Client`](crate::Client)
+//!
+//! Responses to `GET` requests are cached by their full URL along with their `ETag`,
+//! later requests for the same URL send `If-None-Match` and the cached body is served on `304 Not Modified`.
+//! Responses are cached regardless of the user, so a persistent cache should be cleared when the user changes.
+
+use utils::SeralizedStore;
+
+/// The prefix of the keys cached responses are stored under
+const KEY_PREFIX: &str = "spotify-api-etag:";
+
+/// A cached response body and the `ETag` it was served with
+#[derive(
+    Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, serde::Serialize, serde::Deserialize,
+)]
+pub struct CachedResponse {
+    /// The `ETag` header of the response
+    pub etag: String,
+    /// The response body
+    pub body: String,
+}
+
+/// Storage for cached responses keyed by request URL, implemented for every [`SeralizedStore`]
+pub trait ResponseCache {
+    /// Gets the response cached for the `url`
+    fn get(&self, url: &str) -> Option<CachedResponse>;
+
+    /// Caches the `response` for the `url`, replacing any previous response
+    fn insert(&mut self, url: &str, response: CachedResponse);
+
+    /// Removes the response cached for the `url`
+    fn remove(&mut self, url: &str);
+}
+
+/// Store errors are logged and treated as cache misses, since the cache is only an optimisation
+impl<S> ResponseCache for S
+where
+    S: SeralizedStore,
+    S::Error: std::fmt::Display,
+{
+    fn get(&self, url: &str) -> Option<CachedResponse> {
+        SeralizedStore::get(self, &(KEY_PREFIX.to_owned() + url)).unwrap_or_else(|err| {
+            log::warn!("failed to read the cached response for {url}: {err}");
+            None
+        })
+    }
+
+    fn insert(&mut self, url: &str, response: CachedResponse) {
+        if let Err(err) = SeralizedStore::insert(self, KEY_PREFIX.to_owned() + url, response) {
+            log::warn!("failed to cache the response for {url}: {err}");
+        }
+    }
+
+    fn remove(&mut self, url: &str) {
+        let removed: Result<Option<CachedResponse>, _> =
+            SeralizedStore::remove(self, &(KEY_PREFIX.to_owned() + url));
+        if let Err(err) = removed {
+            log::warn!("failed to remove the cached response for {url}: {err}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    fn test_store_cache() {
+        const URL: &str = "https://api.spotify.com/v1/playlists/37i9dQZF1DXcBWIGoYBM5M";
+        let response = CachedResponse {
+            etag: "\"MC0wLTA=\"".to_owned(),
+            body: "{}".to_owned(),
+        };
+        let mut cache = HashMap::<String, String>::new();
+
+        assert_eq!(ResponseCache::get(&cache, URL), None);
+        ResponseCache::insert(&mut cache, URL, response.clone());
+        assert_eq!(ResponseCache::get(&cache, URL), Some(response));
+        assert!(cache.keys().all(|key| key.contains(KEY_PREFIX)));
+
+        ResponseCache::remove(&mut cache, URL);
+        assert_eq!(ResponseCache::get(&cache, URL), None);
+        assert!(cache.is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_corrupt_entry_is_a_miss() {
+        const URL: &str = "https://api.spotify.com/v1/markets";
+        let mut cache = HashMap::<String, String>::new();
+        SeralizedStore::insert(&mut cache, KEY_PREFIX.to_owned() + URL, 42).unwrap();
+        assert_eq!(ResponseCache::get(&cache, URL), None);
+    }
+}
@@ -0,0 +1,189 @@
+//! [Chapters](https://developer.spotify.com/documentation/web-api/reference/get-a-chapter) (audiobook chapters) API
+//!
+//! Chapters share the market restrictions of [audiobooks](crate::audiobooks).
+
+use crate::{
+    audiobooks::SimplifiedAudiobook,
+    client::{not_found_as_none, Query},
+    episodes::ResumePoint,
+    model::*,
+    Client,
+};
+use utils::request::Result;
+
+/// [Chapter Object](https://developer.spotify.com/documentation/web-api/reference/get-a-chapter) with the audiobook it belongs to
+#[derive(Clone, Eq, PartialEq, Hash, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Chapter {
+    /// A URL to a 30 second preview of the chapter, if available
+    pub audio_preview_url: Option<String>,
+    /// The [ISO 3166-1 alpha-2](https://en.wikipedia.org/wiki/ISO_3166-1_alpha-2) country codes the chapter is available in
+    #[serde(default)]
+    pub available_markets: Vec<String>,
+    /// The number of the chapter
+    pub chapter_number: u32,
+    /// A description of the chapter with HTML tags stripped
+    pub description: String,
+    /// A description of the chapter which may contain HTML tags
+    pub html_description: String,
+    /// The chapter length in milliseconds
+    pub duration_ms: u32,
+    /// Whether or not the chapter has explicit content
+    pub explicit: bool,
+    /// External URLs for this chapter
+    pub external_urls: ExternalUrls,
+    /// A link to the Web API endpoint providing full details of the chapter
+    pub href: String,
+    /// The Spotify ID for the chapter
+    pub id: ChapterId,
+    /// The cover art for the chapter in various sizes, widest first
+    pub images: Vec<Image>,
+    /// True if the chapter is playable in the given market
+    #[serde(default)]
+    pub is_playable: Option<bool>,
+    /// The [ISO 639](https://en.wikipedia.org/wiki/ISO_639) codes of the languages used in the chapter
+    pub languages: Vec<String>,
+    /// The name of the chapter
+    pub name: String,
+    /// The date the chapter was first released, e.g. `1981-12-15`
+    pub release_date: String,
+    /// The precision with which `release_date` is known
+    pub release_date_precision: ReleaseDatePrecision,
+    /// The user's most recent position in the chapter
+    #[serde(default)]
+    pub resume_point: Option<ResumePoint>,
+    /// Included when a content restriction is applied
+    #[serde(default)]
+    pub restrictions: Option<Restrictions>,
+    /// The Spotify URI for the chapter
+    pub uri: String,
+    /// The audiobook on which the chapter belongs
+    pub audiobook: SimplifiedAudiobook,
+}
+
+/// [Simplified Chapter Object](https://developer.spotify.com/documentation/web-api/reference/get-audiobook-chapters)
+#[derive(Clone, Eq, PartialEq, Hash, Debug, serde::Serialize, serde::Deserialize)]
+pub struct SimplifiedChapter {
+    /// A URL to a 30 second preview of the chapter, if available
+    pub audio_preview_url: Option<String>,
+    /// The [ISO 3166-1 alpha-2](https://en.wikipedia.org/wiki/ISO_3166-1_alpha-2) country codes the chapter is available in
+    #[serde(default)]
+    pub available_markets: Vec<String>,
+    /// The number of the chapter
+    pub chapter_number: u32,
+    /// A description of the chapter with HTML tags stripped
+    pub description: String,
+    /// A description of the chapter which may contain HTML tags
+    pub html_description: String,
+    /// The chapter length in milliseconds
+    pub duration_ms: u32,
+    /// Whether or not the chapter has explicit content
+    pub explicit: bool,
+    /// External URLs for this chapter
+    pub external_urls: ExternalUrls,
+    /// A link to the Web API endpoint providing full details of the chapter
+    pub href: String,
+    /// The Spotify ID for the chapter
+    pub id: ChapterId,
+    /// The cover art for the chapter in various sizes, widest first
+    pub images: Vec<Image>,
+    /// True if the chapter is playable in the given market
+    #[serde(default)]
+    pub is_playable: Option<bool>,
+    /// The [ISO 639](https://en.wikipedia.org/wiki/ISO_639) codes of the languages used in the chapter
+    pub languages: Vec<String>,
+    /// The name of the chapter
+    pub name: String,
+    /// The date the chapter was first released, e.g. `1981-12-15`
+    pub release_date: String,
+    /// The precision with which `release_date` is known
+    pub release_date_precision: ReleaseDatePrecision,
+    /// The user's most recent position in the chapter
+    #[serde(default)]
+    pub resume_point: Option<ResumePoint>,
+    /// Included when a content restriction is applied
+    #[serde(default)]
+    pub restrictions: Option<Restrictions>,
+    /// The Spotify URI for the chapter
+    pub uri: String,
+}
+
+impl Client {
+    /// [Get a Chapter](https://developer.spotify.com/documentation/web-api/reference/get-a-chapter)
+    ///
+    /// Returns `None` if the chapter can't be found or isn't available in the `market`.
+    pub async fn chapter(
+        &mut self,
+        id: &ChapterId,
+        market: Option<&str>,
+    ) -> Result<Option<Chapter>> {
+        let market = self.resolve_market(market);
+        self.get(
+            &["chapters", id.as_str()],
+            &Query {
+                market: market.as_deref(),
+                ..Default::default()
+            },
+        )
+        .await
+        .map(Some)
+        .or_else(not_found_as_none)
+    }
+
+    /// [Get Several Chapters](https://developer.spotify.com/documentation/web-api/reference/get-several-chapters)
+    ///
+    /// Accepts a maximum of 50 IDs, chapters that can't be found are returned as `None`.
+    pub async fn chapters(
+        &mut self,
+        ids: &[ChapterId],
+        market: Option<&str>,
+    ) -> Result<Vec<Option<Chapter>>> {
+        #[derive(serde::Deserialize)]
+        struct Response {
+            chapters: Vec<Option<Chapter>>,
+        }
+
+        let market = self.resolve_market(market);
+        let res: Response = self
+            .get(
+                &["chapters"],
+                &Query {
+                    ids: Some(&join_ids(ids)),
+                    market: market.as_deref(),
+                    ..Default::default()
+                },
+            )
+            .await?;
+        Ok(res.chapters)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    fn test_simplified_chapter_deserialize() {
+        let chapter: SimplifiedChapter = serde_json::from_str(
+            r#"{"audio_preview_url":null,"available_markets":["US"],"chapter_number":1,"description":"A chapter","html_description":"<p>A chapter</p>","duration_ms":1000,"explicit":false,"external_urls":{"spotify":"https://open.spotify.com/episode/0D5wENdkdwbqlrHoaJ9g29"},"href":"https://api.spotify.com/v1/chapters/0D5wENdkdwbqlrHoaJ9g29","id":"0D5wENdkdwbqlrHoaJ9g29","images":[],"is_playable":false,"languages":["en"],"name":"Chapter 1","release_date":"2020","release_date_precision":"year","resume_point":{"fully_played":true,"resume_position_ms":0},"type":"chapter","uri":"spotify:episode:0D5wENdkdwbqlrHoaJ9g29","restrictions":{"reason":"market"}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            chapter.id,
+            ChapterId::new("0D5wENdkdwbqlrHoaJ9g29").unwrap()
+        );
+        assert_eq!(ChapterId::from_uri(&chapter.uri), Ok(chapter.id.clone()));
+        assert_eq!(chapter.id.uri(), chapter.uri);
+        assert_eq!(chapter.chapter_number, 1);
+        assert_eq!(chapter.is_playable, Some(false));
+        assert_eq!(
+            chapter.restrictions,
+            Some(Restrictions {
+                reason: "market".to_string()
+            })
+        );
+        assert_eq!(chapter.release_date_precision, ReleaseDatePrecision::Year);
+    }
+}
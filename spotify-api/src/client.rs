@@ -1,17 +1,273 @@
 use super::authorisation::*;
+use crate::{cache::*, limiter::*, metrics::*, middleware::*};
 use instant::Duration;
-use utils::request::*;
+use std::{cell::RefCell, rc::Rc};
+use utils::{abort::*, request::*};
+
+/// The expected time required to make a request to one of the typed API endpoints
+pub(crate) const REQUEST_DURATION: Duration = Duration::from_secs(30);
+
+/// Query parameters shared by the typed API endpoints, unset parameters are omitted
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, serde::Serialize)]
+pub(crate) struct Query<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ids: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub market: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fields: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub additional_types: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub q: Option<&'a str>,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub item_type: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_range: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uri: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<&'a str>,
+}
+
+/// Maps a `404 Not Found` error to `None`, e.g. for items that aren't available in the requested market
+pub(crate) fn not_found_as_none<T>(err: Error) -> Result<Option<T>> {
+    match err {
+        Error::Status(StatusError {
+            status: reqwest::StatusCode::NOT_FOUND,
+            ..
+        }) => Ok(None),
+        err => Err(err),
+    }
+}
+
+/// Per request options overriding the defaults of the [`Client`]
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct RequestOptions {
+    /// The time allowed for the request to complete, including any time queued and retrying,
+    /// before it fails with [`Error::Timeout`]
+    pub timeout: Option<Duration>,
+    /// A handle to cancel the request, failing it with [`Error::Cancelled`]
+    pub abort: Option<AbortHandle>,
+}
 
 /// A client that abstracts the need for refreshing the [AccessToken] and authorises each API [request]
-#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+///
+/// Clones share the [`ResponseCache`], [`Middleware`], metrics and [`Limiter`], clients are compared by their [AccessToken] only.
+#[derive(Clone)]
 pub struct Client {
     token: AccessToken,
+    cache: Option<Rc<RefCell<dyn ResponseCache>>>,
+    middleware: Vec<Rc<dyn Middleware>>,
+    metrics: Option<Rc<RefCell<MetricsSnapshot>>>,
+    limiter: Option<Limiter>,
+    market: Option<String>,
+    locale: Option<String>,
+    timeout: Option<Duration>,
+    abort: Option<AbortHandle>,
+}
+
+impl PartialEq for Client {
+    fn eq(&self, other: &Self) -> bool {
+        self.token == other.token
+    }
+}
+
+impl Eq for Client {}
+
+impl PartialOrd for Client {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Client {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.token.cmp(&other.token)
+    }
+}
+
+impl std::hash::Hash for Client {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.token.hash(state);
+    }
+}
+
+impl std::fmt::Debug for Client {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Client")
+            .field("token", &self.token)
+            .field("cache", &self.cache.is_some())
+            .field("middleware", &self.middleware.len())
+            .field("metrics", &self.metrics.is_some())
+            .field("limiter", &self.limiter)
+            .field("market", &self.market)
+            .field("locale", &self.locale)
+            .field("timeout", &self.timeout)
+            .field("abort", &self.abort)
+            .finish()
+    }
 }
 
 impl Client {
     /// Creates a client consuming the [AccessToken]
     pub fn new(token: AccessToken) -> Self {
-        Self { token }
+        Self {
+            token,
+            cache: None,
+            middleware: Vec::new(),
+            metrics: None,
+            limiter: None,
+            market: None,
+            locale: None,
+            timeout: None,
+            abort: None,
+        }
+    }
+
+    /// Sets the default time allowed for each request to complete, see [`RequestOptions::timeout`]
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) -> &mut Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets the default [`AbortHandle`] of each request, see [`RequestOptions::abort`].
+    ///
+    /// Giving a clone of the client its own handle allows aborting all requests made through it,
+    /// e.g. when navigating away from the page that made them.
+    pub fn set_abort_handle(&mut self, abort: Option<AbortHandle>) -> &mut Self {
+        self.abort = abort;
+        self
+    }
+
+    /// The default abort handle, see [`Client::set_abort_handle()`]
+    pub fn abort_handle(&self) -> Option<&AbortHandle> {
+        self.abort.as_ref()
+    }
+
+    /// Sets the default `market` used by endpoints when no market is given, e.g. `AU` or `from_token`.
+    ///
+    /// The market decides which content is available and how tracks are
+    /// [relinked](https://developer.spotify.com/documentation/web-api/concepts/track-relinking).
+    /// Market country codes are also the default `country` of the [browse](crate::browse) endpoints.
+    pub fn set_market(&mut self, market: Option<&str>) -> &mut Self {
+        self.market = market.map(str::to_owned);
+        self
+    }
+
+    /// The default market, see [`Client::set_market()`]
+    pub fn market(&self) -> Option<&str> {
+        self.market.as_deref()
+    }
+
+    /// Sets the default `locale` used by endpoints when no locale is given, e.g. `es_MX`
+    pub fn set_locale(&mut self, locale: Option<&str>) -> &mut Self {
+        self.locale = locale.map(str::to_owned);
+        self
+    }
+
+    /// The default locale, see [`Client::set_locale()`]
+    pub fn locale(&self) -> Option<&str> {
+        self.locale.as_deref()
+    }
+
+    /// Sets the default market to the `country` of the current user.
+    ///
+    /// Requires [`Scopes::UserReadPrivate`], without it no country is returned and the default market is unchanged.
+    pub async fn detect_market(&mut self) -> Result<Option<String>> {
+        let country = self.current_user().await?.country;
+        if let Some(country) = &country {
+            self.set_market(Some(country));
+        }
+        Ok(country)
+    }
+
+    /// Returns the `market`, or the default market if there is none
+    pub(crate) fn resolve_market(&self, market: Option<&str>) -> Option<String> {
+        market.or(self.market()).map(str::to_owned)
+    }
+
+    /// Returns the `country`, or the default market if there is none and it's a country code
+    pub(crate) fn resolve_country(&self, country: Option<&str>) -> Option<String> {
+        country
+            .or(self.market().filter(|market| {
+                market.len() == 2 && market.chars().all(|c| c.is_ascii_uppercase())
+            }))
+            .map(str::to_owned)
+    }
+
+    /// Returns the `locale`, or the default locale if there is none
+    pub(crate) fn resolve_locale(&self, locale: Option<&str>) -> Option<String> {
+        locale.or(self.locale()).map(str::to_owned)
+    }
+
+    /// Limits the number of requests in flight to `max_in_flight`, see [`limiter`](crate::limiter) for more details
+    ///
+    /// Rate limited requests are only retried with a limiter set.
+    pub fn set_limiter(&mut self, max_in_flight: usize) -> &mut Self {
+        self.limiter = Some(Limiter::new(max_in_flight));
+        self
+    }
+
+    /// Returns the [`Limiter`], e.g. to [cancel](Limiter::cancel_queued()) queued requests
+    pub fn limiter(&self) -> Option<&Limiter> {
+        self.limiter.as_ref()
+    }
+
+    /// Records the metrics of each request, see [`metrics`](crate::metrics) for more details
+    pub fn enable_metrics(&mut self) -> &mut Self {
+        if self.metrics.is_none() {
+            self.metrics = Some(Default::default());
+        }
+        self
+    }
+
+    /// Returns a snapshot of the metrics recorded so far, `None` if metrics aren't enabled
+    pub fn metrics(&self) -> Option<MetricsSnapshot> {
+        self.metrics
+            .as_ref()
+            .map(|metrics| metrics.borrow().clone())
+    }
+
+    /// Runs the `middleware` for each request after any previously added middleware
+    pub fn add_middleware<M: Middleware + 'static>(&mut self, middleware: M) -> &mut Self {
+        self.middleware.push(Rc::new(middleware));
+        self
+    }
+
+    /// Caches the responses of `GET` requests to the typed API endpoints in the `cache`,
+    /// see [`cache`](crate::cache) for more details
+    pub fn set_cache<C: ResponseCache + 'static>(&mut self, cache: C) -> &mut Self {
+        self.cache = Some(Rc::new(RefCell::new(cache)));
+        self
+    }
+
+    /// Stops caching responses, the previous cache is kept by any clones
+    pub fn remove_cache(&mut self) -> &mut Self {
+        self.cache = None;
+        self
+    }
+
+    /// Keeps the token of `other`, a clone of this client, if it was refreshed since they were cloned,
+    /// e.g. after making requests with a clone moved into a future
+    pub(crate) fn keep_refreshed_token(&mut self, other: &Client) {
+        if other.token.expires_at > self.token.expires_at {
+            self.token = other.token.clone();
+        }
     }
 
     /// Disposes of the Client and returns the [AccessToken]
@@ -26,20 +282,286 @@ impl Client {
         R: serde::de::DeserializeOwned,
         F: Fn(&reqwest::Client) -> reqwest::RequestBuilder,
     {
-        let token = self.get_valid_token_for(duration).await?;
-        request(&crate::CLIENT, |client| {
-            build_request(client).bearer_auth(token.as_str())
-        })
-        .await
+        self.fetch::<Json<R>, F>(build_request, duration)
+            .await
+            .map(|res| res.body.0)
     }
 
-    async fn get_valid_token_for(&mut self, duration: Duration) -> Result<&AccessToken> {
-        if !self.token.is_valid_for(duration) {
-            self.token = std::mem::take(&mut self.token).refresh().await?;
-            assert!(self.token.is_valid_for(duration))
+    /// Make an authorised request to the API reading any [`ResponseBody`] kind along with the response headers.
+    /// `duration` is the expected time required to make the request.
+    pub async fn fetch<B, F>(&mut self, build_request: F, duration: Duration) -> Result<Response<B>>
+    where
+        B: ResponseBody,
+        F: Fn(&reqwest::Client) -> reqwest::RequestBuilder,
+    {
+        self.fetch_with(build_request, duration, &RequestOptions::default())
+            .await
+    }
+
+    /// Make an authorised request to the API like [`Client::fetch()`] with the `options` overriding the defaults
+    pub async fn fetch_with<B, F>(
+        &mut self,
+        build_request: F,
+        duration: Duration,
+        options: &RequestOptions,
+    ) -> Result<Response<B>>
+    where
+        B: ResponseBody,
+        F: Fn(&reqwest::Client) -> reqwest::RequestBuilder,
+    {
+        let started = instant::Instant::now();
+        let request = self
+            .middleware
+            .iter()
+            .fold(build_request(&crate::CLIENT), |request, middleware| {
+                middleware.on_request(request)
+            });
+        let request = request.build().map_err(Error::Reqwest)?;
+        let info = RequestInfo::from(&request);
+        let abort = options.abort.clone().or_else(|| self.abort.clone());
+        let timeout = options.timeout.or(self.timeout);
+        let mut token_refreshed = false;
+        let mut retries = 0;
+        let intercepted = self
+            .middleware
+            .iter()
+            .find_map(|middleware| middleware.respond(&info));
+        // Refreshed before the request is abortable, so cancelling it can't interrupt the refresh
+        let refreshed = match &intercepted {
+            Some(_) => Ok(false),
+            None => self.refresh_token_for(duration).await,
+        };
+        let res = match intercepted {
+            Some(res) => res.and_then(|res| {
+                Ok(Response {
+                    status: res.status,
+                    headers: res.headers,
+                    body: B::from_body(res.body)?,
+                })
+            }),
+            None => {
+                abortable(
+                    async {
+                        token_refreshed = refreshed?;
+                        let mut permit = match &self.limiter {
+                            Some(limiter) => Some(limiter.acquire().await?),
+                            None => None,
+                        };
+                        let mut request = request;
+                        loop {
+                            let retry = match self.limiter {
+                                Some(_) if retries < MAX_RETRIES => request.try_clone(),
+                                _ => None,
+                            };
+                            match (
+                                self.execute_authorised(request, duration, &mut token_refreshed)
+                                    .await,
+                                retry,
+                            ) {
+                                (Err(Error::Status(err)), Some(retry))
+                                    if err.status == reqwest::StatusCode::TOO_MANY_REQUESTS
+                                        && err
+                                            .retry_after
+                                            .is_some_and(|after| after <= MAX_RETRY_AFTER) =>
+                                {
+                                    log::debug!(
+                                        "{} rate limited, retrying after {:?}",
+                                        info.url,
+                                        err.retry_after
+                                    );
+                                    // Let other requests through while waiting
+                                    drop(permit.take());
+                                    utils::delay(err.retry_after.unwrap_or_default()).await;
+                                    if let Some(limiter) = &self.limiter {
+                                        permit = Some(limiter.acquire().await?);
+                                    }
+                                    request = retry;
+                                    retries += 1;
+                                }
+                                (res, _) => break res,
+                            }
+                        }
+                    },
+                    abort,
+                    timeout,
+                )
+                .await
+            }
+        };
+
+        for middleware in &self.middleware {
+            middleware.on_response(
+                &info,
+                res.as_ref().map(|res| ResponseInfo {
+                    status: res.status,
+                    headers: &res.headers,
+                }),
+            );
+        }
+        if let Some(metrics) = &self.metrics {
+            metrics.borrow_mut().record(RequestRecord {
+                endpoint: Endpoint::new(&info.method, &info.url),
+                status: match &res {
+                    Ok(res) => Some(res.status),
+                    Err(Error::Status(err)) => Some(err.status),
+                    Err(_) => None,
+                },
+                latency: started.elapsed(),
+                retries,
+                token_refreshed,
+            });
         }
+        res
+    }
+
+    /// Authorises the `request` with a token valid for `duration` and sends it,
+    /// setting `token_refreshed` if the token had to be refreshed
+    async fn execute_authorised<B: ResponseBody>(
+        &mut self,
+        mut request: reqwest::Request,
+        duration: Duration,
+        token_refreshed: &mut bool,
+    ) -> Result<Response<B>> {
+        *token_refreshed |= self.refresh_token_for(duration).await?;
+        let token = &self.token;
+        let mut authorization =
+            reqwest::header::HeaderValue::from_str(&format!("Bearer {}", token.as_str()))
+                .expect("Access tokens are visible ASCII");
+        authorization.set_sensitive(true);
+        request
+            .headers_mut()
+            .insert(reqwest::header::AUTHORIZATION, authorization);
+        execute(&crate::CLIENT, request).await
+    }
+
+    /// Make an authorised request to the API which has no response body.
+    /// `duration` is the expected time required to make the request.
+    pub(crate) async fn request_no_content<F>(
+        &mut self,
+        build_request: F,
+        duration: Duration,
+    ) -> Result<()>
+    where
+        F: Fn(&reqwest::Client) -> reqwest::RequestBuilder,
+    {
+        self.fetch::<(), F>(build_request, duration)
+            .await
+            .map(|res| res.body)
+    }
+
+    /// Returns if the [AccessToken] was granted the `scope`
+    pub fn has_scope(&self, scope: Scopes) -> bool {
+        self.token.scope.contains(&scope)
+    }
+
+    /// Make an authorised `GET` request to the API endpoint at `path` with the `query` parameters,
+    /// revalidating the cached response if there is a [`ResponseCache`]
+    pub(crate) async fn get<R, Q>(&mut self, path: &[&str], query: &Q) -> Result<R>
+    where
+        R: serde::de::DeserializeOwned,
+        Q: serde::Serialize + ?Sized,
+    {
+        let Some(cache) = self.cache.clone() else {
+            return self.send(reqwest::Method::GET, path, query).await;
+        };
+        let url = crate::ENDPOINT.with_path(path);
+        let key = crate::CLIENT
+            .get(url.as_str())
+            .query(query)
+            .build()
+            .map_err(Error::Reqwest)?
+            .url()
+            .to_string();
+        let cached = cache.borrow().get(&key);
+
+        let res: Response<String> = self
+            .fetch(
+                |client| {
+                    let request = client
+                        .get(url.as_str())
+                        .query(query)
+                        .header(reqwest::header::ACCEPT, "application/json");
+                    match &cached {
+                        Some(cached) => {
+                            request.header(reqwest::header::IF_NONE_MATCH, cached.etag.as_str())
+                        }
+                        None => request,
+                    }
+                },
+                REQUEST_DURATION,
+            )
+            .await?;
+        let body = match cached {
+            Some(cached) if res.status == reqwest::StatusCode::NOT_MODIFIED => cached.body,
+            _ => {
+                if let Some(etag) = res.header(reqwest::header::ETAG) {
+                    cache.borrow_mut().insert(
+                        &key,
+                        CachedResponse {
+                            etag: etag.to_owned(),
+                            body: res.body.clone(),
+                        },
+                    );
+                }
+                res.body
+            }
+        };
+        Json::from_body(body.into_bytes()).map(|Json(body)| body)
+    }
+
+    /// Make an authorised request to the API endpoint at `path` with the `query` parameters
+    pub(crate) async fn send<R, Q>(
+        &mut self,
+        method: reqwest::Method,
+        path: &[&str],
+        query: &Q,
+    ) -> Result<R>
+    where
+        R: serde::de::DeserializeOwned,
+        Q: serde::Serialize + ?Sized,
+    {
+        let url = crate::ENDPOINT.with_path(path);
+        self.request(
+            |client| client.request(method.clone(), url.as_str()).query(query),
+            REQUEST_DURATION,
+        )
+        .await
+    }
+
+    /// Make an authorised request without a response body to the API endpoint at `path` with the `query` parameters
+    pub(crate) async fn send_no_content<Q>(
+        &mut self,
+        method: reqwest::Method,
+        path: &[&str],
+        query: &Q,
+    ) -> Result<()>
+    where
+        Q: serde::Serialize + ?Sized,
+    {
+        let url = crate::ENDPOINT.with_path(path);
+        self.request_no_content(
+            |client| client.request(method.clone(), url.as_str()).query(query),
+            REQUEST_DURATION,
+        )
+        .await
+    }
+
+    pub(crate) async fn get_valid_token_for(&mut self, duration: Duration) -> Result<&AccessToken> {
+        self.refresh_token_for(duration).await?;
         Ok(&self.token)
     }
+
+    /// Refreshes the token unless it's valid for `duration`, returns if it was refreshed
+    async fn refresh_token_for(&mut self, duration: Duration) -> Result<bool> {
+        if self.token.is_valid_for(duration) {
+            return Ok(false);
+        }
+        // Replaced only once refreshed, so the refresh token is kept if refreshing fails or is cancelled
+        let token = self.token.clone().refresh().await?;
+        self.token = token;
+        assert!(self.token.is_valid_for(duration));
+        Ok(true)
+    }
 }
 
 #[cfg(test)]
@@ -50,6 +572,310 @@ mod tests {
 
     wasm_bindgen_test_configure!(run_in_browser);
 
+    #[wasm_bindgen_test]
+    fn test_not_found_as_none() {
+        let not_found = Error::Status(StatusError {
+            status: reqwest::StatusCode::NOT_FOUND,
+            ..Default::default()
+        });
+        assert!(matches!(not_found_as_none::<()>(not_found), Ok(None)));
+        assert!(matches!(
+            not_found_as_none::<()>(Error::Timeout),
+            Err(Error::Timeout)
+        ));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_shared_cache() {
+        let mut client = Client::new(AccessToken::default());
+        let clone = client.clone();
+        assert!(clone.cache.is_none());
+
+        client.set_cache(std::collections::HashMap::<String, String>::new());
+        let clone = client.clone();
+        let response = CachedResponse {
+            etag: "\"etag\"".to_owned(),
+            body: "{}".to_owned(),
+        };
+        client
+            .cache
+            .as_ref()
+            .unwrap()
+            .borrow_mut()
+            .insert("url", response.clone());
+        assert_eq!(
+            clone.cache.as_ref().unwrap().borrow().get("url"),
+            Some(response)
+        );
+        assert_eq!(client, clone);
+
+        client.remove_cache();
+        assert!(client.cache.is_none());
+        assert!(clone.cache.is_some());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_defaults() {
+        let mut client = Client::new(AccessToken::default());
+        assert_eq!(client.resolve_market(None), None);
+        assert_eq!(client.resolve_market(Some("AU")).as_deref(), Some("AU"));
+        assert_eq!(client.resolve_country(None), None);
+        assert_eq!(client.resolve_locale(None), None);
+
+        client
+            .set_market(Some("from_token"))
+            .set_locale(Some("es_MX"));
+        assert_eq!(client.resolve_market(None).as_deref(), Some("from_token"));
+        assert_eq!(client.resolve_market(Some("AU")).as_deref(), Some("AU"));
+        assert_eq!(client.resolve_country(None), None);
+        assert_eq!(client.resolve_locale(None).as_deref(), Some("es_MX"));
+        assert_eq!(
+            client.resolve_locale(Some("en_AU")).as_deref(),
+            Some("en_AU")
+        );
+
+        client.set_market(Some("NZ"));
+        assert_eq!(client.resolve_country(None).as_deref(), Some("NZ"));
+        assert_eq!(client.resolve_country(Some("AU")).as_deref(), Some("AU"));
+        assert_eq!(client.clone().market(), Some("NZ"));
+
+        client.set_market(None).set_locale(None);
+        assert_eq!((client.market(), client.locale()), (None, None));
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_metrics() {
+        const TIME_DIFF: Duration = Duration::from_secs(5);
+        let mut invalid_token = AccessToken::default();
+        invalid_token.expires_at += TIME_DIFF; // preted to be valid
+        let mut client = Client::new(invalid_token);
+        assert_eq!(client.metrics(), None);
+
+        client.enable_metrics();
+        let mut clone = client.clone();
+        let _: Result<()> = clone
+            .request_no_content(
+                |client| client.get("https://httpstat.us/404"),
+                TIME_DIFF / 2,
+            )
+            .await;
+        let metrics = client.metrics().expect("Enabled metrics");
+        assert_eq!(metrics.requests(), 1);
+        let (endpoint, metrics) = metrics.endpoints.into_iter().next().unwrap();
+        assert_eq!(endpoint.to_string(), "GET /404");
+        assert_eq!(metrics.errors, 1);
+        assert_eq!(metrics.token_refreshes, 0);
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_limiter() {
+        const TIME_DIFF: Duration = Duration::from_secs(5);
+        let mut invalid_token = AccessToken::default();
+        invalid_token.expires_at += TIME_DIFF; // preted to be valid
+        let mut client = Client::new(invalid_token);
+        assert!(client.limiter().is_none());
+
+        client.set_limiter(1);
+        let clone = client.clone();
+        let permit = clone.limiter().unwrap().acquire().await.unwrap();
+        let request = client.request_no_content(
+            |client| client.get("https://httpstat.us/200"),
+            TIME_DIFF / 2,
+        );
+        clone.limiter().unwrap().cancel_queued();
+        drop(permit);
+        // The request is queued once polled, so is unaffected by the earlier cancellation
+        request.await.expect("A valid response");
+        assert_eq!(clone.limiter().unwrap().in_flight(), 0);
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_timeout_and_abort() {
+        const TIME_DIFF: Duration = Duration::from_secs(5);
+        let mut invalid_token = AccessToken::default();
+        invalid_token.expires_at += TIME_DIFF; // preted to be valid
+        let mut client = Client::new(invalid_token);
+        client.set_limiter(1);
+        // Hold the only permit so requests stay queued
+        let _permit = client.limiter().unwrap().acquire().await.unwrap();
+
+        client.set_timeout(Some(Duration::from_millis(10)));
+        let res = client
+            .request_no_content(
+                |client| client.get("https://httpstat.us/200"),
+                TIME_DIFF / 2,
+            )
+            .await;
+        assert!(matches!(res, Err(Error::Timeout)));
+        assert_eq!(client.limiter().unwrap().queued(), 0);
+
+        let abort = AbortHandle::new();
+        client
+            .set_timeout(None)
+            .set_abort_handle(Some(abort.clone()));
+        abort.abort();
+        let options = RequestOptions {
+            abort: Some(AbortHandle::new()),
+            ..Default::default()
+        };
+        let res = client
+            .fetch_with::<(), _>(
+                |client| client.get("https://httpstat.us/200"),
+                TIME_DIFF / 2,
+                &RequestOptions {
+                    timeout: Some(Duration::from_millis(10)),
+                    ..options.clone()
+                },
+            )
+            .await;
+        assert!(matches!(res, Err(Error::Timeout)));
+        let res = client
+            .request_no_content(
+                |client| client.get("https://httpstat.us/200"),
+                TIME_DIFF / 2,
+            )
+            .await;
+        assert!(matches!(res, Err(Error::Cancelled)));
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_middleware() {
+        #[derive(Default)]
+        struct Recorder {
+            statuses: RefCell<Vec<Option<reqwest::StatusCode>>>,
+        }
+        impl Middleware for Rc<Recorder> {
+            fn on_request(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+                request.header("X-Test", "middleware")
+            }
+
+            fn on_response(
+                &self,
+                request: &RequestInfo,
+                response: std::result::Result<ResponseInfo<'_>, &Error>,
+            ) {
+                assert_eq!(request.method, reqwest::Method::GET);
+                self.statuses
+                    .borrow_mut()
+                    .push(response.ok().map(|response| response.status));
+            }
+        }
+
+        const TIME_DIFF: Duration = Duration::from_secs(5);
+        let mut invalid_token = AccessToken::default();
+        invalid_token.expires_at += TIME_DIFF; // preted to be valid
+        let recorder = Rc::new(Recorder::default());
+        let mut client = Client::new(invalid_token);
+        client.add_middleware(recorder.clone());
+
+        let _: Result<()> = client
+            .request_no_content(
+                |client| client.get("https://httpstat.us/200"),
+                TIME_DIFF / 2,
+            )
+            .await;
+        let _: Result<()> = client
+            .request_no_content(
+                |client| client.get("https://httpstat.us/404"),
+                TIME_DIFF / 2,
+            )
+            .await;
+        assert_eq!(
+            *recorder.statuses.borrow(),
+            vec![Some(reqwest::StatusCode::OK), None]
+        );
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_middleware_respond() {
+        struct Respond(reqwest::StatusCode, &'static str);
+        impl Middleware for Respond {
+            fn respond(&self, _request: &RequestInfo) -> Option<Result<Response<Vec<u8>>>> {
+                Some(if self.0.is_success() {
+                    Ok(Response {
+                        status: self.0,
+                        headers: Default::default(),
+                        body: self.1.as_bytes().to_vec(),
+                    })
+                } else {
+                    Err(Error::Status(StatusError {
+                        status: self.0,
+                        body: Some(self.1.to_owned()),
+                        retry_after: None,
+                    }))
+                })
+            }
+        }
+
+        // Answered without an access token or a request being sent
+        let mut client = Client::new(AccessToken::default());
+        client.enable_metrics();
+        let mut faulty = client.clone();
+        faulty.add_middleware(Respond(
+            reqwest::StatusCode::INTERNAL_SERVER_ERROR,
+            "Server error",
+        ));
+        let res: Result<serde_json::Value> = faulty
+            .request(
+                |client| client.get("https://api.spotify.com/v1/me"),
+                REQUEST_DURATION,
+            )
+            .await;
+        assert!(matches!(
+            res,
+            Err(Error::Status(StatusError {
+                status: reqwest::StatusCode::INTERNAL_SERVER_ERROR,
+                ..
+            }))
+        ));
+
+        let mut cached = client.clone();
+        cached.add_middleware(Respond(reqwest::StatusCode::OK, r#"{"id":"cached"}"#));
+        let res: serde_json::Value = cached
+            .request(
+                |client| client.get("https://api.spotify.com/v1/me"),
+                REQUEST_DURATION,
+            )
+            .await
+            .expect("A cached response");
+        assert_eq!(res["id"], "cached");
+        let metrics = client.metrics().unwrap();
+        assert_eq!(metrics.requests(), 2);
+        // The expired token was never refreshed
+        assert!(metrics
+            .endpoints
+            .values()
+            .all(|metrics| metrics.token_refreshes == 0));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_keep_refreshed_token() {
+        let mut client = Client::new(AccessToken::default());
+        let mut refreshed = client.clone();
+        refreshed.token.expires_at += REQUEST_DURATION;
+        let token = refreshed.token.clone();
+
+        let stale = client.clone();
+        client.keep_refreshed_token(&refreshed);
+        assert_eq!(client.token, token);
+        client.keep_refreshed_token(&stale);
+        assert_eq!(client.token, token);
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_failed_refresh_keeps_token() {
+        let token = AccessToken::default();
+        let mut client = Client::new(token.clone());
+        let res = client
+            .fetch::<(), _>(
+                |client| client.get("https://httpstat.us/200"),
+                Duration::from_secs(1),
+            )
+            .await;
+        assert!(res.is_err());
+        assert_eq!(client.take_token(), token);
+    }
+
     #[wasm_bindgen_test]
     async fn test_request() {
         #[derive(Deserialize, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
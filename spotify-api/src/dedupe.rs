@@ -0,0 +1,616 @@
+//! Duplicate track detection within playlists and the saved library
+//!
+//! A [`DedupeAnalyser`] is given the items of any number of playlists and the saved tracks of the library,
+//! then groups occurrences of the same recording matched by [`MatchKind`].
+//! The resulting [`RemovalPlan`] keeps the first occurrence in each playlist and the earliest saved track,
+//! and is applied with [`Client::apply_removal_plan()`].
+//! Items can only be removed from a playlist by URI, which removes every occurrence,
+//! so a kept occurrence sharing its URI with a removed one is added again at its position
+//! and loses when and by whom it was originally added.
+
+use crate::{
+    batch::MAX_IDS_50,
+    model::*,
+    playlists::{PlayableItem, PlaylistItem, SyncError},
+    tracks::{SavedTrack, Track},
+    Client,
+};
+use instant::Duration;
+use std::collections::{BTreeMap, HashMap};
+
+/// The default difference in duration allowed between [fuzzy](MatchKind::Fuzzy) matches
+pub const DEFAULT_DURATION_TOLERANCE: Duration = Duration::from_secs(3);
+
+/// How two occurrences were matched as the same recording, from the most to least certain
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum MatchKind {
+    /// The same track ID
+    Id,
+    /// A track [relinked](https://developer.spotify.com/documentation/web-api/concepts/track-relinking) from the other's ID
+    Relinked,
+    /// The same ISRC
+    Isrc,
+    /// The same normalised title and first artist, with durations within the tolerance
+    Fuzzy,
+}
+
+/// Where a track occurs
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum Location {
+    /// A playlist, at the position of the item
+    Playlist {
+        /// The ID of the playlist
+        id: PlaylistId,
+        /// The position of the item in the playlist
+        position: u32,
+    },
+    /// The saved library
+    Library,
+}
+
+/// An occurrence of a track in a playlist or the library
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Occurrence {
+    /// Where the track occurs
+    pub location: Location,
+    /// When the track was added, if known
+    pub added_at: Option<String>,
+    /// The track
+    pub track: Track,
+}
+
+/// Occurrences of the same recording
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct DuplicateGroup {
+    /// The least certain match linking the occurrences
+    pub kind: MatchKind,
+    /// The occurrences, in the order they were given to the analyser
+    pub occurrences: Vec<Occurrence>,
+}
+
+/// The items of a playlist to remove
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct PlaylistRemoval {
+    /// The snapshot ID of the version of the playlist that was analysed
+    pub snapshot_id: String,
+    /// The positions of the items to remove
+    pub positions: Vec<u32>,
+    /// The URIs of the playlist's available items once the removal is applied
+    pub remaining: Vec<String>,
+}
+
+/// The duplicates to remove, keeping the first occurrence in each playlist and the earliest saved track
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct RemovalPlan {
+    /// The removals from each playlist with duplicates
+    pub playlists: BTreeMap<PlaylistId, PlaylistRemoval>,
+    /// The saved tracks to remove from the library
+    pub library: Vec<TrackId>,
+}
+
+impl RemovalPlan {
+    /// Returns if there's nothing to remove
+    pub fn is_empty(&self) -> bool {
+        self.playlists.is_empty() && self.library.is_empty()
+    }
+}
+
+/// Finds duplicate tracks in the playlists and library it's given
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct DedupeAnalyser {
+    occurrences: Vec<Occurrence>,
+    playlists: BTreeMap<PlaylistId, (String, Vec<Option<String>>)>,
+    duration_tolerance: Duration,
+}
+
+impl Default for DedupeAnalyser {
+    fn default() -> Self {
+        Self {
+            occurrences: Vec::new(),
+            playlists: BTreeMap::new(),
+            duration_tolerance: DEFAULT_DURATION_TOLERANCE,
+        }
+    }
+}
+
+impl DedupeAnalyser {
+    /// Creates an analyser without any tracks
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the difference in duration allowed between [fuzzy](MatchKind::Fuzzy) matches
+    pub fn set_duration_tolerance(&mut self, tolerance: Duration) -> &mut Self {
+        self.duration_tolerance = tolerance;
+        self
+    }
+
+    /// Adds all the `items` of the playlist's version `snapshot_id`, in order,
+    /// episodes and unavailable items are never duplicates
+    pub fn add_playlist(
+        &mut self,
+        id: &PlaylistId,
+        snapshot_id: &str,
+        items: &[PlaylistItem],
+    ) -> &mut Self {
+        let mut uris = Vec::with_capacity(items.len());
+        for (position, item) in items.iter().enumerate() {
+            uris.push(
+                item.track
+                    .as_ref()
+                    .map(|playable| playable.stored_uri().to_owned()),
+            );
+            if let Some(PlayableItem::Track(track)) = &item.track {
+                self.occurrences.push(Occurrence {
+                    location: Location::Playlist {
+                        id: id.clone(),
+                        position: position as u32,
+                    },
+                    added_at: item.added_at.clone(),
+                    track: track.clone(),
+                });
+            }
+        }
+        self.playlists
+            .insert(id.clone(), (snapshot_id.to_owned(), uris));
+        self
+    }
+
+    /// Adds the saved tracks of the library
+    pub fn add_saved_tracks(&mut self, saved: &[SavedTrack]) -> &mut Self {
+        self.occurrences
+            .extend(saved.iter().map(|saved| Occurrence {
+                location: Location::Library,
+                added_at: Some(saved.added_at.clone()),
+                track: saved.track.clone(),
+            }));
+        self
+    }
+
+    /// Groups the occurrences of the same recording, a group is reported even if its occurrences are in different places
+    pub fn duplicates(&self) -> Vec<DuplicateGroup> {
+        self.groups(MatchKind::Fuzzy)
+    }
+
+    /// Groups the occurrences of the same recording matched at most as uncertainly as `max_kind`
+    fn groups(&self, max_kind: MatchKind) -> Vec<DuplicateGroup> {
+        let mut groups = Groups::new(self.occurrences.len());
+
+        let mut ids: HashMap<&str, usize> = HashMap::new();
+        for (i, occurrence) in self.occurrences.iter().enumerate() {
+            if let Some(id) = &occurrence.track.id {
+                match ids.get(id.as_str()) {
+                    Some(first) => groups.union(*first, i, MatchKind::Id),
+                    None => {
+                        ids.insert(id.as_str(), i);
+                    }
+                }
+            }
+        }
+        if max_kind >= MatchKind::Relinked {
+            let mut linked: HashMap<&str, usize> = HashMap::new();
+            for (i, occurrence) in self.occurrences.iter().enumerate() {
+                let Some(linked_from) = &occurrence.track.linked_from else {
+                    continue;
+                };
+                let id = linked_from.id.as_str();
+                if let Some(original) = ids.get(id) {
+                    groups.union(*original, i, MatchKind::Relinked);
+                }
+                match linked.get(id) {
+                    Some(first) => groups.union(*first, i, MatchKind::Relinked),
+                    None => {
+                        linked.insert(id, i);
+                    }
+                }
+            }
+        }
+
+        if max_kind >= MatchKind::Isrc {
+            let mut isrcs: HashMap<String, usize> = HashMap::new();
+            for (i, occurrence) in self.occurrences.iter().enumerate() {
+                let Some(isrc) = &occurrence.track.external_ids.isrc else {
+                    continue;
+                };
+                match isrcs.get(&isrc.to_uppercase()) {
+                    Some(first) => groups.union(*first, i, MatchKind::Isrc),
+                    None => {
+                        isrcs.insert(isrc.to_uppercase(), i);
+                    }
+                }
+            }
+        }
+
+        if max_kind >= MatchKind::Fuzzy {
+            let mut similar: HashMap<(String, String), Vec<usize>> = HashMap::new();
+            for (i, occurrence) in self.occurrences.iter().enumerate() {
+                let track = &occurrence.track;
+                let title = normalise_title(&track.name);
+                let artist = track
+                    .artists
+                    .first()
+                    .map(|artist| normalise(&artist.name))
+                    .unwrap_or_default();
+                if !title.is_empty() {
+                    similar.entry((title, artist)).or_default().push(i);
+                }
+            }
+            let tolerance = self.duration_tolerance.as_millis();
+            for mut similar in similar.into_values() {
+                similar.sort_by_key(|i| self.occurrences[*i].track.duration_ms);
+                for pair in similar.windows(2) {
+                    let [shorter, longer] = [pair[0], pair[1]]
+                        .map(|i| u128::from(self.occurrences[i].track.duration_ms));
+                    if longer - shorter <= tolerance {
+                        groups.union(pair[0], pair[1], MatchKind::Fuzzy);
+                    }
+                }
+            }
+        }
+
+        let mut duplicates: BTreeMap<usize, DuplicateGroup> = BTreeMap::new();
+        for (i, occurrence) in self.occurrences.iter().enumerate() {
+            let (root, kind) = groups.find(i);
+            if let Some(kind) = kind {
+                duplicates
+                    .entry(root)
+                    .or_insert_with(|| DuplicateGroup {
+                        kind,
+                        occurrences: Vec::new(),
+                    })
+                    .occurrences
+                    .push(occurrence.clone());
+            }
+        }
+        let mut duplicates: Vec<DuplicateGroup> = duplicates.into_values().collect();
+        duplicates.sort_by_key(|group| group.occurrences[0].location.clone());
+        duplicates
+    }
+
+    /// Plans the removal of the duplicates in groups matched at most as uncertainly as `max_kind`,
+    /// e.g. [`MatchKind::Isrc`] to leave fuzzy matches for the user to review
+    pub fn removal_plan(&self, max_kind: MatchKind) -> RemovalPlan {
+        let mut plan = RemovalPlan::default();
+        for group in self.groups(max_kind) {
+            let mut kept: Vec<&Location> = Vec::new();
+            let mut library: Vec<&Occurrence> = Vec::new();
+            for occurrence in &group.occurrences {
+                match &occurrence.location {
+                    Location::Playlist { id, position } => {
+                        let is_duplicate = kept.iter().any(|location| {
+                            matches!(location, Location::Playlist { id: kept_id, .. } if kept_id == id)
+                        });
+                        if is_duplicate {
+                            plan.playlists
+                                .entry(id.clone())
+                                .or_default()
+                                .positions
+                                .push(*position);
+                        } else {
+                            kept.push(&occurrence.location);
+                        }
+                    }
+                    Location::Library => library.push(occurrence),
+                }
+            }
+            // Saved tracks without IDs can't be removed through the API
+            library.retain(|occurrence| occurrence.track.id.is_some());
+            library.sort_by(|l, r| l.added_at.cmp(&r.added_at));
+            plan.library.extend(
+                library
+                    .into_iter()
+                    .skip(1)
+                    .filter_map(|occurrence| occurrence.track.id.clone()),
+            );
+        }
+
+        for (id, removal) in &mut plan.playlists {
+            removal.positions.sort_unstable();
+            let (snapshot_id, uris) = &self.playlists[id];
+            removal.snapshot_id = snapshot_id.clone();
+            removal.remaining = uris
+                .iter()
+                .enumerate()
+                .filter(|(position, _)| {
+                    removal
+                        .positions
+                        .binary_search(&(*position as u32))
+                        .is_err()
+                })
+                .filter_map(|(_, uri)| uri.clone())
+                .collect();
+        }
+        plan
+    }
+}
+
+/// Lowercases `text` and collapses everything but letters and digits into single spaces
+fn normalise(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+/// Normalises a track `title` without version details such as `(Remastered 2011)`, `[Live]` or `- Radio Edit`
+fn normalise_title(title: &str) -> String {
+    let mut stripped = String::with_capacity(title.len());
+    let mut depth = 0usize;
+    for c in title.chars() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth = depth.saturating_sub(1),
+            c if depth == 0 => stripped.push(c),
+            _ => {}
+        }
+    }
+    let stripped = stripped.split(" - ").next().unwrap_or_default();
+    normalise(stripped)
+}
+
+/// Disjoint sets of occurrence indices, tracking the least certain match joining each set
+struct Groups {
+    parents: Vec<usize>,
+    kinds: Vec<Option<MatchKind>>,
+}
+
+impl Groups {
+    fn new(len: usize) -> Self {
+        Self {
+            parents: (0..len).collect(),
+            kinds: vec![None; len],
+        }
+    }
+
+    /// Returns the root of the set of `i` and the kind of match joining it, `None` if it's alone
+    fn find(&mut self, mut i: usize) -> (usize, Option<MatchKind>) {
+        while self.parents[i] != i {
+            self.parents[i] = self.parents[self.parents[i]];
+            i = self.parents[i];
+        }
+        (i, self.kinds[i])
+    }
+
+    fn union(&mut self, l: usize, r: usize, kind: MatchKind) {
+        let ((l, l_kind), (r, r_kind)) = (self.find(l), self.find(r));
+        if l != r {
+            let (root, child) = (l.min(r), l.max(r));
+            self.parents[child] = root;
+            self.kinds[root] = l_kind.max(r_kind).max(Some(kind));
+        }
+    }
+}
+
+impl Client {
+    /// Applies the removal `plan`, syncing each playlist to its remaining items and removing saved tracks
+    ///
+    /// Requires [`Scopes::PlaylistModifyPublic`](crate::authorisation::Scopes::PlaylistModifyPublic)
+    /// or [`Scopes::PlaylistModifyPrivate`](crate::authorisation::Scopes::PlaylistModifyPrivate) for playlists,
+    /// and [`Scopes::UserLibraryModify`](crate::authorisation::Scopes::UserLibraryModify) for the library.
+    /// Each playlist is synced to its remaining items, as removing a URI would remove every occurrence of it,
+    /// so kept items sharing a URI with a removed duplicate are added again as new items,
+    /// failing with [`SyncError::Conflict`] if it was modified since it was analysed so positions still refer to the same items.
+    pub async fn apply_removal_plan(&mut self, plan: &RemovalPlan) -> Result<(), SyncError> {
+        for (id, removal) in &plan.playlists {
+            self.sync_playlist_snapshot(id, &removal.snapshot_id, &removal.remaining)
+                .await?;
+        }
+        for ids in plan.library.chunks(MAX_IDS_50) {
+            self.remove_saved_tracks(ids)
+                .await
+                .map_err(SyncError::Request)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        playlists::{diff, Operation},
+        tracks::tests::track,
+    };
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    const PLAYLIST: &str = "37i9dQZF1DXcBWIGoYBM5M";
+    const SNAPSHOT: &str = "AAAAB8C+GgIU3xUgVV1Gj5reh6xa3wKE";
+
+    fn item(track: Track) -> PlaylistItem {
+        PlaylistItem {
+            added_at: None,
+            added_by: None,
+            is_local: false,
+            track: Some(PlayableItem::Track(track)),
+        }
+    }
+
+    fn saved(track: Track, added_at: &str) -> SavedTrack {
+        SavedTrack {
+            added_at: added_at.to_owned(),
+            track,
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn test_normalise_title() {
+        assert_eq!(normalise_title("Hey Jude - Remastered 2015"), "hey jude");
+        assert_eq!(
+            normalise_title("Don't Stop Me Now (Live) [2011 Mix]"),
+            "don t stop me now"
+        );
+        assert_eq!(normalise_title("  ÁBC!!  "), "ábc");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_duplicates() {
+        let original = track("11dFghVXANMlKmJXsNCbNl", "Song", "Artist", "USUM71703861");
+        let mut relinked = track("6kLCHFM39wkFjOuyPGLGeQ", "Song", "Other", "GBAAA0000001");
+        relinked.linked_from = Some(crate::tracks::LinkedTrack {
+            external_urls: ExternalUrls::default(),
+            href: String::new(),
+            id: TrackId::new("11dFghVXANMlKmJXsNCbNl").unwrap(),
+            uri: "spotify:track:11dFghVXANMlKmJXsNCbNl".to_owned(),
+        });
+        let isrc = track("2up3OPMp9Tb4dAKM2erWXQ", "Song", "Someone", "usum71703861");
+        let mut fuzzy = track(
+            "0TnOYISbd1XYRBk9myaseg",
+            "Other (Live)",
+            "Band",
+            "GBAAA0000002",
+        );
+        fuzzy.duration_ms += 2000;
+        let other = track(
+            "4aawyAB9vmqN3uQ7FjRGTy",
+            "Other - Remastered",
+            "Band",
+            "GBAAA0000003",
+        );
+        let unique = track("18yVqkdbdRvS24c0Ilj2ci", "Unique", "Band", "GBAAA0000004");
+
+        let id = PlaylistId::new(PLAYLIST).unwrap();
+        let mut analyser = DedupeAnalyser::new();
+        analyser
+            .add_playlist(
+                &id,
+                SNAPSHOT,
+                &[
+                    item(original.clone()),
+                    item(unique.clone()),
+                    item(original.clone()),
+                    item(fuzzy.clone()),
+                    item(other),
+                ],
+            )
+            .add_saved_tracks(&[
+                saved(relinked, "2024-01-01T00:00:00Z"),
+                saved(isrc, "2023-01-01T00:00:00Z"),
+                saved(unique, "2022-01-01T00:00:00Z"),
+            ]);
+
+        let duplicates = analyser.duplicates();
+        let kinds: Vec<(MatchKind, usize)> = duplicates
+            .iter()
+            .map(|group| (group.kind, group.occurrences.len()))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                (MatchKind::Isrc, 4),
+                (MatchKind::Id, 2),
+                (MatchKind::Fuzzy, 2)
+            ]
+        );
+        assert_eq!(duplicates[1].occurrences[1].location, Location::Library);
+
+        analyser.set_duration_tolerance(Duration::from_secs(1));
+        assert_eq!(analyser.duplicates().len(), 2);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_removal_plan() {
+        let song = track("11dFghVXANMlKmJXsNCbNl", "Song", "Artist", "USUM71703861");
+        let same = track("2up3OPMp9Tb4dAKM2erWXQ", "Song", "Artist", "USUM71703861");
+        let similar = track(
+            "0TnOYISbd1XYRBk9myaseg",
+            "Song (Live)",
+            "Artist",
+            "GBAAA0000002",
+        );
+        let other = track("18yVqkdbdRvS24c0Ilj2ci", "Other", "Band", "GBAAA0000004");
+        let mut relinked = track("6kLCHFM39wkFjOuyPGLGeQ", "Relinked", "Band", "GBAAA0000005");
+        relinked.linked_from = Some(crate::tracks::LinkedTrack {
+            external_urls: ExternalUrls::default(),
+            href: String::new(),
+            id: TrackId::new("4aawyAB9vmqN3uQ7FjRGTy").unwrap(),
+            uri: "spotify:track:4aawyAB9vmqN3uQ7FjRGTy".to_owned(),
+        });
+
+        let id = PlaylistId::new(PLAYLIST).unwrap();
+        let mut analyser = DedupeAnalyser::new();
+        analyser
+            .add_playlist(
+                &id,
+                SNAPSHOT,
+                &[
+                    item(song.clone()),
+                    item(relinked),
+                    item(other.clone()),
+                    item(same.clone()),
+                    item(similar.clone()),
+                    PlaylistItem {
+                        track: None,
+                        ..item(other.clone())
+                    },
+                ],
+            )
+            .add_saved_tracks(&[
+                saved(same, "2024-01-01T00:00:00Z"),
+                saved(song, "2023-01-01T00:00:00Z"),
+                saved(other, "2022-01-01T00:00:00Z"),
+            ]);
+
+        let plan = analyser.removal_plan(MatchKind::Isrc);
+        assert_eq!(
+            plan.library,
+            [TrackId::new("2up3OPMp9Tb4dAKM2erWXQ").unwrap()]
+        );
+        assert_eq!(
+            plan.playlists[&id],
+            PlaylistRemoval {
+                snapshot_id: SNAPSHOT.to_owned(),
+                positions: vec![3],
+                remaining: vec![
+                    "spotify:track:11dFghVXANMlKmJXsNCbNl".to_owned(),
+                    // The URI stored in the playlist rather than the relinked track
+                    "spotify:track:4aawyAB9vmqN3uQ7FjRGTy".to_owned(),
+                    "spotify:track:18yVqkdbdRvS24c0Ilj2ci".to_owned(),
+                    "spotify:track:0TnOYISbd1XYRBk9myaseg".to_owned(),
+                ],
+            }
+        );
+
+        let plan = analyser.removal_plan(MatchKind::Fuzzy);
+        assert_eq!(plan.playlists[&id].positions, [3, 4]);
+        assert!(analyser.removal_plan(MatchKind::Id).is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_removal_readds_kept_items() {
+        let song = track("11dFghVXANMlKmJXsNCbNl", "Song", "Artist", "USUM71703861");
+        let other = track("18yVqkdbdRvS24c0Ilj2ci", "Other", "Band", "GBAAA0000004");
+        let id = PlaylistId::new(PLAYLIST).unwrap();
+        let mut analyser = DedupeAnalyser::new();
+        analyser.add_playlist(
+            &id,
+            SNAPSHOT,
+            &[item(song.clone()), item(other), item(song)],
+        );
+        let removal = &analyser.removal_plan(MatchKind::Id).playlists[&id];
+        assert_eq!(removal.positions, [2]);
+
+        // The duplicate can only be removed with every occurrence of its URI,
+        // so the first occurrence is added back as a new item
+        let song = "spotify:track:11dFghVXANMlKmJXsNCbNl".to_owned();
+        let current = [
+            song.clone(),
+            "spotify:track:18yVqkdbdRvS24c0Ilj2ci".to_owned(),
+            song.clone(),
+        ];
+        assert_eq!(
+            diff(&current, &removal.remaining),
+            [
+                Operation::Remove {
+                    uris: vec![song.clone()]
+                },
+                Operation::Add {
+                    position: 0,
+                    uris: vec![song]
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,237 @@
+//! [Episodes](https://developer.spotify.com/documentation/web-api/reference/get-an-episode) (podcast episodes) API
+
+use crate::{client::Query, model::*, shows::SimplifiedShow, Client};
+use utils::request::Result;
+
+/// The user's most recent position in an episode
+///
+/// Only available with [`Scopes::UserReadPlaybackPosition`](crate::authorisation::Scopes::UserReadPlaybackPosition)
+#[derive(
+    Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, serde::Serialize, serde::Deserialize,
+)]
+pub struct ResumePoint {
+    /// Whether or not the episode has been fully played by the user
+    pub fully_played: bool,
+    /// The user's most recent position in the episode in milliseconds
+    pub resume_position_ms: u32,
+}
+
+/// [Episode Object](https://developer.spotify.com/documentation/web-api/reference/get-an-episode) with the show it belongs to
+#[derive(Clone, Eq, PartialEq, Hash, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Episode {
+    /// A URL to a 30 second preview of the episode, if available
+    pub audio_preview_url: Option<String>,
+    /// A description of the episode with HTML tags stripped
+    pub description: String,
+    /// A description of the episode which may contain HTML tags
+    pub html_description: String,
+    /// The episode length in milliseconds
+    pub duration_ms: u32,
+    /// Whether or not the episode has explicit content
+    pub explicit: bool,
+    /// External URLs for this episode
+    pub external_urls: ExternalUrls,
+    /// A link to the Web API endpoint providing full details of the episode
+    pub href: String,
+    /// The Spotify ID for the episode
+    pub id: EpisodeId,
+    /// The cover art for the episode in various sizes, widest first
+    pub images: Vec<Image>,
+    /// True if the episode is hosted outside of Spotify's CDN
+    pub is_externally_hosted: bool,
+    /// True if the episode is playable in the given market
+    #[serde(default)]
+    pub is_playable: Option<bool>,
+    /// The [ISO 639](https://en.wikipedia.org/wiki/ISO_639) codes of the languages used in the episode
+    pub languages: Vec<String>,
+    /// The name of the episode
+    pub name: String,
+    /// The date the episode was first released, e.g. `1981-12-15`
+    pub release_date: String,
+    /// The precision with which `release_date` is known
+    pub release_date_precision: ReleaseDatePrecision,
+    /// The user's most recent position in the episode
+    #[serde(default)]
+    pub resume_point: Option<ResumePoint>,
+    /// Included when a content restriction is applied
+    #[serde(default)]
+    pub restrictions: Option<Restrictions>,
+    /// The Spotify URI for the episode
+    pub uri: String,
+    /// The show on which the episode belongs
+    pub show: SimplifiedShow,
+}
+
+/// [Simplified Episode Object](https://developer.spotify.com/documentation/web-api/reference/get-a-shows-episodes)
+#[derive(Clone, Eq, PartialEq, Hash, Debug, serde::Serialize, serde::Deserialize)]
+pub struct SimplifiedEpisode {
+    /// A URL to a 30 second preview of the episode, if available
+    pub audio_preview_url: Option<String>,
+    /// A description of the episode with HTML tags stripped
+    pub description: String,
+    /// A description of the episode which may contain HTML tags
+    pub html_description: String,
+    /// The episode length in milliseconds
+    pub duration_ms: u32,
+    /// Whether or not the episode has explicit content
+    pub explicit: bool,
+    /// External URLs for this episode
+    pub external_urls: ExternalUrls,
+    /// A link to the Web API endpoint providing full details of the episode
+    pub href: String,
+    /// The Spotify ID for the episode
+    pub id: EpisodeId,
+    /// The cover art for the episode in various sizes, widest first
+    pub images: Vec<Image>,
+    /// True if the episode is hosted outside of Spotify's CDN
+    pub is_externally_hosted: bool,
+    /// True if the episode is playable in the given market
+    #[serde(default)]
+    pub is_playable: Option<bool>,
+    /// The [ISO 639](https://en.wikipedia.org/wiki/ISO_639) codes of the languages used in the episode
+    pub languages: Vec<String>,
+    /// The name of the episode
+    pub name: String,
+    /// The date the episode was first released, e.g. `1981-12-15`
+    pub release_date: String,
+    /// The precision with which `release_date` is known
+    pub release_date_precision: ReleaseDatePrecision,
+    /// The user's most recent position in the episode
+    #[serde(default)]
+    pub resume_point: Option<ResumePoint>,
+    /// Included when a content restriction is applied
+    #[serde(default)]
+    pub restrictions: Option<Restrictions>,
+    /// The Spotify URI for the episode
+    pub uri: String,
+}
+
+/// An episode saved in the current user's library
+#[derive(Clone, Eq, PartialEq, Hash, Debug, serde::Serialize, serde::Deserialize)]
+pub struct SavedEpisode {
+    /// The [ISO 8601](https://en.wikipedia.org/wiki/ISO_8601) UTC timestamp of when the episode was saved
+    pub added_at: String,
+    /// The saved episode
+    pub episode: Episode,
+}
+
+impl Client {
+    /// [Get Episode](https://developer.spotify.com/documentation/web-api/reference/get-an-episode)
+    ///
+    /// If `market` is given, only content available in that market is returned.
+    /// The resume point is only included with [`Scopes::UserReadPlaybackPosition`](crate::authorisation::Scopes::UserReadPlaybackPosition).
+    pub async fn episode(&mut self, id: &EpisodeId, market: Option<&str>) -> Result<Episode> {
+        let market = self.resolve_market(market);
+        self.get(
+            &["episodes", id.as_str()],
+            &Query {
+                market: market.as_deref(),
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// [Get Several Episodes](https://developer.spotify.com/documentation/web-api/reference/get-multiple-episodes)
+    ///
+    /// Accepts a maximum of 50 IDs, episodes that can't be found are returned as `None`.
+    pub async fn episodes(
+        &mut self,
+        ids: &[EpisodeId],
+        market: Option<&str>,
+    ) -> Result<Vec<Option<Episode>>> {
+        #[derive(serde::Deserialize)]
+        struct Response {
+            episodes: Vec<Option<Episode>>,
+        }
+
+        let market = self.resolve_market(market);
+        let res: Response = self
+            .get(
+                &["episodes"],
+                &Query {
+                    ids: Some(&join_ids(ids)),
+                    market: market.as_deref(),
+                    ..Default::default()
+                },
+            )
+            .await?;
+        Ok(res.episodes)
+    }
+
+    /// [Get User's Saved Episodes](https://developer.spotify.com/documentation/web-api/reference/get-users-saved-episodes)
+    ///
+    /// Requires [`Scopes::UserLibraryRead`](crate::authorisation::Scopes::UserLibraryRead),
+    /// `limit` is between 1 and 50, defaulting to 20.
+    pub async fn saved_episodes(
+        &mut self,
+        market: Option<&str>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<Page<SavedEpisode>> {
+        let market = self.resolve_market(market);
+        self.get(
+            &["me", "episodes"],
+            &Query {
+                market: market.as_deref(),
+                limit,
+                offset,
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// [Check User's Saved Episodes](https://developer.spotify.com/documentation/web-api/reference/check-users-saved-episodes)
+    ///
+    /// Requires [`Scopes::UserLibraryRead`](crate::authorisation::Scopes::UserLibraryRead),
+    /// accepts a maximum of 50 IDs and returns whether each episode is saved in the same order.
+    pub async fn check_saved_episodes(&mut self, ids: &[EpisodeId]) -> Result<Vec<bool>> {
+        self.get(
+            &["me", "episodes", "contains"],
+            &Query {
+                ids: Some(&join_ids(ids)),
+                ..Default::default()
+            },
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    const SIMPLIFIED_EPISODE: &str = r#"{"audio_preview_url":null,"description":"An episode","html_description":"<p>An episode</p>","duration_ms":1686230,"explicit":false,"external_urls":{"spotify":"https://open.spotify.com/episode/512ojhOuo1ktJprKbVcKyQ"},"href":"https://api.spotify.com/v1/episodes/512ojhOuo1ktJprKbVcKyQ","id":"512ojhOuo1ktJprKbVcKyQ","images":[],"is_externally_hosted":false,"is_playable":true,"language":"en","languages":["en"],"name":"Episode","release_date":"2023-01","release_date_precision":"month","resume_point":{"fully_played":false,"resume_position_ms":60000},"type":"episode","uri":"spotify:episode:512ojhOuo1ktJprKbVcKyQ"}"#;
+
+    #[wasm_bindgen_test]
+    fn test_simplified_episode_deserialize() {
+        let episode: SimplifiedEpisode = serde_json::from_str(SIMPLIFIED_EPISODE).unwrap();
+        assert_eq!(
+            episode.id,
+            EpisodeId::new("512ojhOuo1ktJprKbVcKyQ").unwrap()
+        );
+        assert_eq!(episode.release_date_precision, ReleaseDatePrecision::Month);
+        assert_eq!(
+            episode.resume_point,
+            Some(ResumePoint {
+                fully_played: false,
+                resume_position_ms: 60000
+            })
+        );
+        assert_eq!(episode.restrictions, None);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_episode_without_resume_point_deserialize() {
+        let episode: SimplifiedEpisode = serde_json::from_str(&SIMPLIFIED_EPISODE.replace(
+            r#""resume_point":{"fully_played":false,"resume_position_ms":60000},"#,
+            "",
+        ))
+        .unwrap();
+        assert_eq!(episode.resume_point, None);
+    }
+}
@@ -16,7 +16,29 @@ lazy_static! {
 
 pub mod authorisation;
 mod client;
-pub use client::Client;
+pub use client::{Client, RequestOptions};
+pub mod albums;
+pub mod artists;
+pub mod audio;
+pub mod audiobooks;
+pub mod batch;
+pub mod browse;
+pub mod cache;
+pub mod chapters;
+pub mod dedupe;
+pub mod episodes;
+pub mod limiter;
+pub mod metrics;
+pub mod middleware;
+pub mod model;
+pub mod player;
+pub mod playlists;
+pub mod recommendations;
+pub mod shows;
+pub mod smart_playlists;
+pub mod stats;
+pub mod tracks;
+pub mod users;
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,271 @@
+//! Concurrency limiter with a fair request queue for the [`Client`](crate::Client)
+//!
+//! Once [set](crate::Client::set_limiter()) at most `max_in_flight` requests are sent at a time,
+//! any further requests wait in first come, first served order until a request completes.
+//! Requests that receive `429 Too Many Requests` are retried after their `Retry-After` duration,
+//! up to [`MAX_RETRIES`] times, unless it's longer than [`MAX_RETRY_AFTER`].
+//! Waiting requests give up their place so other requests can be sent meanwhile.
+//! Without a limiter, rate limited requests fail without being retried.
+
+use std::{
+    cell::RefCell,
+    collections::{HashSet, VecDeque},
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+use utils::request::Error;
+
+/// The maximum number of times a rate limited request is retried
+pub const MAX_RETRIES: u32 = 3;
+
+/// The longest `Retry-After` duration waited for, longer durations fail the request with the `429 Too Many Requests` error
+pub const MAX_RETRY_AFTER: instant::Duration = instant::Duration::from_secs(60);
+
+#[derive(Debug)]
+struct Waiter {
+    ticket: u64,
+    waker: Waker,
+}
+
+#[derive(Debug)]
+struct State {
+    max_in_flight: usize,
+    in_flight: usize,
+    next_ticket: u64,
+    queue: VecDeque<Waiter>,
+    cancelled: HashSet<u64>,
+}
+
+impl State {
+    /// Returns the waker of the first queued request if it can be sent
+    fn next_waker(&self) -> Option<Waker> {
+        if self.in_flight < self.max_in_flight {
+            self.queue.front().map(|waiter| waiter.waker.clone())
+        } else {
+            None
+        }
+    }
+}
+
+/// A shared limit of requests in flight, clones share the same limit and queue
+#[derive(Clone, Debug)]
+pub struct Limiter(Rc<RefCell<State>>);
+
+impl Limiter {
+    /// Creates a limiter allowing `max_in_flight` requests at a time, at least 1
+    pub fn new(max_in_flight: usize) -> Self {
+        Self(Rc::new(RefCell::new(State {
+            max_in_flight: max_in_flight.max(1),
+            in_flight: 0,
+            next_ticket: 0,
+            queue: VecDeque::new(),
+            cancelled: HashSet::new(),
+        })))
+    }
+
+    /// Waits for a turn to send a request, the turn lasts until the [`Permit`] is dropped
+    ///
+    /// Dropping the returned future leaves the queue.
+    pub fn acquire(&self) -> Acquire {
+        Acquire {
+            limiter: self.clone(),
+            ticket: None,
+        }
+    }
+
+    /// The maximum number of requests in flight
+    pub fn max_in_flight(&self) -> usize {
+        self.0.borrow().max_in_flight
+    }
+
+    /// The number of requests in flight
+    pub fn in_flight(&self) -> usize {
+        self.0.borrow().in_flight
+    }
+
+    /// The number of requests waiting for a turn
+    pub fn queued(&self) -> usize {
+        self.0.borrow().queue.len()
+    }
+
+    /// Cancels all requests waiting for a turn with [`Error::Cancelled`], requests in flight are unaffected
+    pub fn cancel_queued(&self) {
+        let wakers = {
+            let mut state = self.0.borrow_mut();
+            let cancelled: Vec<Waiter> = state.queue.drain(..).collect();
+            state
+                .cancelled
+                .extend(cancelled.iter().map(|waiter| waiter.ticket));
+            cancelled
+        };
+        wakers.into_iter().for_each(|waiter| waiter.waker.wake());
+    }
+
+    fn wake_next(&self) {
+        let waker = self.0.borrow().next_waker();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// A turn to send a request, the next queued request is sent once it's dropped
+#[derive(Debug)]
+pub struct Permit(Limiter);
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        self.0 .0.borrow_mut().in_flight -= 1;
+        self.0.wake_next();
+    }
+}
+
+/// Future of a [`Permit`] from [`Limiter::acquire()`]
+#[derive(Debug)]
+pub struct Acquire {
+    limiter: Limiter,
+    ticket: Option<u64>,
+}
+
+impl Future for Acquire {
+    type Output = Result<Permit, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let mut state = this.limiter.0.borrow_mut();
+        match this.ticket {
+            None if state.queue.is_empty() && state.in_flight < state.max_in_flight => {
+                state.in_flight += 1;
+            }
+            None => {
+                let ticket = state.next_ticket;
+                state.next_ticket += 1;
+                state.queue.push_back(Waiter {
+                    ticket,
+                    waker: cx.waker().clone(),
+                });
+                this.ticket = Some(ticket);
+                return Poll::Pending;
+            }
+            Some(ticket) if state.cancelled.remove(&ticket) => {
+                this.ticket = None;
+                return Poll::Ready(Err(Error::Cancelled));
+            }
+            Some(ticket)
+                if state.in_flight < state.max_in_flight
+                    && state.queue.front().map(|waiter| waiter.ticket) == Some(ticket) =>
+            {
+                state.queue.pop_front();
+                state.in_flight += 1;
+                this.ticket = None;
+            }
+            Some(ticket) => {
+                if let Some(waiter) = state.queue.iter_mut().find(|w| w.ticket == ticket) {
+                    waiter.waker = cx.waker().clone();
+                }
+                return Poll::Pending;
+            }
+        }
+        drop(state);
+        // There may be room for the next request as well
+        this.limiter.wake_next();
+        Poll::Ready(Ok(Permit(this.limiter.clone())))
+    }
+}
+
+impl Drop for Acquire {
+    fn drop(&mut self) {
+        if let Some(ticket) = self.ticket.take() {
+            {
+                let mut state = self.limiter.0.borrow_mut();
+                state.queue.retain(|waiter| waiter.ticket != ticket);
+                state.cancelled.remove(&ticket);
+            }
+            self.limiter.wake_next();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    fn poll(acquire: &mut Acquire) -> Poll<Result<Permit, Error>> {
+        Pin::new(acquire).poll(&mut Context::from_waker(Waker::noop()))
+    }
+
+    #[wasm_bindgen_test]
+    fn test_max_in_flight() {
+        let limiter = Limiter::new(2);
+        let first = poll(&mut limiter.acquire());
+        let second = poll(&mut limiter.acquire());
+        assert!(matches!(first, Poll::Ready(Ok(_))));
+        assert!(matches!(second, Poll::Ready(Ok(_))));
+        assert_eq!(limiter.in_flight(), 2);
+
+        let mut third = limiter.acquire();
+        assert!(poll(&mut third).is_pending());
+        assert_eq!(limiter.queued(), 1);
+
+        drop(first);
+        assert_eq!(limiter.in_flight(), 1);
+        let third = poll(&mut third);
+        assert!(matches!(third, Poll::Ready(Ok(_))));
+        assert_eq!((limiter.in_flight(), limiter.queued()), (2, 0));
+
+        drop((second, third));
+        assert_eq!(limiter.in_flight(), 0);
+        assert_eq!(Limiter::new(0).max_in_flight(), 1);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_fair_queue() {
+        let limiter = Limiter::new(1);
+        let permit = poll(&mut limiter.acquire());
+        let mut first = limiter.acquire();
+        let mut second = limiter.acquire();
+        assert!(poll(&mut first).is_pending());
+        assert!(poll(&mut second).is_pending());
+
+        drop(permit);
+        // The later request can't jump the queue
+        assert!(poll(&mut second).is_pending());
+        let permit = poll(&mut first);
+        assert!(matches!(permit, Poll::Ready(Ok(_))));
+        assert!(poll(&mut second).is_pending());
+
+        // Nor can a new request
+        drop(permit);
+        assert!(poll(&mut limiter.acquire()).is_pending());
+        assert!(matches!(poll(&mut second), Poll::Ready(Ok(_))));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_cancellation() {
+        let limiter = Limiter::new(1);
+        let permit = poll(&mut limiter.acquire());
+        let mut dropped = limiter.acquire();
+        let mut cancelled = limiter.acquire();
+        assert!(poll(&mut dropped).is_pending());
+        assert!(poll(&mut cancelled).is_pending());
+
+        drop(dropped);
+        assert_eq!(limiter.queued(), 1);
+
+        limiter.cancel_queued();
+        assert_eq!(limiter.queued(), 0);
+        assert!(matches!(
+            poll(&mut cancelled),
+            Poll::Ready(Err(Error::Cancelled))
+        ));
+        assert_eq!(limiter.in_flight(), 1);
+
+        drop(permit);
+        assert!(matches!(poll(&mut limiter.acquire()), Poll::Ready(Ok(_))));
+    }
+}
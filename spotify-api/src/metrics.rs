@@ -0,0 +1,270 @@
+//! Opt-in request tracing and timing metrics for the [`Client`](crate::Client)
+//!
+//! Once [enabled](crate::Client::enable_metrics()) each request is logged through [`log`] as a [`RequestRecord`]
+//! and aggregated per [`Endpoint`] into a [`MetricsSnapshot`].
+
+use instant::Duration;
+use std::collections::BTreeMap;
+
+/// The collections whose following path segment is always an ID, even if it isn't a Spotify ID
+const NAMED_COLLECTIONS: [&str; 2] = ["categories", "users"];
+
+/// An API endpoint identified by its method and path template
+#[derive(
+    Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, serde::Serialize, serde::Deserialize,
+)]
+pub struct Endpoint {
+    /// The HTTP method, e.g. `GET`
+    pub method: String,
+    /// The path with IDs replaced by `{id}`, e.g. `/v1/albums/{id}/tracks`
+    pub template: String,
+}
+
+impl Endpoint {
+    /// The endpoint of a request with the `method` to the `url`
+    pub fn new(method: &reqwest::Method, url: &reqwest::Url) -> Self {
+        let mut previous = "";
+        let template = url
+            .path_segments()
+            .into_iter()
+            .flatten()
+            .map(|segment| {
+                let is_id = NAMED_COLLECTIONS.contains(&previous) || is_spotify_id(segment);
+                previous = segment;
+                if is_id {
+                    "/{id}".to_owned()
+                } else {
+                    "/".to_owned() + segment
+                }
+            })
+            .collect();
+        Self {
+            method: method.to_string(),
+            template,
+        }
+    }
+}
+
+impl std::fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.method, self.template)
+    }
+}
+
+/// Returns if the path `segment` has the form of a base-62 Spotify ID
+fn is_spotify_id(segment: &str) -> bool {
+    segment.len() == 22 && segment.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// The trace of a single request
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct RequestRecord {
+    /// The endpoint requested
+    pub endpoint: Endpoint,
+    /// The HTTP status of the response, `None` if no response was received
+    pub status: Option<reqwest::StatusCode>,
+    /// The time from the request being made until its response body was read
+    pub latency: Duration,
+    /// The number of times the request was retried
+    pub retries: u32,
+    /// Whether or not the access token was refreshed to make the request
+    pub token_refreshed: bool,
+}
+
+impl std::fmt::Display for RequestRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} -> {} in {}ms, {} retries{}",
+            self.endpoint,
+            self.status
+                .map_or_else(|| "no response".to_owned(), |status| status.to_string()),
+            self.latency.as_millis(),
+            self.retries,
+            if self.token_refreshed {
+                ", token refreshed"
+            } else {
+                ""
+            }
+        )
+    }
+}
+
+/// Aggregated metrics for requests to an [`Endpoint`]
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct EndpointMetrics {
+    /// The number of requests made
+    pub requests: u32,
+    /// The number of requests that failed, either with an HTTP error status or no response
+    pub errors: u32,
+    /// The number of requests made for each HTTP status received
+    pub statuses: BTreeMap<u16, u32>,
+    /// The total latency of all requests
+    pub total_latency: Duration,
+    /// The highest latency of any request
+    pub max_latency: Duration,
+    /// The total number of retries
+    pub retries: u32,
+    /// The number of access token refreshes
+    pub token_refreshes: u32,
+}
+
+impl EndpointMetrics {
+    /// The mean latency of the requests
+    pub fn mean_latency(&self) -> Duration {
+        self.total_latency
+            .checked_div(self.requests)
+            .unwrap_or_default()
+    }
+}
+
+/// The metrics of all requests made since metrics were enabled
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct MetricsSnapshot {
+    /// The aggregated metrics of each endpoint requested
+    pub endpoints: BTreeMap<Endpoint, EndpointMetrics>,
+}
+
+impl MetricsSnapshot {
+    /// Logs and aggregates the `record`
+    pub(crate) fn record(&mut self, record: RequestRecord) {
+        match record.status {
+            Some(status) if !status.is_client_error() && !status.is_server_error() => {
+                log::debug!("{record}")
+            }
+            _ => log::warn!("{record}"),
+        }
+
+        let metrics = self.endpoints.entry(record.endpoint).or_default();
+        metrics.requests += 1;
+        match record.status {
+            Some(status) => {
+                *metrics.statuses.entry(status.as_u16()).or_default() += 1;
+                if status.is_client_error() || status.is_server_error() {
+                    metrics.errors += 1;
+                }
+            }
+            None => metrics.errors += 1,
+        }
+        metrics.total_latency += record.latency;
+        metrics.max_latency = metrics.max_latency.max(record.latency);
+        metrics.retries += record.retries;
+        metrics.token_refreshes += u32::from(record.token_refreshed);
+    }
+
+    /// The total number of requests made to all endpoints
+    pub fn requests(&self) -> u32 {
+        self.endpoints
+            .values()
+            .map(|metrics| metrics.requests)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    fn endpoint(method: reqwest::Method, url: &str) -> Endpoint {
+        Endpoint::new(&method, &reqwest::Url::parse(url).unwrap())
+    }
+
+    #[wasm_bindgen_test]
+    fn test_endpoint_template() {
+        assert_eq!(
+            endpoint(
+                reqwest::Method::GET,
+                "https://api.spotify.com/v1/albums/4aawyAB9vmqN3uQ7FjRGTy/tracks?limit=5"
+            )
+            .to_string(),
+            "GET /v1/albums/{id}/tracks"
+        );
+        assert_eq!(
+            endpoint(
+                reqwest::Method::GET,
+                "https://api.spotify.com/v1/browse/categories/dinner/playlists"
+            )
+            .template,
+            "/v1/browse/categories/{id}/playlists"
+        );
+        assert_eq!(
+            endpoint(
+                reqwest::Method::GET,
+                "https://api.spotify.com/v1/users/smedjan"
+            )
+            .template,
+            "/v1/users/{id}"
+        );
+        assert_eq!(
+            endpoint(
+                reqwest::Method::PUT,
+                "https://api.spotify.com/v1/me/audiobooks?ids=18yVqkdbdRvS24c0Ilj2ci"
+            )
+            .to_string(),
+            "PUT /v1/me/audiobooks"
+        );
+        assert_eq!(
+            endpoint(
+                reqwest::Method::GET,
+                "https://api.spotify.com/v1/recommendations/available-genre-seeds"
+            )
+            .template,
+            "/v1/recommendations/available-genre-seeds"
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_record() {
+        let albums = endpoint(
+            reqwest::Method::GET,
+            "https://api.spotify.com/v1/albums/4aawyAB9vmqN3uQ7FjRGTy",
+        );
+        let record = |status, latency, token_refreshed| RequestRecord {
+            endpoint: albums.clone(),
+            status,
+            latency: Duration::from_millis(latency),
+            retries: 0,
+            token_refreshed,
+        };
+
+        let mut snapshot = MetricsSnapshot::default();
+        snapshot.record(record(Some(reqwest::StatusCode::OK), 100, true));
+        snapshot.record(record(Some(reqwest::StatusCode::NOT_FOUND), 300, false));
+        snapshot.record(record(None, 200, false));
+        snapshot.record(RequestRecord {
+            endpoint: endpoint(reqwest::Method::GET, "https://api.spotify.com/v1/markets"),
+            ..record(Some(reqwest::StatusCode::OK), 50, false)
+        });
+
+        assert_eq!(snapshot.requests(), 4);
+        let metrics = &snapshot.endpoints[&albums];
+        assert_eq!(metrics.requests, 3);
+        assert_eq!(metrics.errors, 2);
+        assert_eq!(metrics.statuses, BTreeMap::from([(200, 1), (404, 1)]));
+        assert_eq!(metrics.max_latency, Duration::from_millis(300));
+        assert_eq!(metrics.mean_latency(), Duration::from_millis(200));
+        assert_eq!(metrics.token_refreshes, 1);
+        assert_eq!(
+            EndpointMetrics::default().mean_latency(),
+            Duration::default()
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_record_display() {
+        assert_eq!(
+            RequestRecord {
+                endpoint: endpoint(reqwest::Method::GET, "https://api.spotify.com/v1/markets"),
+                status: Some(reqwest::StatusCode::OK),
+                latency: Duration::from_millis(42),
+                retries: 1,
+                token_refreshed: true,
+            }
+            .to_string(),
+            "GET /v1/markets -> 200 OK in 42ms, 1 retries, token refreshed"
+        );
+    }
+}
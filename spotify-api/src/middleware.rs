@@ -0,0 +1,104 @@
+//! Hooks for inspecting and modifying the requests a [`Client`](crate::Client) makes
+//!
+//! Middleware is run in the order it was [added](crate::Client::add_middleware()) for each authorised API request,
+//! requests made to refresh the access token aren't passed through middleware.
+//! Middleware may also [answer](Middleware::respond()) requests itself, e.g. to serve cached responses or inject faults in tests.
+
+use utils::request::{Error, Response, Result};
+
+/// The method and URL of a request after it has passed through all middleware
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct RequestInfo {
+    /// The HTTP method of the request
+    pub method: reqwest::Method,
+    /// The full URL of the request, including its query parameters
+    pub url: reqwest::Url,
+}
+
+impl From<&reqwest::Request> for RequestInfo {
+    fn from(request: &reqwest::Request) -> Self {
+        Self {
+            method: request.method().clone(),
+            url: request.url().clone(),
+        }
+    }
+}
+
+/// The status and headers of a successful response
+#[derive(Copy, Clone, Debug)]
+pub struct ResponseInfo<'a> {
+    /// The HTTP status code
+    pub status: reqwest::StatusCode,
+    /// The response headers
+    pub headers: &'a reqwest::header::HeaderMap,
+}
+
+/// A request interceptor, e.g. for logging, metrics, header injection or fault injection in tests
+pub trait Middleware {
+    /// Inspects and modifies the `request` before it's sent
+    fn on_request(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        request
+    }
+
+    /// Answers the `request` without sending it, e.g. with a cached body or an injected error,
+    /// or `None` to send it
+    ///
+    /// The first middleware to answer wins, the request then isn't authorised, limited or retried.
+    fn respond(&self, _request: &RequestInfo) -> Option<Result<Response<Vec<u8>>>> {
+        None
+    }
+
+    /// Observes the `response` to, or error from, sending the `request`
+    fn on_response(
+        &self,
+        _request: &RequestInfo,
+        _response: std::result::Result<ResponseInfo<'_>, &Error>,
+    ) {
+    }
+}
+
+/// Logs each request and its outcome through [`log`]
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct LogMiddleware;
+
+impl Middleware for LogMiddleware {
+    fn on_response(
+        &self,
+        request: &RequestInfo,
+        response: std::result::Result<ResponseInfo<'_>, &Error>,
+    ) {
+        match response {
+            Ok(response) => {
+                log::debug!("{} {} -> {}", request.method, request.url, response.status)
+            }
+            Err(err) => log::warn!("{} {} -> {err}", request.method, request.url),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    fn test_request_info() {
+        let request = reqwest::Client::new()
+            .delete("https://api.spotify.com/v1/me/audiobooks")
+            .query(&[("ids", "18yVqkdbdRvS24c0Ilj2ci")])
+            .build()
+            .unwrap();
+        assert_eq!(
+            RequestInfo::from(&request),
+            RequestInfo {
+                method: reqwest::Method::DELETE,
+                url: reqwest::Url::parse(
+                    "https://api.spotify.com/v1/me/audiobooks?ids=18yVqkdbdRvS24c0Ilj2ci"
+                )
+                .unwrap(),
+            }
+        );
+    }
+}
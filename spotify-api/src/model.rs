@@ -0,0 +1,302 @@
+//! Objects shared between the [Spotify Web API](https://developer.spotify.com/documentation/web-api/reference/#/) endpoints
+
+mod id;
+pub use id::*;
+
+/// Cover art or artwork of an object in a single size
+#[derive(
+    Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, serde::Serialize, serde::Deserialize,
+)]
+pub struct Image {
+    /// The source URL of the image
+    pub url: String,
+    /// The image height in pixels, if known
+    pub height: Option<u32>,
+    /// The image width in pixels, if known
+    pub width: Option<u32>,
+}
+
+/// Known external URLs for an object
+#[derive(
+    Clone,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    Debug,
+    Default,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub struct ExternalUrls {
+    /// The [Spotify URL](https://developer.spotify.com/documentation/web-api/concepts/spotify-uris-ids) for the object
+    pub spotify: Option<String>,
+}
+
+/// Information about the followers of an object
+#[derive(
+    Clone,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    Debug,
+    Default,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub struct Followers {
+    /// Always `None`, the Web API doesn't support it at the moment
+    pub href: Option<String>,
+    /// The total number of followers
+    pub total: u32,
+}
+
+/// The type of a [`Copyright`]
+#[derive(
+    Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, serde::Serialize, serde::Deserialize,
+)]
+pub enum CopyrightType {
+    /// The copyright
+    #[serde(rename = "C")]
+    Copyright,
+    /// The sound recording (performance) copyright
+    #[serde(rename = "P")]
+    Performance,
+}
+
+/// Copyright statement of an object
+#[derive(
+    Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, serde::Serialize, serde::Deserialize,
+)]
+pub struct Copyright {
+    /// The copyright text for this content
+    pub text: String,
+    /// The type of copyright
+    #[serde(rename = "type")]
+    pub copyright_type: CopyrightType,
+}
+
+/// Content restrictions applied to an object
+#[derive(
+    Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, serde::Serialize, serde::Deserialize,
+)]
+pub struct Restrictions {
+    /// The reason for the restriction, e.g. `market`, `product` or `explicit`
+    pub reason: String,
+}
+
+/// The precision with which a release date is known
+#[derive(
+    Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum ReleaseDatePrecision {
+    /// Formatted as `YYYY`
+    Year,
+    /// Formatted as `YYYY-MM`
+    Month,
+    /// Formatted as `YYYY-MM-DD`
+    Day,
+}
+
+/// [Paging Object](https://developer.spotify.com/documentation/web-api/concepts/api-calls#pagination)
+#[derive(
+    Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, serde::Serialize, serde::Deserialize,
+)]
+pub struct Page<T> {
+    /// A link to the Web API endpoint returning the full result of the request
+    pub href: String,
+    /// The requested content
+    pub items: Vec<T>,
+    /// The maximum number of items in the response
+    pub limit: u32,
+    /// URL to the next page of items, if any
+    pub next: Option<String>,
+    /// The offset of the items returned
+    pub offset: u32,
+    /// URL to the previous page of items, if any
+    pub previous: Option<String>,
+    /// The total number of items available to return
+    pub total: u32,
+}
+
+/// The cursors of a [`CursorPage`]
+#[derive(
+    Clone,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    Debug,
+    Default,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub struct Cursors {
+    /// The cursor to use as the `after` parameter to get the next page of items
+    #[serde(default)]
+    pub after: Option<String>,
+    /// The cursor to use as the `before` parameter to get the previous page of items
+    #[serde(default)]
+    pub before: Option<String>,
+}
+
+/// [Cursor-based Paging Object](https://developer.spotify.com/documentation/web-api/reference/get-recently-played)
+#[derive(
+    Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, serde::Serialize, serde::Deserialize,
+)]
+pub struct CursorPage<T> {
+    /// A link to the Web API endpoint returning the full result of the request
+    pub href: String,
+    /// The requested content
+    pub items: Vec<T>,
+    /// The maximum number of items in the response
+    pub limit: u32,
+    /// URL to the next page of items, if any
+    pub next: Option<String>,
+    /// The cursors used to find the next set of items, `None` if there are no items
+    #[serde(default)]
+    pub cursors: Option<Cursors>,
+    /// The total number of items available to return, if known
+    #[serde(default)]
+    pub total: Option<u32>,
+}
+
+/// Deserializes `null` as the default value of `T`, for fields the API may send as `null` instead of empty
+pub(crate) fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Default + serde::Deserialize<'de>,
+{
+    <Option<T> as serde::Deserialize>::deserialize(deserializer).map(Option::unwrap_or_default)
+}
+
+/// Joins `ids` into the comma separated list expected by the `ids` query parameter
+pub(crate) fn join_ids<I: AsRef<str>>(ids: &[I]) -> String {
+    ids.iter()
+        .map(|id| id.as_ref())
+        .collect::<Vec<&str>>()
+        .join(",")
+}
+
+/// Parses an [ISO 8601](https://en.wikipedia.org/wiki/ISO_8601) UTC timestamp as sent by the API,
+/// e.g. `2024-03-01T12:34:56Z` or `2024-03-01T12:34:56.789Z`, into the time since the Unix epoch
+pub(crate) fn parse_timestamp(timestamp: &str) -> Option<std::time::Duration> {
+    let (date, time) = timestamp.strip_suffix('Z')?.split_once('T')?;
+    let mut date = date.splitn(3, '-').map(str::parse::<i64>);
+    let (year, month, day) = (date.next()?.ok()?, date.next()?.ok()?, date.next()?.ok()?);
+    let (time, millis) = match time.split_once('.') {
+        Some((time, fraction)) => {
+            let digits = fraction.get(..fraction.len().min(3))?;
+            let millis: u64 = digits.parse().ok()?;
+            (time, millis * 10u64.pow(3 - digits.len() as u32))
+        }
+        None => (time, 0),
+    };
+    let mut time = time.splitn(3, ':').map(str::parse::<i64>);
+    let (hour, minute, second) = (time.next()?.ok()?, time.next()?.ok()?, time.next()?.ok()?);
+    if !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+        || !(0..24).contains(&hour)
+        || !(0..60).contains(&minute)
+        || !(0..=60).contains(&second)
+    {
+        return None;
+    }
+
+    // Days since the epoch of the proleptic Gregorian date, from http://howardhinnant.github.io/date_algorithms.html
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146097 + day_of_era - 719468;
+
+    let seconds = days * 86400 + hour * 3600 + minute * 60 + second;
+    Some(
+        std::time::Duration::from_secs(seconds.try_into().ok()?)
+            + std::time::Duration::from_millis(millis),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    fn test_page_deserialize() {
+        assert_eq!(
+            serde_json::from_str::<Page<Image>>(
+                r#"{"href":"https://api.spotify.com/v1/me/shows?offset=0&limit=1","items":[{"url":"https://i.scdn.co/image/ab67","height":640,"width":null}],"limit":1,"next":null,"offset":0,"previous":null,"total":1}"#
+            )
+            .unwrap(),
+            Page {
+                href: "https://api.spotify.com/v1/me/shows?offset=0&limit=1".to_string(),
+                items: vec![Image {
+                    url: "https://i.scdn.co/image/ab67".to_string(),
+                    height: Some(640),
+                    width: None,
+                }],
+                limit: 1,
+                next: None,
+                offset: 0,
+                previous: None,
+                total: 1,
+            }
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_copyright_deserialize() {
+        assert_eq!(
+            serde_json::from_str::<Vec<Copyright>>(
+                r#"[{"text":"(C) Label","type":"C"},{"text":"(P) Label","type":"P"}]"#
+            )
+            .unwrap(),
+            vec![
+                Copyright {
+                    text: "(C) Label".to_string(),
+                    copyright_type: CopyrightType::Copyright
+                },
+                Copyright {
+                    text: "(P) Label".to_string(),
+                    copyright_type: CopyrightType::Performance
+                },
+            ]
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_join_ids() {
+        assert_eq!(join_ids::<&str>(&[]), "");
+        assert_eq!(join_ids(&["a"]), "a");
+        assert_eq!(join_ids(&["a", "b", "c"]), "a,b,c");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_parse_timestamp() {
+        use std::time::Duration;
+        assert_eq!(
+            parse_timestamp("1970-01-01T00:00:00Z"),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(
+            parse_timestamp("2024-02-29T12:34:56Z"),
+            Some(Duration::from_secs(1709210096))
+        );
+        assert_eq!(
+            parse_timestamp("2000-03-01T00:00:00.5Z"),
+            Some(Duration::from_millis(951868800500))
+        );
+        assert_eq!(parse_timestamp("2024-02-29"), None);
+        assert_eq!(parse_timestamp("2024-13-01T00:00:00Z"), None);
+        assert_eq!(parse_timestamp("1969-12-31T23:59:59Z"), None);
+    }
+}
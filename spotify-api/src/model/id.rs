@@ -0,0 +1,196 @@
+//! Typed [Spotify IDs](https://developer.spotify.com/documentation/web-api/concepts/spotify-uris-ids)
+
+/// Error for a malformed [Spotify ID or URI](https://developer.spotify.com/documentation/web-api/concepts/spotify-uris-ids)
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct InvalidIdError {
+    /// The type of item the ID was expected to identify, e.g. `show`
+    pub item_type: &'static str,
+    /// The rejected ID or URI
+    pub id: String,
+}
+impl std::error::Error for InvalidIdError {}
+impl std::fmt::Display for InvalidIdError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "\"{}\" is not a valid Spotify {} ID or URI",
+            self.id, self.item_type
+        )
+    }
+}
+
+macro_rules! spotify_id {
+    ($id_type:ident, $item_type:literal) => {
+        spotify_id! {$id_type, $item_type, "A base-62", |c: char| c.is_ascii_alphanumeric()}
+    };
+    ($id_type:ident, $item_type:literal, $id_format:literal, $is_valid_char:expr) => {
+        #[doc = concat!($id_format, " [Spotify ID](https://developer.spotify.com/documentation/web-api/concepts/spotify-uris-ids) for the `", $item_type, "` item type")]
+        #[derive(
+            Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, serde::Serialize, serde::Deserialize,
+        )]
+        #[serde(try_from = "String", into = "String")]
+        pub struct $id_type(String);
+
+        impl $id_type {
+            /// The item type as it appears in a Spotify URI
+            pub const ITEM_TYPE: &'static str = $item_type;
+
+            /// Creates an ID from its string representation
+            pub fn new(id: &str) -> Result<Self, InvalidIdError> {
+                if !id.is_empty() && id.chars().all($is_valid_char) {
+                    Ok(Self(id.to_owned()))
+                } else {
+                    Err(InvalidIdError {
+                        item_type: Self::ITEM_TYPE,
+                        id: id.to_owned(),
+                    })
+                }
+            }
+
+            #[doc = concat!("Creates an ID from a `spotify:", $item_type, ":{id}` URI")]
+            pub fn from_uri(uri: &str) -> Result<Self, InvalidIdError> {
+                match uri.split(':').collect::<Vec<&str>>()[..] {
+                    ["spotify", Self::ITEM_TYPE, id] => Self::new(id),
+                    _ => Err(InvalidIdError {
+                        item_type: Self::ITEM_TYPE,
+                        id: uri.to_owned(),
+                    }),
+                }
+            }
+
+            /// The string representation of the ID
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+
+            #[doc = concat!("The `spotify:", $item_type, ":{id}` URI for the ID")]
+            pub fn uri(&self) -> String {
+                format!("spotify:{}:{}", Self::ITEM_TYPE, self.0)
+            }
+        }
+
+        impl std::str::FromStr for $id_type {
+            type Err = InvalidIdError;
+
+            /// Parses either an ID or a Spotify URI
+            fn from_str(id: &str) -> Result<Self, Self::Err> {
+                if id.starts_with("spotify:") {
+                    Self::from_uri(id)
+                } else {
+                    Self::new(id)
+                }
+            }
+        }
+
+        impl TryFrom<String> for $id_type {
+            type Error = InvalidIdError;
+
+            fn try_from(id: String) -> Result<Self, Self::Error> {
+                id.parse()
+            }
+        }
+
+        impl From<$id_type> for String {
+            fn from(id: $id_type) -> Self {
+                id.0
+            }
+        }
+
+        impl AsRef<str> for $id_type {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl std::fmt::Display for $id_type {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+    };
+}
+
+spotify_id! {AlbumId, "album"}
+spotify_id! {ArtistId, "artist"}
+spotify_id! {TrackId, "track"}
+spotify_id! {ShowId, "show"}
+spotify_id! {EpisodeId, "episode"}
+// Audiobooks and their chapters share the URIs of shows and episodes
+spotify_id! {AudiobookId, "show"}
+spotify_id! {ChapterId, "episode"}
+spotify_id! {PlaylistId, "playlist"}
+spotify_id! {UserId, "user", "A username", |c: char| !matches!(c, ':' | '/' | '?' | '#')}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    fn test_new() {
+        assert_eq!(
+            ShowId::new("38bS44xjbVVZ3No3ByF1dJ").map(String::from),
+            Ok("38bS44xjbVVZ3No3ByF1dJ".to_string())
+        );
+        assert_eq!(
+            ShowId::new(""),
+            Err(InvalidIdError {
+                item_type: "show",
+                id: "".to_string()
+            })
+        );
+        assert_eq!(
+            ShowId::new("not/an-id"),
+            Err(InvalidIdError {
+                item_type: "show",
+                id: "not/an-id".to_string()
+            })
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_user_id() {
+        assert_eq!(
+            UserId::new("smedjan.user_1-x").map(String::from),
+            Ok("smedjan.user_1-x".to_string())
+        );
+        assert_eq!(
+            UserId::from_uri("spotify:user:smedjan").map(|id| id.uri()),
+            Ok("spotify:user:smedjan".to_string())
+        );
+        assert!(UserId::new("user/name").is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_uri() {
+        let id = EpisodeId::from_uri("spotify:episode:512ojhOuo1ktJprKbVcKyQ").unwrap();
+        assert_eq!(id.as_str(), "512ojhOuo1ktJprKbVcKyQ");
+        assert_eq!(id.uri(), "spotify:episode:512ojhOuo1ktJprKbVcKyQ");
+        assert_eq!(
+            EpisodeId::from_uri("spotify:show:512ojhOuo1ktJprKbVcKyQ"),
+            Err(InvalidIdError {
+                item_type: "episode",
+                id: "spotify:show:512ojhOuo1ktJprKbVcKyQ".to_string()
+            })
+        );
+        assert_eq!(
+            "spotify:episode:512ojhOuo1ktJprKbVcKyQ".parse::<EpisodeId>(),
+            "512ojhOuo1ktJprKbVcKyQ".parse::<EpisodeId>()
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_serde() {
+        let id: ShowId = serde_json::from_str(r#""38bS44xjbVVZ3No3ByF1dJ""#).unwrap();
+        assert_eq!(id.as_str(), "38bS44xjbVVZ3No3ByF1dJ");
+        assert_eq!(
+            serde_json::to_string(&id).unwrap(),
+            r#""38bS44xjbVVZ3No3ByF1dJ""#
+        );
+        assert!(
+            serde_json::from_str::<ShowId>(r#""spotify:episode:38bS44xjbVVZ3No3ByF1dJ""#).is_err()
+        );
+    }
+}
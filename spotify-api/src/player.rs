@@ -0,0 +1,281 @@
+//! [Player](https://developer.spotify.com/documentation/web-api/reference/get-information-about-the-users-current-playback) API
+
+use crate::{client::Query, model::*, playlists::PlayableItem, tracks::Track, Client};
+use strum_macros::*;
+use utils::request::Result;
+
+mod progress;
+mod queue_manager;
+mod watcher;
+pub use progress::*;
+pub use queue_manager::*;
+pub use watcher::*;
+
+/// [Device Object](https://developer.spotify.com/documentation/web-api/reference/get-a-users-available-devices)
+#[derive(Clone, Eq, PartialEq, Hash, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Device {
+    /// The device ID, `None` for some restricted devices
+    #[serde(default)]
+    pub id: Option<String>,
+    /// If this device is the currently active device
+    pub is_active: bool,
+    /// If this device is currently in a private session
+    pub is_private_session: bool,
+    /// Whether controlling this device is restricted, if so no Web API commands will be accepted by it
+    pub is_restricted: bool,
+    /// A human-readable name for the device
+    pub name: String,
+    /// Device type, such as `computer`, `smartphone` or `speaker`
+    #[serde(rename = "type")]
+    pub device_type: String,
+    /// The current volume in percent, `None` if unavailable
+    #[serde(default)]
+    pub volume_percent: Option<u32>,
+    /// If this device can be used to set the volume
+    #[serde(default)]
+    pub supports_volume: bool,
+}
+
+/// Whether and what playback repeats
+#[derive(
+    Clone,
+    Copy,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    Debug,
+    Default,
+    AsRefStr,
+    EnumString,
+    Display,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum RepeatState {
+    /// Playback doesn't repeat
+    #[default]
+    Off,
+    /// The current item repeats
+    Track,
+    /// The current context repeats
+    Context,
+}
+
+/// [Playback State](https://developer.spotify.com/documentation/web-api/reference/get-information-about-the-users-current-playback)
+#[derive(Clone, Eq, PartialEq, Hash, Debug, serde::Serialize, serde::Deserialize)]
+pub struct PlaybackState {
+    /// The device that is currently active
+    pub device: Device,
+    /// Whether and what playback repeats
+    #[serde(default)]
+    pub repeat_state: RepeatState,
+    /// If shuffle is on
+    #[serde(default)]
+    pub shuffle_state: bool,
+    /// The context the item is played from, `None` if there's no context
+    #[serde(default)]
+    pub context: Option<PlayContext>,
+    /// Unix timestamp in milliseconds of when the playback state was last changed
+    pub timestamp: u64,
+    /// Progress into the currently playing item in milliseconds
+    #[serde(default)]
+    pub progress_ms: Option<u32>,
+    /// If something is currently playing
+    pub is_playing: bool,
+    /// The currently playing track or episode, `None` for adverts or if nothing is playing
+    #[serde(default)]
+    pub item: Option<PlayableItem>,
+    /// The object type of the currently playing item, `track`, `episode`, `ad` or `unknown`
+    pub currently_playing_type: String,
+}
+
+/// [User's Queue](https://developer.spotify.com/documentation/web-api/reference/get-queue)
+#[derive(Clone, Eq, PartialEq, Hash, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Queue {
+    /// The currently playing track or episode
+    #[serde(default)]
+    pub currently_playing: Option<PlayableItem>,
+    /// The tracks or episodes in the queue, next first
+    #[serde(default)]
+    pub queue: Vec<PlayableItem>,
+}
+
+/// The context an item was played from
+#[derive(
+    Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, serde::Serialize, serde::Deserialize,
+)]
+pub struct PlayContext {
+    /// The object type of the context, e.g. `artist`, `playlist` or `album`
+    #[serde(rename = "type")]
+    pub context_type: String,
+    /// A link to the Web API endpoint providing full details of the context
+    #[serde(default)]
+    pub href: Option<String>,
+    /// External URLs for the context
+    #[serde(default)]
+    pub external_urls: ExternalUrls,
+    /// The Spotify URI for the context
+    pub uri: String,
+}
+
+/// [Play History Object](https://developer.spotify.com/documentation/web-api/reference/get-recently-played)
+#[derive(Clone, Eq, PartialEq, Hash, Debug, serde::Serialize, serde::Deserialize)]
+pub struct PlayHistory {
+    /// The track the user listened to
+    pub track: Track,
+    /// The [ISO 8601](https://en.wikipedia.org/wiki/ISO_8601) UTC timestamp of when the track was played
+    pub played_at: String,
+    /// The context the track was played from
+    #[serde(default)]
+    pub context: Option<PlayContext>,
+}
+
+impl Client {
+    /// [Get Playback State](https://developer.spotify.com/documentation/web-api/reference/get-information-about-the-users-current-playback)
+    ///
+    /// Requires [`Scopes::UserReadPlaybackState`](crate::authorisation::Scopes::UserReadPlaybackState),
+    /// `None` if there's no active device.
+    /// The state is never cached as it changes constantly.
+    pub async fn playback_state(&mut self, market: Option<&str>) -> Result<Option<PlaybackState>> {
+        let market = self.resolve_market(market);
+        self.send(
+            reqwest::Method::GET,
+            &["me", "player"],
+            &Query {
+                market: market.as_deref(),
+                additional_types: Some("track,episode"),
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// [Get the User's Queue](https://developer.spotify.com/documentation/web-api/reference/get-queue)
+    ///
+    /// Requires [`Scopes::UserReadPlaybackState`](crate::authorisation::Scopes::UserReadPlaybackState),
+    /// the queue is empty if there's no active device.
+    pub async fn queue(&mut self) -> Result<Queue> {
+        self.send(
+            reqwest::Method::GET,
+            &["me", "player", "queue"],
+            &Query::default(),
+        )
+        .await
+    }
+
+    /// [Add Item to Playback Queue](https://developer.spotify.com/documentation/web-api/reference/add-to-queue)
+    ///
+    /// Requires [`Scopes::UserModifyPlaybackState`](crate::authorisation::Scopes::UserModifyPlaybackState),
+    /// appends the track or episode with the Spotify `uri` to the queue of the device with `device_id`,
+    /// or the active device if `None`.
+    pub async fn add_to_queue(&mut self, uri: &str, device_id: Option<&str>) -> Result<()> {
+        self.send_no_content(
+            reqwest::Method::POST,
+            &["me", "player", "queue"],
+            &Query {
+                uri: Some(uri),
+                device_id,
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// [Get Recently Played Tracks](https://developer.spotify.com/documentation/web-api/reference/get-recently-played)
+    ///
+    /// Requires [`Scopes::UserReadRecentlyPlayed`](crate::authorisation::Scopes::UserReadRecentlyPlayed),
+    /// `limit` is between 1 and 50, defaulting to 20.
+    /// Only the 50 most recent plays are available, newest first,
+    /// played after or before the Unix timestamps in milliseconds `after` and `before`, of which at most one may be given.
+    pub async fn recently_played(
+        &mut self,
+        limit: Option<u32>,
+        after: Option<u64>,
+        before: Option<u64>,
+    ) -> Result<CursorPage<PlayHistory>> {
+        self.get(
+            &["me", "player", "recently-played"],
+            &Query {
+                limit,
+                after,
+                before,
+                ..Default::default()
+            },
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::tracks::tests::track;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    pub(crate) fn playback_state(id: &str, progress_ms: u32, is_playing: bool) -> PlaybackState {
+        PlaybackState {
+            device: Device {
+                id: Some("5fbb3ba6aa454b5534c4ba43a8c7e8e45a63ad0e".to_owned()),
+                is_active: true,
+                is_private_session: false,
+                is_restricted: false,
+                name: "Kitchen".to_owned(),
+                device_type: "speaker".to_owned(),
+                volume_percent: Some(50),
+                supports_volume: true,
+            },
+            repeat_state: Default::default(),
+            shuffle_state: false,
+            context: None,
+            timestamp: 1709296496789,
+            progress_ms: Some(progress_ms),
+            is_playing,
+            item: Some(PlayableItem::Track(track(id, id, "Artist", "USUM71703861"))),
+            currently_playing_type: "track".to_owned(),
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn test_recently_played_deserialize() {
+        let page: CursorPage<serde_json::Value> = serde_json::from_str(
+            r#"{"href":"https://api.spotify.com/v1/me/player/recently-played?limit=1","items":[{"track":{},"played_at":"2024-03-01T12:34:56.789Z","context":null}],"limit":1,"next":"https://api.spotify.com/v1/me/player/recently-played?before=1709296496789&limit=1","cursors":{"after":"1709296496789","before":"1709296496789"}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            page.cursors.and_then(|cursors| cursors.before).as_deref(),
+            Some("1709296496789")
+        );
+        assert_eq!(page.total, None);
+
+        let context: PlayContext = serde_json::from_str(
+            r#"{"type":"playlist","href":"https://api.spotify.com/v1/playlists/37i9dQZF1DXcBWIGoYBM5M","external_urls":{"spotify":"https://open.spotify.com/playlist/37i9dQZF1DXcBWIGoYBM5M"},"uri":"spotify:playlist:37i9dQZF1DXcBWIGoYBM5M"}"#,
+        )
+        .unwrap();
+        assert_eq!(context.context_type, "playlist");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_playback_state_deserialize() {
+        let state: Option<PlaybackState> = serde_json::from_str(
+            r#"{"device":{"id":"5fbb3ba6aa454b5534c4ba43a8c7e8e45a63ad0e","is_active":true,"is_private_session":false,"is_restricted":false,"name":"Kitchen","type":"speaker","volume_percent":59,"supports_volume":true},"repeat_state":"context","shuffle_state":false,"context":null,"timestamp":1709296496789,"progress_ms":44272,"is_playing":true,"item":null,"currently_playing_type":"ad","actions":{"disallows":{"resuming":true}}}"#,
+        )
+        .unwrap();
+        let state = state.unwrap();
+        assert_eq!(state.device.volume_percent, Some(59));
+        assert_eq!(state.repeat_state, RepeatState::Context);
+        assert_eq!(state.progress_ms, Some(44272));
+        assert_eq!(state.item, None);
+
+        // No active device responds with 204 No Content
+        assert_eq!(
+            serde_json::from_str::<Option<PlaybackState>>("null").unwrap(),
+            None
+        );
+    }
+}
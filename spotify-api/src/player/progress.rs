@@ -0,0 +1,252 @@
+//! Interpolating the playback progress between polls of the playback state
+
+use super::PlaybackState;
+use crate::playlists::PlayableItem;
+use instant::Duration;
+
+/// The most latency accounted for between the API measuring the progress and the state being received,
+/// as slower requests, e.g. ones that were retried, give no good estimate of when it was measured
+pub const MAX_LATENCY: Duration = Duration::from_secs(2);
+
+/// The largest drift that's corrected smoothly, larger differences jump to the polled progress
+pub const MAX_SMOOTHED_DRIFT: Duration = Duration::from_secs(2);
+
+/// The least time over which drift is corrected, longer for larger drift so the progress never moves backwards
+const MIN_CORRECTION_TIME: Duration = Duration::from_secs(1);
+
+/// Drift being corrected, decaying linearly from `offset_ms` to nothing
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+struct Correction {
+    offset_ms: i64,
+    started: Duration,
+    length: Duration,
+}
+
+impl Correction {
+    fn offset_at(&self, now: Duration) -> i64 {
+        let elapsed = now.saturating_sub(self.started);
+        if elapsed >= self.length {
+            return 0;
+        }
+        let remaining = (self.length - elapsed).as_millis() as i64;
+        self.offset_ms * remaining / self.length.as_millis() as i64
+    }
+}
+
+/// The progress through the playing item, interpolated from the latest [`PlaybackState`]
+/// so a progress bar can move smoothly without polling every second
+///
+/// Times are the time since the Unix epoch on the local clock.
+/// When the next state of the same item arrives without the state's [timestamp](PlaybackState::timestamp) changing,
+/// small drift between the interpolated and polled progress is corrected gradually
+/// while seeks, pauses and item changes are applied immediately.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct PlaybackProgress {
+    uri: Option<String>,
+    duration_ms: u32,
+    is_playing: bool,
+    timestamp: u64,
+    progress_ms: u32,
+    anchored_at: Duration,
+    correction: Option<Correction>,
+}
+
+impl PlaybackProgress {
+    /// Creates the progress from the `state` received at `now`, nothing is playing if `None`,
+    /// see [`update()`](Self::update()) for the `latency`
+    pub fn new(state: Option<&PlaybackState>, now: Duration, latency: Duration) -> Self {
+        let mut progress = Self::default();
+        progress.update(state, now, latency);
+        progress
+    }
+
+    /// Updates the progress from the `state` received at `now`,
+    /// returning the drift in milliseconds of the interpolated progress ahead of the polled progress,
+    /// `None` if the progress jumped to the new state instead
+    ///
+    /// The `latency` is the time since the API measured the progress, measured locally,
+    /// e.g. half the round trip of the request, and is accounted for up to [`MAX_LATENCY`].
+    pub fn update(
+        &mut self,
+        state: Option<&PlaybackState>,
+        now: Duration,
+        latency: Duration,
+    ) -> Option<i64> {
+        let Some(state) = state else {
+            *self = Self {
+                anchored_at: now,
+                ..Self::default()
+            };
+            return None;
+        };
+        let latency = match state.is_playing {
+            true => latency.min(MAX_LATENCY),
+            false => Duration::ZERO,
+        };
+        let progress_ms = state
+            .progress_ms
+            .unwrap_or_default()
+            .saturating_add(latency.as_millis() as u32);
+        let uri = state.item.as_ref().map(|item| item.uri().to_owned());
+        let duration_ms = state.item.as_ref().map_or(0, PlayableItem::duration_ms);
+        let progress_ms = progress_ms.min(duration_ms);
+
+        // The timestamp only changes when playback is changed, e.g. by a seek, rather than as it progresses
+        let continued = uri.is_some()
+            && uri == self.uri
+            && self.is_playing
+            && state.is_playing
+            && state.timestamp == self.timestamp;
+        let drift = self.position_ms(now) as i64 - progress_ms as i64;
+        self.correction = (continued
+            && drift.unsigned_abs() <= MAX_SMOOTHED_DRIFT.as_millis() as u64)
+            .then(|| Correction {
+                offset_ms: drift,
+                started: now,
+                length: MIN_CORRECTION_TIME.max(Duration::from_millis(2 * drift.unsigned_abs())),
+            });
+        self.uri = uri;
+        self.duration_ms = duration_ms;
+        self.is_playing = state.is_playing;
+        self.timestamp = state.timestamp;
+        self.progress_ms = progress_ms;
+        self.anchored_at = now;
+        self.correction.map(|_| drift)
+    }
+
+    /// The interpolated progress in milliseconds at `now`
+    pub fn position_ms(&self, now: Duration) -> u32 {
+        let elapsed = if self.is_playing {
+            now.saturating_sub(self.anchored_at).as_millis() as i64
+        } else {
+            0
+        };
+        let offset = self
+            .correction
+            .map_or(0, |correction| correction.offset_at(now));
+        (self.progress_ms as i64 + elapsed + offset).clamp(0, self.duration_ms as i64) as u32
+    }
+
+    /// The interpolated progress at `now` as a fraction of the duration, between `0.0` and `1.0`
+    pub fn fraction(&self, now: Duration) -> f64 {
+        if self.duration_ms == 0 {
+            return 0.0;
+        }
+        self.position_ms(now) as f64 / self.duration_ms as f64
+    }
+
+    /// The interpolated time left of the item at `now` in milliseconds
+    pub fn remaining_ms(&self, now: Duration) -> u32 {
+        self.duration_ms - self.position_ms(now)
+    }
+
+    /// The Spotify URI of the item, `None` if nothing is playing
+    pub fn uri(&self) -> Option<&str> {
+        self.uri.as_deref()
+    }
+
+    /// The length of the item in milliseconds
+    pub fn duration_ms(&self) -> u32 {
+        self.duration_ms
+    }
+
+    /// If the progress is advancing
+    pub fn is_playing(&self) -> bool {
+        self.is_playing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::tests::playback_state;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    const RECEIVED: Duration = Duration::from_millis(1709296496789);
+    /// When playback was last changed, which is unrelated to when the state is received
+    const CHANGED: Duration = Duration::from_millis(1709296400000);
+
+    fn state(progress_ms: u32, is_playing: bool, timestamp: Duration) -> PlaybackState {
+        let mut state = playback_state("11dFghVXANMlKmJXsNCbNl", progress_ms, is_playing);
+        state.timestamp = timestamp.as_millis() as u64;
+        state
+    }
+
+    #[wasm_bindgen_test]
+    fn test_progress_interpolates() {
+        let progress =
+            PlaybackProgress::new(Some(&state(10000, true, CHANGED)), RECEIVED, Duration::ZERO);
+        assert_eq!(progress.position_ms(RECEIVED), 10000);
+        assert_eq!(
+            progress.position_ms(RECEIVED + Duration::from_secs(5)),
+            15000
+        );
+        assert_eq!(
+            progress.remaining_ms(RECEIVED + Duration::from_secs(5)),
+            185000
+        );
+        assert_eq!(
+            progress.position_ms(RECEIVED + Duration::from_secs(500)),
+            200000
+        );
+        assert_eq!(progress.fraction(RECEIVED + Duration::from_secs(90)), 0.5);
+
+        // Accounts for the latency of the request
+        let latency = Duration::from_millis(300);
+        let progress = PlaybackProgress::new(Some(&state(10000, true, CHANGED)), RECEIVED, latency);
+        assert_eq!(progress.position_ms(RECEIVED), 10300);
+        // Up to the most latency accounted for
+        let slow = Duration::from_secs(60);
+        let progress = PlaybackProgress::new(Some(&state(10000, true, CHANGED)), RECEIVED, slow);
+        assert_eq!(progress.position_ms(RECEIVED), 12000);
+
+        let paused = PlaybackProgress::new(Some(&state(10000, false, CHANGED)), RECEIVED, latency);
+        assert_eq!(paused.position_ms(RECEIVED + Duration::from_secs(5)), 10000);
+
+        let idle = PlaybackProgress::new(None, RECEIVED, latency);
+        assert_eq!(idle.uri(), None);
+        assert_eq!(idle.position_ms(RECEIVED + Duration::from_secs(5)), 0);
+        assert_eq!(idle.fraction(RECEIVED), 0.0);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_progress_corrects_drift() {
+        let mut progress =
+            PlaybackProgress::new(Some(&state(10000, true, CHANGED)), RECEIVED, Duration::ZERO);
+
+        // The interpolated 15000 is 400ms ahead
+        let polled = RECEIVED + Duration::from_secs(5);
+        assert_eq!(
+            progress.update(Some(&state(14600, true, CHANGED)), polled, Duration::ZERO),
+            Some(400)
+        );
+        assert_eq!(progress.position_ms(polled), 15000);
+        assert_eq!(
+            progress.position_ms(polled + Duration::from_millis(500)),
+            15300
+        );
+        assert_eq!(progress.position_ms(polled + Duration::from_secs(1)), 15600);
+        assert_eq!(progress.position_ms(polled + Duration::from_secs(2)), 16600);
+
+        // Seeking changes the timestamp and jumps to the new progress, however close
+        let seeked = polled + Duration::from_secs(5);
+        assert_eq!(
+            progress.update(Some(&state(20000, true, seeked)), seeked, Duration::ZERO),
+            None
+        );
+        assert_eq!(progress.position_ms(seeked), 20000);
+        assert_eq!(
+            progress.update(Some(&state(90000, true, seeked)), seeked, Duration::ZERO),
+            None
+        );
+        assert_eq!(progress.position_ms(seeked), 90000);
+
+        let mut other = state(0, true, seeked);
+        other.item = playback_state("6kLCHFM39wkFjOuyPGLGeQ", 0, true).item;
+        assert_eq!(progress.update(Some(&other), seeked, Duration::ZERO), None);
+        assert_eq!(progress.uri(), Some("spotify:track:6kLCHFM39wkFjOuyPGLGeQ"));
+        assert_eq!(progress.position_ms(seeked), 0);
+    }
+}
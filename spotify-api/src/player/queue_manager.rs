@@ -0,0 +1,488 @@
+//! An app-side playback queue fed to Spotify one item at a time
+//!
+//! The Web API can only append to the queue, so items are kept in a [`LocalQueue`] that can be reordered freely
+//! and only the next item is [added to the queue](Client::add_to_queue()) just before the current item ends.
+//! The [`QueueManager`] follows the events of a [`PlaybackWatcher`](super::PlaybackWatcher):
+//! ```ignore
+//! let manager = QueueManager::new(client.clone(), |queue| log::info!("{} queued", queue.items().len()));
+//! let watcher = PlaybackWatcher::new(client, {
+//!     let manager = manager.clone();
+//!     move |event, state| manager.handle_event(event, state)
+//! });
+//! watcher.start();
+//! ```
+//! The manager and the watcher each keep the token of their clone of the client refreshed
+//! across the requests they make.
+
+use super::{PlaybackEvent, PlaybackProgress, PlaybackState};
+use crate::{playlists::PlayableItem, Client};
+use instant::Duration;
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+};
+use utils::DelayedFn;
+
+/// The default time before the end of the current item that the next item is fed
+pub const DEFAULT_FEED_LEAD: Duration = Duration::from_secs(10);
+
+/// The most times feeding is retried after failing, before the current item ends
+pub const MAX_FEED_RETRIES: u32 = 2;
+
+/// An ordered queue of items waiting to be fed to Spotify's queue,
+/// along with the item fed that hasn't started playing yet
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct LocalQueue {
+    items: Vec<PlayableItem>,
+    fed: Option<PlayableItem>,
+}
+
+impl LocalQueue {
+    /// Creates an empty queue
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The items waiting to be fed, next first
+    pub fn items(&self) -> &[PlayableItem] {
+        &self.items
+    }
+
+    /// The item added to Spotify's queue that hasn't started playing yet
+    pub fn fed(&self) -> Option<&PlayableItem> {
+        self.fed.as_ref()
+    }
+
+    /// Adds the `item` to the end of the queue
+    pub fn push(&mut self, item: PlayableItem) {
+        self.items.push(item);
+    }
+
+    /// Inserts the `item` at `index`, or at the end if `index` is past the end
+    pub fn insert(&mut self, index: usize, item: PlayableItem) {
+        self.items.insert(index.min(self.items.len()), item);
+    }
+
+    /// Removes the item at `index`, `None` if there's no such item
+    pub fn remove(&mut self, index: usize) -> Option<PlayableItem> {
+        (index < self.items.len()).then(|| self.items.remove(index))
+    }
+
+    /// Moves the item at `from` to `to`, returns if there was an item to move
+    pub fn move_item(&mut self, from: usize, to: usize) -> bool {
+        if from >= self.items.len() {
+            return false;
+        }
+        let item = self.items.remove(from);
+        self.items.insert(to.min(self.items.len()), item);
+        true
+    }
+
+    /// Removes every item waiting to be fed, an item already fed stays in Spotify's queue
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    /// Takes the next item to feed, `None` if the queue is empty or an item fed hasn't started playing yet
+    pub fn take_next(&mut self) -> Option<&PlayableItem> {
+        if self.fed.is_some() || self.items.is_empty() {
+            return None;
+        }
+        self.fed = Some(self.items.remove(0));
+        self.fed.as_ref()
+    }
+
+    /// Returns the item fed to the front of the queue, e.g. when adding it to Spotify's queue failed
+    pub fn requeue_fed(&mut self) {
+        if let Some(fed) = self.fed.take() {
+            self.items.insert(0, fed);
+        }
+    }
+
+    /// Updates the queue after the `current` item started playing, returns if it's the item fed
+    pub fn on_track_changed(&mut self, current: Option<&PlayableItem>) -> bool {
+        let played = matches!(
+            (&self.fed, current),
+            (Some(fed), Some(current)) if fed.uri() == current.uri()
+        );
+        if played {
+            self.fed = None;
+        }
+        played
+    }
+
+    /// Updates the queue from Spotify's `queue` while `current` is playing,
+    /// returns if the item fed was skipped, as it's neither playing nor queued any more
+    pub fn on_queue(&mut self, current: Option<&PlayableItem>, queue: &[PlayableItem]) -> bool {
+        let Some(fed) = &self.fed else {
+            return false;
+        };
+        let skipped = current.is_none_or(|current| current.uri() != fed.uri())
+            && queue.iter().all(|item| item.uri() != fed.uri());
+        if skipped {
+            self.fed = None;
+        }
+        skipped
+    }
+
+    /// Returns the time from `now` until the next item should be fed, `lead` before the end of the current item,
+    /// or `None` if nothing is playing, the queue is empty or an item fed hasn't started playing yet
+    pub fn feed_in(
+        &self,
+        progress: &PlaybackProgress,
+        now: Duration,
+        lead: Duration,
+    ) -> Option<Duration> {
+        if self.fed.is_some() || self.items.is_empty() || !progress.is_playing() {
+            return None;
+        }
+        progress.uri()?;
+        Some(Duration::from_millis(progress.remaining_ms(now).into()).saturating_sub(lead))
+    }
+
+    /// Returns the time from `now` until feeding is retried after the `failures`th consecutive failure,
+    /// spreading the retries left over the rest of the current item,
+    /// or `None` after [`MAX_FEED_RETRIES`] retries or if there's nothing to feed
+    pub fn retry_in(
+        &self,
+        progress: &PlaybackProgress,
+        now: Duration,
+        failures: u32,
+    ) -> Option<Duration> {
+        if failures > MAX_FEED_RETRIES
+            || self.fed.is_some()
+            || self.items.is_empty()
+            || !progress.is_playing()
+        {
+            return None;
+        }
+        progress.uri()?;
+        let retries = MAX_FEED_RETRIES - failures + 1;
+        Some(Duration::from_millis(progress.remaining_ms(now).into()) / (retries + 1))
+    }
+}
+
+type ChangeCallback = dyn FnMut(&LocalQueue);
+
+struct Manager {
+    client: Client,
+    device_id: Option<String>,
+    lead: Duration,
+    queue: LocalQueue,
+    progress: PlaybackProgress,
+    timer: Option<DelayedFn>,
+    failures: u32,
+    on_change: Rc<RefCell<ChangeCallback>>,
+}
+
+/// Feeds a [`LocalQueue`] to Spotify's queue one item at a time, just before the current item ends,
+/// following the events of a [`PlaybackWatcher`](super::PlaybackWatcher) passed to [`handle_event()`](Self::handle_event())
+///
+/// If playback skips past the item fed, e.g. from another device,
+/// the item is dropped once it's no longer in Spotify's queue and feeding continues with the next item.
+/// If feeding fails the item returns to the front of the queue,
+/// and feeding is retried up to [`MAX_FEED_RETRIES`] times before the current item ends.
+/// Clones share the same queue.
+#[derive(Clone)]
+pub struct QueueManager {
+    manager: Rc<RefCell<Manager>>,
+}
+
+impl QueueManager {
+    /// Creates a manager with an empty queue making requests with the `client`,
+    /// calling `on_change` with the queue whenever an item is fed, played or skipped
+    ///
+    /// Requires [`Scopes::UserModifyPlaybackState`](crate::authorisation::Scopes::UserModifyPlaybackState)
+    /// and [`Scopes::UserReadPlaybackState`](crate::authorisation::Scopes::UserReadPlaybackState).
+    pub fn new<F: FnMut(&LocalQueue) + 'static>(client: Client, on_change: F) -> Self {
+        Self {
+            manager: Rc::new(RefCell::new(Manager {
+                client,
+                device_id: None,
+                lead: DEFAULT_FEED_LEAD,
+                queue: LocalQueue::new(),
+                progress: PlaybackProgress::default(),
+                timer: None,
+                failures: 0,
+                on_change: Rc::new(RefCell::new(on_change)),
+            })),
+        }
+    }
+
+    /// Sets the time before the end of the current item that the next item is fed
+    pub fn set_lead(&mut self, lead: Duration) -> &mut Self {
+        self.manager.borrow_mut().lead = lead;
+        Self::schedule(&self.manager);
+        self
+    }
+
+    /// Feeds the device with `device_id`, or the active device if `None`
+    pub fn set_device(&mut self, device_id: Option<&str>) -> &mut Self {
+        self.manager.borrow_mut().device_id = device_id.map(str::to_owned);
+        self
+    }
+
+    /// The queue
+    pub fn queue(&self) -> LocalQueue {
+        self.manager.borrow().queue.clone()
+    }
+
+    /// Changes the queue with `change`, e.g. to [reorder](LocalQueue::move_item()) or [remove](LocalQueue::remove()) items,
+    /// rescheduling when the next item is fed
+    pub fn update<R>(&self, change: impl FnOnce(&mut LocalQueue) -> R) -> R {
+        let result = change(&mut self.manager.borrow_mut().queue);
+        Self::schedule(&self.manager);
+        result
+    }
+
+    /// Follows a [`PlaybackEvent`] with the current `state`, checking whether the item fed was played or skipped
+    /// and rescheduling when the next item is fed
+    pub fn handle_event(&self, event: &PlaybackEvent, state: Option<&PlaybackState>) {
+        let now = instant::SystemTime::now()
+            .duration_since(instant::SystemTime::UNIX_EPOCH)
+            .unwrap_or_default();
+        let current = state.and_then(|state| state.item.as_ref());
+        let mut manager = self.manager.borrow_mut();
+        // The state was just received by the watcher
+        manager.progress.update(state, now, Duration::ZERO);
+        let (changed, check_skipped) = match event {
+            PlaybackEvent::TrackChanged { .. } => {
+                manager.failures = 0;
+                let played = manager.queue.on_track_changed(current);
+                (played, !played && manager.queue.fed().is_some())
+            }
+            PlaybackEvent::QueueChanged { queue } => {
+                (manager.queue.on_queue(current, queue), false)
+            }
+            _ => (false, false),
+        };
+        drop(manager);
+        if check_skipped {
+            Self::check_skipped(Rc::downgrade(&self.manager));
+        }
+        Self::schedule(&self.manager);
+        if changed {
+            Self::notify(&self.manager);
+        }
+    }
+
+    /// Schedules feeding the next item, replacing any feed scheduled before
+    fn schedule(manager: &Rc<RefCell<Manager>>) {
+        let weak = Rc::downgrade(manager);
+        let mut manager = manager.borrow_mut();
+        if let Some(timer) = manager.timer.take() {
+            timer.stop();
+        }
+        let now = instant::SystemTime::now()
+            .duration_since(instant::SystemTime::UNIX_EPOCH)
+            .unwrap_or_default();
+        let delay = match manager.failures {
+            0 => manager.queue.feed_in(&manager.progress, now, manager.lead),
+            failures => manager.queue.retry_in(&manager.progress, now, failures),
+        };
+        if let Some(delay) = delay {
+            manager.timer = Some(DelayedFn::new_once(move || Self::feed(weak), delay));
+        }
+    }
+
+    /// Adds the next item to Spotify's queue, returning it to the local queue and scheduling a retry if that fails
+    fn feed(manager: Weak<RefCell<Manager>>) {
+        let Some(this) = manager.upgrade() else {
+            return;
+        };
+        let mut this_ref = this.borrow_mut();
+        this_ref.timer = None;
+        let Some(uri) = this_ref.queue.take_next().map(|item| item.uri().to_owned()) else {
+            return;
+        };
+        let mut client = this_ref.client.clone();
+        let device_id = this_ref.device_id.clone();
+        drop(this_ref);
+        Self::notify(&this);
+        drop(this);
+
+        wasm_bindgen_futures::spawn_local(async move {
+            let result = client.add_to_queue(&uri, device_id.as_deref()).await;
+            let Some(this) = manager.upgrade() else {
+                return;
+            };
+            this.borrow_mut().client.keep_refreshed_token(&client);
+            match result {
+                Ok(()) => this.borrow_mut().failures = 0,
+                Err(err) => {
+                    log::warn!("adding {uri} to the queue failed, {err}");
+                    let mut this_ref = this.borrow_mut();
+                    this_ref.queue.requeue_fed();
+                    this_ref.failures += 1;
+                    drop(this_ref);
+                    Self::schedule(&this);
+                    Self::notify(&this);
+                }
+            }
+        });
+    }
+
+    /// Requests Spotify's queue to find out if the item fed was skipped, feeding the next item if so
+    fn check_skipped(manager: Weak<RefCell<Manager>>) {
+        let Some(this) = manager.upgrade() else {
+            return;
+        };
+        let mut client = this.borrow().client.clone();
+        drop(this);
+
+        wasm_bindgen_futures::spawn_local(async move {
+            let queue = client.queue().await;
+            let Some(this) = manager.upgrade() else {
+                return;
+            };
+            this.borrow_mut().client.keep_refreshed_token(&client);
+            match queue {
+                Ok(queue) => {
+                    let skipped = this
+                        .borrow_mut()
+                        .queue
+                        .on_queue(queue.currently_playing.as_ref(), &queue.queue);
+                    if skipped {
+                        Self::schedule(&this);
+                        Self::notify(&this);
+                    }
+                }
+                Err(err) => log::warn!("requesting the queue failed, {err}"),
+            }
+        });
+    }
+
+    /// Calls back with the queue, without holding a borrow so the callback may use the manager
+    fn notify(manager: &Rc<RefCell<Manager>>) {
+        let (on_change, queue) = {
+            let manager = manager.borrow();
+            (manager.on_change.clone(), manager.queue.clone())
+        };
+        (on_change.borrow_mut())(&queue);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::tests::playback_state;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    fn item(id: &str) -> PlayableItem {
+        playback_state(id, 0, true).item.unwrap()
+    }
+
+    fn uris(queue: &LocalQueue) -> Vec<&str> {
+        queue.items().iter().map(PlayableItem::uri).collect()
+    }
+
+    #[wasm_bindgen_test]
+    fn test_local_queue_reorder() {
+        let mut queue = LocalQueue::new();
+        queue.push(item("11dFghVXANMlKmJXsNCbNl"));
+        queue.push(item("6kLCHFM39wkFjOuyPGLGeQ"));
+        queue.insert(10, item("4iV5W9uYEdYUVa79Axb7Rh"));
+        queue.insert(0, item("1301WleyT98MSxVHPZCA6M"));
+
+        assert!(queue.move_item(0, 2));
+        assert!(!queue.move_item(4, 0));
+        assert_eq!(
+            uris(&queue),
+            vec![
+                "spotify:track:11dFghVXANMlKmJXsNCbNl",
+                "spotify:track:6kLCHFM39wkFjOuyPGLGeQ",
+                "spotify:track:1301WleyT98MSxVHPZCA6M",
+                "spotify:track:4iV5W9uYEdYUVa79Axb7Rh"
+            ]
+        );
+        assert_eq!(queue.remove(4), None);
+        assert_eq!(queue.remove(1), Some(item("6kLCHFM39wkFjOuyPGLGeQ")));
+        assert_eq!(queue.items().len(), 3);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_local_queue_feeding() {
+        let mut queue = LocalQueue::new();
+        queue.push(item("6kLCHFM39wkFjOuyPGLGeQ"));
+        queue.push(item("4iV5W9uYEdYUVa79Axb7Rh"));
+
+        // 190s left of the 200s track playing
+        let now = Duration::from_millis(1709296496789);
+        let progress = PlaybackProgress::new(
+            Some(&playback_state("11dFghVXANMlKmJXsNCbNl", 10000, true)),
+            now,
+            Duration::ZERO,
+        );
+        assert_eq!(
+            queue.feed_in(&progress, now, DEFAULT_FEED_LEAD),
+            Some(Duration::from_secs(180))
+        );
+        let paused = PlaybackProgress::new(
+            Some(&playback_state("11dFghVXANMlKmJXsNCbNl", 10000, false)),
+            now,
+            Duration::ZERO,
+        );
+        assert_eq!(queue.feed_in(&paused, now, DEFAULT_FEED_LEAD), None);
+
+        assert_eq!(queue.take_next(), Some(&item("6kLCHFM39wkFjOuyPGLGeQ")));
+        // Only one item is fed at a time
+        assert_eq!(queue.take_next(), None);
+        assert_eq!(queue.feed_in(&progress, now, DEFAULT_FEED_LEAD), None);
+
+        // Feeding failed
+        queue.requeue_fed();
+        assert_eq!(queue.items().len(), 2);
+
+        // The item fed played
+        queue.take_next();
+        assert!(!queue.on_track_changed(Some(&item("11dFghVXANMlKmJXsNCbNl"))));
+        assert!(queue.on_track_changed(Some(&item("6kLCHFM39wkFjOuyPGLGeQ"))));
+        assert_eq!(queue.fed(), None);
+
+        // Skipped from another device, the item fed is still queued then skipped too
+        queue.take_next();
+        let other = item("1301WleyT98MSxVHPZCA6M");
+        assert!(!queue.on_track_changed(Some(&other)));
+        assert!(!queue.on_queue(Some(&other), &[item("4iV5W9uYEdYUVa79Axb7Rh")]));
+        assert!(queue.on_queue(Some(&other), &[]));
+        assert_eq!(queue.fed(), None);
+        assert!(queue.items().is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_local_queue_retry() {
+        let mut queue = LocalQueue::new();
+        queue.push(item("6kLCHFM39wkFjOuyPGLGeQ"));
+
+        // 6s left of the 200s track playing when feeding failed
+        let now = Duration::from_millis(1709296496789);
+        let progress = PlaybackProgress::new(
+            Some(&playback_state("11dFghVXANMlKmJXsNCbNl", 194000, true)),
+            now,
+            Duration::ZERO,
+        );
+        assert_eq!(
+            queue.retry_in(&progress, now, 1),
+            Some(Duration::from_secs(2))
+        );
+        // The last retry halfway through the 4s left
+        let later = now + Duration::from_secs(2);
+        assert_eq!(
+            queue.retry_in(&progress, later, MAX_FEED_RETRIES),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(queue.retry_in(&progress, later, MAX_FEED_RETRIES + 1), None);
+
+        queue.take_next();
+        assert_eq!(queue.retry_in(&progress, now, 1), None);
+        let paused = PlaybackProgress::new(
+            Some(&playback_state("11dFghVXANMlKmJXsNCbNl", 194000, false)),
+            now,
+            Duration::ZERO,
+        );
+        queue.requeue_fed();
+        assert_eq!(queue.retry_in(&paused, now, 1), None);
+    }
+}
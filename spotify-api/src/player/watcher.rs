@@ -0,0 +1,566 @@
+//! Watching the playback state for changes
+
+use super::{Device, PlaybackProgress, PlaybackState};
+use crate::{playlists::PlayableItem, Client};
+use instant::{Duration, Instant};
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+};
+use utils::DelayedFn;
+
+/// How far the progress may differ from the expected progress before it's considered a seek,
+/// allowing for the latency of the requests
+pub const SEEK_TOLERANCE: Duration = Duration::from_secs(3);
+
+/// A change between consecutive playback states
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub enum PlaybackEvent {
+    /// Another item started playing, or the same item started again
+    TrackChanged {
+        /// The item that was playing, `None` if nothing was playing
+        previous: Option<Box<PlayableItem>>,
+        /// The item now playing, `None` if nothing is playing
+        current: Option<Box<PlayableItem>>,
+    },
+    /// Playback was paused, or stopped as there's no longer an active device
+    Paused,
+    /// Playback was resumed
+    Resumed,
+    /// The progress jumped within the same item
+    Seeked {
+        /// Where the progress was expected to be in milliseconds
+        from_ms: u32,
+        /// The progress in milliseconds
+        to_ms: u32,
+    },
+    /// Playback moved to another device
+    DeviceChanged {
+        /// The device that was active, `None` if there was no active device
+        previous: Option<Device>,
+        /// The device now active, `None` if there's no active device
+        current: Option<Device>,
+    },
+    /// The volume of the active device changed
+    VolumeChanged {
+        /// The volume in percent
+        previous: Option<u32>,
+        /// The new volume in percent
+        current: Option<u32>,
+    },
+    /// The queue changed, only emitted if [watching the queue](PlaybackWatcher::set_watch_queue())
+    QueueChanged {
+        /// The tracks or episodes in the queue, next first
+        queue: Vec<PlayableItem>,
+    },
+}
+
+/// Returns the events between the `previous` and `current` playback states,
+/// where `elapsed` is the time between fetching them
+///
+/// Seeks are detected by comparing the progress to the progress expected after `elapsed`,
+/// an item that reached its end and started again is reported as a [`PlaybackEvent::TrackChanged`].
+pub fn diff_playback(
+    previous: Option<&PlaybackState>,
+    current: Option<&PlaybackState>,
+    elapsed: Duration,
+) -> Vec<PlaybackEvent> {
+    let mut events = Vec::new();
+    let previous_item = previous.and_then(|state| state.item.as_ref());
+    let current_item = current.and_then(|state| state.item.as_ref());
+    let same_item = previous_item.is_some()
+        && previous_item.map(PlayableItem::uri) == current_item.map(PlayableItem::uri);
+
+    let restarted = match (previous, current) {
+        (Some(previous), Some(current)) if same_item => {
+            match (previous.progress_ms, current.progress_ms) {
+                (Some(from), Some(to)) => {
+                    let elapsed = elapsed.as_millis().min(u32::MAX as u128) as u32;
+                    let (earliest, latest) = match (previous.is_playing, current.is_playing) {
+                        (true, true) => {
+                            (from.saturating_add(elapsed), from.saturating_add(elapsed))
+                        }
+                        (false, false) => (from, from),
+                        // The pause or resume happened at some point in between
+                        _ => (from, from.saturating_add(elapsed)),
+                    };
+                    let tolerance = SEEK_TOLERANCE.as_millis() as u32;
+                    let duration = previous_item.map_or(0, PlayableItem::duration_ms);
+                    if previous.is_playing
+                        && latest.saturating_add(tolerance) >= duration
+                        && to < from
+                    {
+                        true
+                    } else {
+                        if to.saturating_add(tolerance) < earliest {
+                            events.push(PlaybackEvent::Seeked {
+                                from_ms: earliest,
+                                to_ms: to,
+                            });
+                        } else if to > latest.saturating_add(tolerance) {
+                            events.push(PlaybackEvent::Seeked {
+                                from_ms: latest,
+                                to_ms: to,
+                            });
+                        }
+                        false
+                    }
+                }
+                _ => false,
+            }
+        }
+        _ => false,
+    };
+    if !same_item || restarted {
+        events.insert(
+            0,
+            PlaybackEvent::TrackChanged {
+                previous: previous_item.cloned().map(Box::new),
+                current: current_item.cloned().map(Box::new),
+            },
+        );
+    }
+
+    let is_playing = |state: Option<&PlaybackState>| state.is_some_and(|state| state.is_playing);
+    match (is_playing(previous), is_playing(current)) {
+        (true, false) => events.push(PlaybackEvent::Paused),
+        (false, true) => events.push(PlaybackEvent::Resumed),
+        _ => {}
+    }
+
+    let previous_device = previous.map(|state| &state.device);
+    let current_device = current.map(|state| &state.device);
+    match (previous_device, current_device) {
+        (previous, current)
+            if previous.map(|device| (&device.id, &device.name))
+                != current.map(|device| (&device.id, &device.name)) =>
+        {
+            events.push(PlaybackEvent::DeviceChanged {
+                previous: previous.cloned(),
+                current: current.cloned(),
+            })
+        }
+        (Some(previous), Some(current)) if previous.volume_percent != current.volume_percent => {
+            events.push(PlaybackEvent::VolumeChanged {
+                previous: previous.volume_percent,
+                current: current.volume_percent,
+            })
+        }
+        _ => {}
+    }
+    events
+}
+
+/// The time between polls of a [`PlaybackWatcher`]
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct PollIntervals {
+    /// The time between polls while playing
+    pub playing: Duration,
+    /// The time between polls while paused
+    pub paused: Duration,
+    /// The time between polls while there's no active device
+    pub idle: Duration,
+    /// How long after the expected end of the playing item to poll, if that's sooner than `playing`
+    pub track_end_margin: Duration,
+}
+
+impl Default for PollIntervals {
+    fn default() -> Self {
+        Self {
+            playing: Duration::from_secs(5),
+            paused: Duration::from_secs(15),
+            idle: Duration::from_secs(30),
+            track_end_margin: Duration::from_millis(500),
+        }
+    }
+}
+
+/// Returns the time until the next poll after fetching the playback `state`
+///
+/// Polls sooner just after the playing item is expected to end so track changes are noticed quickly,
+/// and less often while paused or when there's no active device.
+pub fn next_poll(state: Option<&PlaybackState>, intervals: &PollIntervals) -> Duration {
+    match state {
+        None => intervals.idle,
+        Some(state) if !state.is_playing => intervals.paused,
+        Some(state) => {
+            let remaining = match (&state.item, state.progress_ms) {
+                (Some(item), Some(progress_ms)) => Some(Duration::from_millis(
+                    item.duration_ms().saturating_sub(progress_ms).into(),
+                )),
+                _ => None,
+            };
+            remaining
+                .map(|remaining| remaining + intervals.track_end_margin)
+                .map_or(intervals.playing, |until_end| {
+                    until_end.min(intervals.playing)
+                })
+        }
+    }
+}
+
+/// Returns the time until retrying after the `failures`th consecutive failed poll
+///
+/// Backs off from the interval while playing, doubling with each failure up to the interval when idle,
+/// so an unreachable API or expired token isn't polled as often as a playing item.
+pub fn retry_poll(failures: u32, intervals: &PollIntervals) -> Duration {
+    let factor = 2u32.saturating_pow(failures.saturating_sub(1));
+    intervals
+        .playing
+        .saturating_mul(factor)
+        .min(intervals.idle.max(intervals.playing))
+}
+
+type EventCallback = dyn FnMut(&PlaybackEvent, Option<&PlaybackState>);
+
+struct Watcher {
+    client: Client,
+    market: Option<String>,
+    intervals: PollIntervals,
+    watch_queue: bool,
+    on_event: Rc<RefCell<EventCallback>>,
+    state: Option<PlaybackState>,
+    fetched_at: Option<Instant>,
+    progress: PlaybackProgress,
+    queue: Option<Vec<String>>,
+    timer: Option<DelayedFn>,
+    running: bool,
+    polling: bool,
+    failures: u32,
+}
+
+/// Polls the playback state and calls back with a [`PlaybackEvent`] for each change,
+/// see [`diff_playback()`] for how changes are detected and [`next_poll()`] for when polls are made
+///
+/// Failed polls are logged and retried with backoff, see [`retry_poll()`].
+/// Watching stops when the watcher is [stopped](Self::stop()) or dropped.
+pub struct PlaybackWatcher {
+    watcher: Rc<RefCell<Watcher>>,
+}
+
+impl PlaybackWatcher {
+    /// Creates a watcher making requests with the `client`, calling `on_event` with each event and the current state
+    ///
+    /// Requires [`Scopes::UserReadPlaybackState`](crate::authorisation::Scopes::UserReadPlaybackState).
+    pub fn new<F>(client: Client, on_event: F) -> Self
+    where
+        F: FnMut(&PlaybackEvent, Option<&PlaybackState>) + 'static,
+    {
+        Self {
+            watcher: Rc::new(RefCell::new(Watcher {
+                client,
+                market: None,
+                intervals: PollIntervals::default(),
+                watch_queue: false,
+                on_event: Rc::new(RefCell::new(on_event)),
+                state: None,
+                fetched_at: None,
+                progress: PlaybackProgress::default(),
+                queue: None,
+                timer: None,
+                running: false,
+                polling: false,
+                failures: 0,
+            })),
+        }
+    }
+
+    /// Sets the time between polls, used from the next poll
+    pub fn set_intervals(&mut self, intervals: PollIntervals) -> &mut Self {
+        self.watcher.borrow_mut().intervals = intervals;
+        self
+    }
+
+    /// Sets the market of the playing items, or uses the client's default market if `None`
+    pub fn set_market(&mut self, market: Option<&str>) -> &mut Self {
+        self.watcher.borrow_mut().market = market.map(str::to_owned);
+        self
+    }
+
+    /// Also requests the queue with each poll, emitting [`PlaybackEvent::QueueChanged`] when it changes
+    pub fn set_watch_queue(&mut self, watch_queue: bool) -> &mut Self {
+        let mut watcher = self.watcher.borrow_mut();
+        watcher.watch_queue = watch_queue;
+        if !watch_queue {
+            watcher.queue = None;
+        }
+        drop(watcher);
+        self
+    }
+
+    /// The playback state of the latest poll, `None` if there's no active device or no poll has been made
+    pub fn state(&self) -> Option<PlaybackState> {
+        self.watcher.borrow().state.clone()
+    }
+
+    /// The progress through the playing item interpolated from the latest poll,
+    /// with the drift from each poll corrected smoothly
+    pub fn progress(&self) -> PlaybackProgress {
+        self.watcher.borrow().progress.clone()
+    }
+
+    /// If the watcher is polling
+    pub fn is_running(&self) -> bool {
+        self.watcher.borrow().running
+    }
+
+    /// Starts polling, the first poll is made immediately
+    pub fn start(&self) {
+        let mut watcher = self.watcher.borrow_mut();
+        if watcher.running {
+            return;
+        }
+        watcher.running = true;
+        drop(watcher);
+        Watcher::poll(Rc::downgrade(&self.watcher));
+    }
+
+    /// Polls immediately instead of waiting for the next poll,
+    /// e.g. after controlling playback so the change is noticed quickly
+    pub fn poll_now(&self) {
+        if self.is_running() {
+            Watcher::poll(Rc::downgrade(&self.watcher));
+        }
+    }
+
+    /// Stops polling, a poll in flight completes without emitting events
+    pub fn stop(&self) {
+        let mut watcher = self.watcher.borrow_mut();
+        watcher.running = false;
+        if let Some(timer) = watcher.timer.take() {
+            timer.stop();
+        }
+    }
+}
+
+impl Drop for PlaybackWatcher {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl Watcher {
+    /// Polls the state unless a poll is in flight, then schedules the next poll
+    fn poll(watcher: Weak<RefCell<Self>>) {
+        let Some(this) = watcher.upgrade() else {
+            return;
+        };
+        let mut this_ref = this.borrow_mut();
+        if !this_ref.running || this_ref.polling {
+            return;
+        }
+        this_ref.polling = true;
+        if let Some(timer) = this_ref.timer.take() {
+            timer.stop();
+        }
+        let mut client = this_ref.client.clone();
+        let market = this_ref.market.clone();
+        let watch_queue = this_ref.watch_queue;
+        drop(this_ref);
+        drop(this);
+
+        wasm_bindgen_futures::spawn_local(async move {
+            let requested_at = Instant::now();
+            let state = client.playback_state(market.as_deref()).await;
+            let fetched_at = Instant::now();
+            let queue = match (&state, watch_queue) {
+                (Ok(Some(_)), true) => match client.queue().await {
+                    Ok(queue) => Some(queue.queue),
+                    Err(err) => {
+                        log::warn!("requesting the queue failed, {err}");
+                        None
+                    }
+                },
+                (Ok(None), true) => Some(Vec::new()),
+                _ => None,
+            };
+
+            let Some(this) = watcher.upgrade() else {
+                return;
+            };
+            let mut this_ref = this.borrow_mut();
+            this_ref.polling = false;
+            this_ref.client.keep_refreshed_token(&client);
+            if !this_ref.running {
+                return;
+            }
+            let mut events = Vec::new();
+            let next = match state {
+                Ok(state) => {
+                    let elapsed = this_ref.fetched_at.map_or(Duration::ZERO, |previous| {
+                        fetched_at.duration_since(previous)
+                    });
+                    events = diff_playback(this_ref.state.as_ref(), state.as_ref(), elapsed);
+                    if let Some(queue) = queue {
+                        let uris: Vec<String> =
+                            queue.iter().map(|item| item.uri().to_owned()).collect();
+                        if this_ref.queue.as_ref() != Some(&uris) {
+                            this_ref.queue = Some(uris);
+                            events.push(PlaybackEvent::QueueChanged { queue });
+                        }
+                    }
+                    let now = instant::SystemTime::now()
+                        .duration_since(instant::SystemTime::UNIX_EPOCH)
+                        .unwrap_or_default();
+                    // The progress was measured around halfway through the request
+                    let latency =
+                        fetched_at.duration_since(requested_at) / 2 + fetched_at.elapsed();
+                    this_ref.progress.update(state.as_ref(), now, latency);
+                    this_ref.state = state;
+                    this_ref.fetched_at = Some(fetched_at);
+                    this_ref.failures = 0;
+                    next_poll(this_ref.state.as_ref(), &this_ref.intervals)
+                }
+                Err(err) => {
+                    log::warn!("requesting the playback state failed, {err}");
+                    this_ref.failures = this_ref.failures.saturating_add(1);
+                    retry_poll(this_ref.failures, &this_ref.intervals)
+                }
+            };
+            let weak = Rc::downgrade(&this);
+            this_ref.timer = Some(DelayedFn::new_once(move || Self::poll(weak), next));
+            let on_event = this_ref.on_event.clone();
+            let state = this_ref.state.clone();
+            drop(this_ref);
+            drop(this);
+
+            let mut on_event = on_event.borrow_mut();
+            for event in &events {
+                on_event(event, state.as_ref());
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::tests::playback_state as state;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    fn test_diff_playback() {
+        let elapsed = Duration::from_secs(5);
+        let playing = state("11dFghVXANMlKmJXsNCbNl", 10000, true);
+
+        // Progress as expected
+        let later = state("11dFghVXANMlKmJXsNCbNl", 15500, true);
+        assert_eq!(diff_playback(Some(&playing), Some(&later), elapsed), vec![]);
+
+        let seeked = state("11dFghVXANMlKmJXsNCbNl", 90000, true);
+        assert_eq!(
+            diff_playback(Some(&playing), Some(&seeked), elapsed),
+            vec![PlaybackEvent::Seeked {
+                from_ms: 15000,
+                to_ms: 90000
+            }]
+        );
+
+        // Paused somewhere in between isn't a seek
+        let paused = state("11dFghVXANMlKmJXsNCbNl", 12000, false);
+        assert_eq!(
+            diff_playback(Some(&playing), Some(&paused), elapsed),
+            vec![PlaybackEvent::Paused]
+        );
+        assert_eq!(
+            diff_playback(Some(&paused), Some(&playing), elapsed),
+            vec![PlaybackEvent::Resumed]
+        );
+
+        let next = state("6kLCHFM39wkFjOuyPGLGeQ", 1000, true);
+        assert_eq!(
+            diff_playback(Some(&playing), Some(&next), elapsed),
+            vec![PlaybackEvent::TrackChanged {
+                previous: playing.item.clone().map(Box::new),
+                current: next.item.clone().map(Box::new),
+            }]
+        );
+
+        // Repeating the same track
+        let ending = state("11dFghVXANMlKmJXsNCbNl", 198000, true);
+        let repeated = state("11dFghVXANMlKmJXsNCbNl", 3000, true);
+        assert_eq!(
+            diff_playback(Some(&ending), Some(&repeated), elapsed),
+            vec![PlaybackEvent::TrackChanged {
+                previous: ending.item.clone().map(Box::new),
+                current: repeated.item.clone().map(Box::new),
+            }]
+        );
+
+        let mut louder = later.clone();
+        louder.device.volume_percent = Some(80);
+        assert_eq!(
+            diff_playback(Some(&playing), Some(&louder), elapsed),
+            vec![PlaybackEvent::VolumeChanged {
+                previous: Some(50),
+                current: Some(80)
+            }]
+        );
+
+        let mut moved = later.clone();
+        moved.device.id = Some("a0b2f3c1d4e5".to_owned());
+        moved.device.name = "Phone".to_owned();
+        assert_eq!(
+            diff_playback(Some(&playing), Some(&moved), elapsed),
+            vec![PlaybackEvent::DeviceChanged {
+                previous: Some(playing.device.clone()),
+                current: Some(moved.device.clone())
+            }]
+        );
+
+        assert_eq!(
+            diff_playback(Some(&playing), None, elapsed),
+            vec![
+                PlaybackEvent::TrackChanged {
+                    previous: playing.item.clone().map(Box::new),
+                    current: None,
+                },
+                PlaybackEvent::Paused,
+                PlaybackEvent::DeviceChanged {
+                    previous: Some(playing.device.clone()),
+                    current: None
+                }
+            ]
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_next_poll() {
+        let intervals = PollIntervals::default();
+        assert_eq!(next_poll(None, &intervals), intervals.idle);
+        assert_eq!(
+            next_poll(
+                Some(&state("11dFghVXANMlKmJXsNCbNl", 10000, false)),
+                &intervals
+            ),
+            intervals.paused
+        );
+        assert_eq!(
+            next_poll(
+                Some(&state("11dFghVXANMlKmJXsNCbNl", 10000, true)),
+                &intervals
+            ),
+            intervals.playing
+        );
+        // 2s before the end of the 200s track
+        assert_eq!(
+            next_poll(
+                Some(&state("11dFghVXANMlKmJXsNCbNl", 198000, true)),
+                &intervals
+            ),
+            Duration::from_millis(2500)
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_retry_poll() {
+        let intervals = PollIntervals::default();
+        assert_eq!(retry_poll(1, &intervals), Duration::from_secs(5));
+        assert_eq!(retry_poll(2, &intervals), Duration::from_secs(10));
+        assert_eq!(retry_poll(3, &intervals), Duration::from_secs(20));
+        assert_eq!(retry_poll(4, &intervals), intervals.idle);
+        assert_eq!(retry_poll(u32::MAX, &intervals), intervals.idle);
+    }
+}
@@ -0,0 +1,619 @@
+//! [Playlists](https://developer.spotify.com/documentation/web-api/reference/get-playlist) API
+
+use crate::{
+    authorisation::Scopes,
+    client::{Query, REQUEST_DURATION},
+    episodes::Episode,
+    model::*,
+    tracks::Track,
+    users::PublicUser,
+    Client,
+};
+use base64::Engine;
+
+mod export;
+pub use export::*;
+mod fields;
+pub use fields::*;
+mod reorder;
+pub use reorder::*;
+mod sync;
+pub use sync::*;
+
+/// The item types requested from playlists, so episodes aren't returned as `null`
+const ADDITIONAL_TYPES: &str = "track,episode";
+
+/// The maximum number of items that can be added or removed by a single request
+pub const MAX_MODIFIED_ITEMS: usize = 100;
+
+/// The maximum size in bytes of the base64 encoded image accepted by [`Client::upload_playlist_cover_image()`]
+pub const MAX_COVER_IMAGE_SIZE: usize = 256 * 1024;
+
+/// A reference to the tracks of a playlist
+#[derive(
+    Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, serde::Serialize, serde::Deserialize,
+)]
+pub struct PlaylistTracksRef {
+    /// A link to the Web API endpoint where full details of the playlist's tracks can be retrieved
+    pub href: String,
+    /// The number of tracks in the playlist
+    pub total: u32,
+}
+
+/// [Simplified Playlist Object](https://developer.spotify.com/documentation/web-api/reference/get-a-list-of-current-users-playlists)
+#[derive(Clone, Eq, PartialEq, Hash, Debug, serde::Serialize, serde::Deserialize)]
+pub struct SimplifiedPlaylist {
+    /// True if the owner allows other users to modify the playlist
+    pub collaborative: bool,
+    /// The playlist description, only returned for modified, verified playlists
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Known external URLs for this playlist
+    pub external_urls: ExternalUrls,
+    /// A link to the Web API endpoint providing full details of the playlist
+    pub href: String,
+    /// The Spotify ID for the playlist
+    pub id: PlaylistId,
+    /// Images for the playlist, widest first
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub images: Vec<Image>,
+    /// The name of the playlist
+    pub name: String,
+    /// The user who owns the playlist
+    pub owner: PublicUser,
+    /// The playlist's public/private status, `None` if not relevant
+    #[serde(default)]
+    pub public: Option<bool>,
+    /// The version identifier for the current playlist
+    pub snapshot_id: String,
+    /// Where to find the tracks of the playlist
+    pub tracks: PlaylistTracksRef,
+    /// The Spotify URI for the playlist
+    pub uri: String,
+}
+
+/// A track or episode in a playlist
+#[derive(Clone, Eq, PartialEq, Hash, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum PlayableItem {
+    /// A track, which may be a local file
+    Track(Track),
+    /// A podcast episode
+    Episode(Episode),
+}
+
+impl PlayableItem {
+    /// The Spotify URI for the item
+    pub fn uri(&self) -> &str {
+        match self {
+            PlayableItem::Track(track) => &track.uri,
+            PlayableItem::Episode(episode) => &episode.uri,
+        }
+    }
+
+    /// The Spotify URI stored in the playlist, the original track's if it was
+    /// [relinked](https://developer.spotify.com/documentation/web-api/concepts/track-relinking)
+    pub fn stored_uri(&self) -> &str {
+        match self {
+            PlayableItem::Track(track) => track
+                .linked_from
+                .as_ref()
+                .map_or(&track.uri, |linked_from| &linked_from.uri),
+            PlayableItem::Episode(episode) => &episode.uri,
+        }
+    }
+
+    /// The length of the item in milliseconds
+    pub fn duration_ms(&self) -> u32 {
+        match self {
+            PlayableItem::Track(track) => track.duration_ms,
+            PlayableItem::Episode(episode) => episode.duration_ms,
+        }
+    }
+}
+
+/// [Playlist Track Object](https://developer.spotify.com/documentation/web-api/reference/get-playlists-tracks)
+#[derive(Clone, Eq, PartialEq, Hash, Debug, serde::Serialize, serde::Deserialize)]
+pub struct PlaylistItem {
+    /// The date and time the item was added, `None` for very old playlists
+    #[serde(default)]
+    pub added_at: Option<String>,
+    /// The user who added the item, `None` for very old playlists
+    #[serde(default)]
+    pub added_by: Option<PublicUser>,
+    /// Whether or not the item is a local file
+    #[serde(default)]
+    pub is_local: bool,
+    /// The track or episode, `None` if it's no longer available
+    #[serde(default)]
+    pub track: Option<PlayableItem>,
+}
+
+/// [Playlist Object](https://developer.spotify.com/documentation/web-api/reference/get-playlist) with its first page of items
+#[derive(Clone, Eq, PartialEq, Hash, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Playlist {
+    /// True if the owner allows other users to modify the playlist
+    pub collaborative: bool,
+    /// The playlist description, only returned for modified, verified playlists
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Known external URLs for this playlist
+    pub external_urls: ExternalUrls,
+    /// Information about the followers of the playlist
+    pub followers: Followers,
+    /// A link to the Web API endpoint providing full details of the playlist
+    pub href: String,
+    /// The Spotify ID for the playlist
+    pub id: PlaylistId,
+    /// Images for the playlist, widest first
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub images: Vec<Image>,
+    /// The name of the playlist
+    pub name: String,
+    /// The user who owns the playlist
+    pub owner: PublicUser,
+    /// The playlist's public/private status, `None` if not relevant
+    #[serde(default)]
+    pub public: Option<bool>,
+    /// The version identifier for the current playlist
+    pub snapshot_id: String,
+    /// The first page of items in the playlist
+    pub tracks: Page<PlaylistItem>,
+    /// The Spotify URI for the playlist
+    pub uri: String,
+}
+
+/// [`Client::upload_playlist_cover_image()`] Errors
+#[derive(Debug)]
+pub enum CoverImageError {
+    /// The image isn't a JPEG
+    NotJpeg,
+    /// The base64 encoded image of the given size is larger than [`MAX_COVER_IMAGE_SIZE`]
+    TooLarge(usize),
+    /// The [`Scopes::UgcImageUpload`] scope wasn't granted to the access token
+    MissingScope,
+    /// Errors uploading the image
+    Request(utils::request::Error),
+}
+use CoverImageError::*;
+impl std::error::Error for CoverImageError {}
+impl std::fmt::Display for CoverImageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NotJpeg => write!(f, "the playlist cover image isn't a JPEG"),
+            TooLarge(size) => write!(
+                f,
+                "the encoded playlist cover image is {size} bytes but only up to {MAX_COVER_IMAGE_SIZE} are allowed"
+            ),
+            MissingScope => write!(
+                f,
+                "the `{}` scope is required to upload a playlist cover image",
+                Scopes::UgcImageUpload
+            ),
+            Request(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl PartialEq for CoverImageError {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (TooLarge(l), TooLarge(r)) => l == r,
+            (Request(l_req), Request(r_req)) => l_req.to_string() == r_req.to_string(),
+            (_self, _other) => core::mem::discriminant(self) == core::mem::discriminant(other),
+        }
+    }
+}
+
+/// Returns the standard base64 encoding of the JPEG `image` if it's a valid cover image
+fn encode_cover_image(image: &[u8]) -> Result<String, CoverImageError> {
+    const JPEG_MAGIC: [u8; 3] = [0xFF, 0xD8, 0xFF];
+    if !image.starts_with(&JPEG_MAGIC) {
+        return Err(NotJpeg);
+    }
+    let encoded = base64::engine::general_purpose::STANDARD.encode(image);
+    if encoded.len() > MAX_COVER_IMAGE_SIZE {
+        return Err(TooLarge(encoded.len()));
+    }
+    Ok(encoded)
+}
+
+impl Client {
+    /// [Get Playlist](https://developer.spotify.com/documentation/web-api/reference/get-playlist)
+    pub async fn playlist(
+        &mut self,
+        id: &PlaylistId,
+        market: Option<&str>,
+    ) -> utils::request::Result<Playlist> {
+        self.playlist_fields(id, market, &Fields::new()).await
+    }
+
+    /// [Get Playlist](https://developer.spotify.com/documentation/web-api/reference/get-playlist)
+    /// with only the fields of the [`Projection`] `P`
+    pub async fn playlist_projection<P: Projection>(
+        &mut self,
+        id: &PlaylistId,
+        market: Option<&str>,
+    ) -> utils::request::Result<P> {
+        self.playlist_fields(id, market, &P::fields()).await
+    }
+
+    /// [Get Playlist](https://developer.spotify.com/documentation/web-api/reference/get-playlist)
+    /// with only the `fields` selected, e.g. as a [`serde_json::Value`]
+    pub async fn playlist_fields<R: serde::de::DeserializeOwned>(
+        &mut self,
+        id: &PlaylistId,
+        market: Option<&str>,
+        fields: &Fields,
+    ) -> utils::request::Result<R> {
+        let market = self.resolve_market(market);
+        let fields = fields.to_string();
+        self.get(
+            &["playlists", id.as_str()],
+            &Query {
+                market: market.as_deref(),
+                fields: Some(fields.as_str()).filter(|fields| !fields.is_empty()),
+                additional_types: Some(ADDITIONAL_TYPES),
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// [Get Playlist Items](https://developer.spotify.com/documentation/web-api/reference/get-playlists-tracks)
+    ///
+    /// `limit` is between 1 and 50, defaulting to 20.
+    pub async fn playlist_items(
+        &mut self,
+        id: &PlaylistId,
+        market: Option<&str>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> utils::request::Result<Page<PlaylistItem>> {
+        self.playlist_items_fields(id, market, &Fields::new(), limit, offset)
+            .await
+    }
+
+    /// [Get Playlist Items](https://developer.spotify.com/documentation/web-api/reference/get-playlists-tracks)
+    /// with only the fields of the [`Projection`] `P` for each item
+    ///
+    /// `limit` is between 1 and 50, defaulting to 20.
+    pub async fn playlist_items_projection<P: Projection>(
+        &mut self,
+        id: &PlaylistId,
+        market: Option<&str>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> utils::request::Result<Page<P>> {
+        self.playlist_items_fields(id, market, &page_fields(P::fields()), limit, offset)
+            .await
+    }
+
+    /// [Get Playlist Items](https://developer.spotify.com/documentation/web-api/reference/get-playlists-tracks)
+    /// with only the `fields` selected, e.g. as a [`serde_json::Value`]
+    ///
+    /// The `fields` select from the page, not from each item.
+    /// `limit` is between 1 and 50, defaulting to 20.
+    pub async fn playlist_items_fields<R: serde::de::DeserializeOwned>(
+        &mut self,
+        id: &PlaylistId,
+        market: Option<&str>,
+        fields: &Fields,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> utils::request::Result<R> {
+        let market = self.resolve_market(market);
+        let fields = fields.to_string();
+        self.get(
+            &["playlists", id.as_str(), "tracks"],
+            &Query {
+                market: market.as_deref(),
+                fields: Some(fields.as_str()).filter(|fields| !fields.is_empty()),
+                additional_types: Some(ADDITIONAL_TYPES),
+                limit,
+                offset,
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// [Create Playlist](https://developer.spotify.com/documentation/web-api/reference/create-playlist)
+    ///
+    /// Requires [`Scopes::PlaylistModifyPublic`] or [`Scopes::PlaylistModifyPrivate`],
+    /// the playlist is created for the user `user_id`, which must be the current user,
+    /// and is public unless `public` is `Some(false)`.
+    pub async fn create_playlist(
+        &mut self,
+        user_id: &UserId,
+        name: &str,
+        public: Option<bool>,
+        description: Option<&str>,
+    ) -> utils::request::Result<Playlist> {
+        #[derive(serde::Serialize)]
+        struct Body<'a> {
+            name: &'a str,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            public: Option<bool>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            description: Option<&'a str>,
+        }
+
+        let body = Body {
+            name,
+            public,
+            description,
+        };
+        let url = crate::ENDPOINT.with_path(["users", user_id.as_str(), "playlists"]);
+        self.request(
+            |client| client.post(url.as_str()).json(&body),
+            REQUEST_DURATION,
+        )
+        .await
+    }
+
+    /// [Add Items to Playlist](https://developer.spotify.com/documentation/web-api/reference/add-tracks-to-playlist)
+    ///
+    /// Requires [`Scopes::PlaylistModifyPublic`] or [`Scopes::PlaylistModifyPrivate`],
+    /// accepts a maximum of [`MAX_MODIFIED_ITEMS`] track or episode URIs,
+    /// inserted at `position` or appended, and returns the new snapshot ID.
+    pub async fn add_playlist_items<U: AsRef<str>>(
+        &mut self,
+        id: &PlaylistId,
+        uris: &[U],
+        position: Option<u32>,
+    ) -> utils::request::Result<String> {
+        #[derive(serde::Serialize)]
+        struct Body<'a> {
+            uris: Vec<&'a str>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            position: Option<u32>,
+        }
+
+        let body = Body {
+            uris: uris.iter().map(AsRef::as_ref).collect(),
+            position,
+        };
+        self.modify_playlist_items(reqwest::Method::POST, id, &body)
+            .await
+    }
+
+    /// [Remove Playlist Items](https://developer.spotify.com/documentation/web-api/reference/remove-tracks-playlist)
+    ///
+    /// Requires [`Scopes::PlaylistModifyPublic`] or [`Scopes::PlaylistModifyPrivate`],
+    /// accepts a maximum of [`MAX_MODIFIED_ITEMS`] track or episode URIs and removes every occurrence of each.
+    /// The removal is made against the playlist's version `snapshot_id` if given, and the new snapshot ID is returned.
+    pub async fn remove_playlist_items<U: AsRef<str>>(
+        &mut self,
+        id: &PlaylistId,
+        uris: &[U],
+        snapshot_id: Option<&str>,
+    ) -> utils::request::Result<String> {
+        #[derive(serde::Serialize)]
+        struct Uri<'a> {
+            uri: &'a str,
+        }
+        #[derive(serde::Serialize)]
+        struct Body<'a> {
+            tracks: Vec<Uri<'a>>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            snapshot_id: Option<&'a str>,
+        }
+
+        let body = Body {
+            tracks: uris.iter().map(|uri| Uri { uri: uri.as_ref() }).collect(),
+            snapshot_id,
+        };
+        self.modify_playlist_items(reqwest::Method::DELETE, id, &body)
+            .await
+    }
+
+    /// [Update Playlist Items](https://developer.spotify.com/documentation/web-api/reference/reorder-or-replace-playlists-tracks)
+    ///
+    /// Requires [`Scopes::PlaylistModifyPublic`] or [`Scopes::PlaylistModifyPrivate`].
+    /// Moves the `range_length` items from `range_start` to before the item at `insert_before`,
+    /// both positions being before the move, against the playlist's version `snapshot_id` if given.
+    /// Returns the new snapshot ID.
+    pub async fn reorder_playlist_items(
+        &mut self,
+        id: &PlaylistId,
+        range_start: u32,
+        insert_before: u32,
+        range_length: u32,
+        snapshot_id: Option<&str>,
+    ) -> utils::request::Result<String> {
+        #[derive(serde::Serialize)]
+        struct Body<'a> {
+            range_start: u32,
+            insert_before: u32,
+            range_length: u32,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            snapshot_id: Option<&'a str>,
+        }
+
+        let body = Body {
+            range_start,
+            insert_before,
+            range_length,
+            snapshot_id,
+        };
+        self.modify_playlist_items(reqwest::Method::PUT, id, &body)
+            .await
+    }
+
+    /// Make a request modifying the items of a playlist with the JSON `body`, returning the new snapshot ID
+    async fn modify_playlist_items<B: serde::Serialize>(
+        &mut self,
+        method: reqwest::Method,
+        id: &PlaylistId,
+        body: &B,
+    ) -> utils::request::Result<String> {
+        #[derive(serde::Deserialize)]
+        struct Response {
+            snapshot_id: String,
+        }
+
+        let url = crate::ENDPOINT.with_path(["playlists", id.as_str(), "tracks"]);
+        let res: Response = self
+            .request(
+                |client| client.request(method.clone(), url.as_str()).json(body),
+                REQUEST_DURATION,
+            )
+            .await?;
+        Ok(res.snapshot_id)
+    }
+
+    /// [Add Custom Playlist Cover Image](https://developer.spotify.com/documentation/web-api/reference/upload-custom-playlist-cover)
+    ///
+    /// Requires [`Scopes::UgcImageUpload`] and [`Scopes::PlaylistModifyPublic`] or [`Scopes::PlaylistModifyPrivate`].
+    /// The `image` must be a JPEG, at most [`MAX_COVER_IMAGE_SIZE`] once base64 encoded,
+    /// and is checked before any request is made.
+    pub async fn upload_playlist_cover_image(
+        &mut self,
+        id: &PlaylistId,
+        image: &[u8],
+    ) -> Result<(), CoverImageError> {
+        if !self.has_scope(Scopes::UgcImageUpload) {
+            return Err(MissingScope);
+        }
+        let encoded = encode_cover_image(image)?;
+        let url = crate::ENDPOINT.with_path(["playlists", id.as_str(), "images"]);
+        self.request_no_content(
+            |client| {
+                client
+                    .put(url.as_str())
+                    .header(reqwest::header::CONTENT_TYPE, "image/jpeg")
+                    .body(encoded.clone())
+            },
+            REQUEST_DURATION,
+        )
+        .await
+        .map_err(Request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    fn test_simplified_playlist_deserialize() {
+        let playlist: SimplifiedPlaylist = serde_json::from_str(
+            r#"{"collaborative":false,"description":"","external_urls":{"spotify":"https://open.spotify.com/playlist/37i9dQZF1DXcBWIGoYBM5M"},"href":"https://api.spotify.com/v1/playlists/37i9dQZF1DXcBWIGoYBM5M","id":"37i9dQZF1DXcBWIGoYBM5M","images":null,"name":"Today's Top Hits","owner":{"display_name":"Spotify","external_urls":{"spotify":"https://open.spotify.com/user/spotify"},"href":"https://api.spotify.com/v1/users/spotify","id":"spotify","type":"user","uri":"spotify:user:spotify"},"primary_color":null,"public":null,"snapshot_id":"MTY3","tracks":{"href":"https://api.spotify.com/v1/playlists/37i9dQZF1DXcBWIGoYBM5M/tracks","total":50},"type":"playlist","uri":"spotify:playlist:37i9dQZF1DXcBWIGoYBM5M"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            playlist.id,
+            PlaylistId::new("37i9dQZF1DXcBWIGoYBM5M").unwrap()
+        );
+        assert!(playlist.images.is_empty());
+        assert_eq!(playlist.public, None);
+        assert_eq!(playlist.tracks.total, 50);
+        assert_eq!(playlist.owner.id, UserId::new("spotify").unwrap());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_playlist_item_deserialize() {
+        let mut track = serde_json::to_value(crate::tracks::tests::track(
+            "11dFghVXANMlKmJXsNCbNl",
+            "Cut To The Feeling",
+            "Carly Rae Jepsen",
+            "USUM71703861",
+        ))
+        .unwrap();
+        track["type"] = "track".into();
+        let items: Vec<PlaylistItem> = serde_json::from_value(serde_json::json!([
+            {"added_at": "2023-01-01T00:00:00Z", "added_by": {"external_urls": {}, "href": "https://api.spotify.com/v1/users/spotify", "id": "spotify", "type": "user", "uri": "spotify:user:spotify"}, "is_local": false, "track": track},
+            {"added_at": null, "added_by": null, "is_local": false, "track": null},
+        ]))
+        .unwrap();
+        assert_eq!(
+            items[0].track.as_ref().map(PlayableItem::uri),
+            Some("spotify:track:11dFghVXANMlKmJXsNCbNl")
+        );
+        assert_eq!(
+            items[0].added_by.as_ref().map(|user| user.id.as_str()),
+            Some("spotify")
+        );
+        assert_eq!(items[1].track, None);
+        assert_eq!(items[1].added_at, None);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_projection_deserialize() {
+        #[derive(serde::Deserialize)]
+        struct ItemUri {
+            track: Option<TrackUri>,
+        }
+
+        #[derive(serde::Deserialize)]
+        struct TrackUri {
+            uri: String,
+        }
+
+        impl Projection for ItemUri {
+            fn fields() -> Fields {
+                let mut fields = Fields::new();
+                fields.nested("track", |track| {
+                    track.field("uri");
+                });
+                fields
+            }
+        }
+
+        assert_eq!(
+            page_fields(ItemUri::fields()).to_string(),
+            "href,limit,next,offset,previous,total,items(track(uri))"
+        );
+        let page: Page<ItemUri> = serde_json::from_str(
+            r#"{"href":"https://api.spotify.com/v1/playlists/37i9dQZF1DXcBWIGoYBM5M/tracks","items":[{"track":{"uri":"spotify:track:11dFghVXANMlKmJXsNCbNl"}},{"track":null}],"limit":100,"next":null,"offset":0,"previous":null,"total":2}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            page.items[0].track.as_ref().map(|track| track.uri.as_str()),
+            Some("spotify:track:11dFghVXANMlKmJXsNCbNl")
+        );
+        assert!(page.items[1].track.is_none());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_encode_cover_image() {
+        const JPEG: [u8; 6] = [0xFF, 0xD8, 0xFF, 0xE0, 0xFB, 0xFF];
+        // Standard alphabet with padding rather than URL safe
+        assert_eq!(encode_cover_image(&JPEG), Ok("/9j/4Pv/".to_owned()));
+        assert_eq!(encode_cover_image(&JPEG[..5]), Ok("/9j/4Ps=".to_owned()));
+
+        assert_eq!(encode_cover_image(&[]), Err(NotJpeg));
+        assert_eq!(encode_cover_image("GIF89a".as_bytes()), Err(NotJpeg));
+
+        // 3 bytes encode to 4 chars
+        let mut image = JPEG.to_vec();
+        image.resize(MAX_COVER_IMAGE_SIZE / 4 * 3, 0);
+        assert_eq!(
+            encode_cover_image(&image).map(|encoded| encoded.len()),
+            Ok(MAX_COVER_IMAGE_SIZE)
+        );
+        image.push(0);
+        assert_eq!(
+            encode_cover_image(&image),
+            Err(TooLarge(MAX_COVER_IMAGE_SIZE + 4))
+        );
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_upload_requires_scope() {
+        let mut client = Client::new(crate::authorisation::AccessToken::default());
+        assert_eq!(
+            client
+                .upload_playlist_cover_image(
+                    &PlaylistId::new("37i9dQZF1DXcBWIGoYBM5M").unwrap(),
+                    &[0xFF, 0xD8, 0xFF]
+                )
+                .await,
+            Err(MissingScope)
+        );
+    }
+}
@@ -0,0 +1,896 @@
+//! Exporting playlists to, and importing them from, JSON, CSV, extended M3U and XSPF
+//!
+//! Formatting and parsing are pure functions of an [`ExportedPlaylist`],
+//! only [`Client::export_playlist()`] and [`Client::resolve_exported_tracks()`] make requests.
+
+use super::{PlayableItem, Playlist};
+use crate::{model::*, tracks::Track, Client};
+
+/// The column names of exported CSV
+const CSV_HEADER: [&str; 6] = ["uri", "name", "artists", "album", "duration_ms", "isrc"];
+
+/// The separator of artists within a CSV field
+const CSV_ARTIST_SEPARATOR: &str = "; ";
+
+/// The separator of artists in M3U and XSPF
+const ARTIST_SEPARATOR: &str = ", ";
+
+/// The XSPF identifier prefix of an ISRC
+const ISRC_URN: &str = "urn:isrc:";
+
+/// A file format of an [`ExportedPlaylist`]
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum Format {
+    /// The [`ExportedPlaylist`] serialized as JSON, lossless
+    Json,
+    /// A row per track, without the playlist's metadata
+    Csv,
+    /// [Extended M3U](https://en.wikipedia.org/wiki/M3U#Extended_M3U), without ISRCs or the playlist's description
+    M3u,
+    /// [XML Shareable Playlist Format](https://www.xspf.org/spec)
+    Xspf,
+}
+
+impl Format {
+    /// The file extension of the format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Json => "json",
+            Format::Csv => "csv",
+            Format::M3u => "m3u8",
+            Format::Xspf => "xspf",
+        }
+    }
+
+    /// The MIME type of the format
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            Format::Json => "application/json",
+            Format::Csv => "text/csv",
+            Format::M3u => "audio/x-mpegurl",
+            Format::Xspf => "application/xspf+xml",
+        }
+    }
+}
+
+/// A track, or episode, of an [`ExportedPlaylist`]
+#[derive(
+    Clone,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    Debug,
+    Default,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub struct ExportedTrack {
+    /// The Spotify URI, `None` for tracks imported from other services
+    #[serde(default)]
+    pub uri: Option<String>,
+    /// The name of the track
+    pub name: String,
+    /// The names of the artists who performed the track, or the publisher of an episode
+    #[serde(default)]
+    pub artists: Vec<String>,
+    /// The name of the album, or show of an episode
+    #[serde(default)]
+    pub album: Option<String>,
+    /// The track length in milliseconds
+    #[serde(default)]
+    pub duration_ms: Option<u32>,
+    /// [International Standard Recording Code](https://en.wikipedia.org/wiki/International_Standard_Recording_Code)
+    #[serde(default)]
+    pub isrc: Option<String>,
+}
+
+impl From<&Track> for ExportedTrack {
+    fn from(track: &Track) -> Self {
+        Self {
+            uri: Some(track.uri.clone()),
+            name: track.name.clone(),
+            artists: track
+                .artists
+                .iter()
+                .map(|artist| artist.name.clone())
+                .collect(),
+            album: Some(track.album.name.clone()).filter(|album| !album.is_empty()),
+            duration_ms: Some(track.duration_ms),
+            isrc: track.external_ids.isrc.clone(),
+        }
+    }
+}
+
+impl From<&PlayableItem> for ExportedTrack {
+    fn from(item: &PlayableItem) -> Self {
+        match item {
+            PlayableItem::Track(track) => track.into(),
+            PlayableItem::Episode(episode) => Self {
+                uri: Some(episode.uri.clone()),
+                name: episode.name.clone(),
+                artists: vec![episode.show.publisher.clone()],
+                album: Some(episode.show.name.clone()),
+                duration_ms: Some(episode.duration_ms),
+                isrc: None,
+            },
+        }
+    }
+}
+
+/// A playlist's metadata and tracks in a form independent of any service
+#[derive(
+    Clone,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    Debug,
+    Default,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub struct ExportedPlaylist {
+    /// The name of the playlist
+    pub name: String,
+    /// The playlist description
+    #[serde(default)]
+    pub description: Option<String>,
+    /// The Spotify URI of the playlist, `None` for playlists imported from other services
+    #[serde(default)]
+    pub uri: Option<String>,
+    /// The tracks of the playlist in order
+    #[serde(default)]
+    pub tracks: Vec<ExportedTrack>,
+}
+
+/// [`ExportedPlaylist::import()`] Errors
+#[derive(Debug)]
+pub enum ImportError {
+    /// Invalid JSON
+    Json(serde_json::Error),
+    /// Invalid CSV, with the reason
+    Csv(String),
+    /// Invalid XSPF, with the reason
+    Xspf(String),
+}
+impl std::error::Error for ImportError {}
+impl std::fmt::Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportError::Json(err) => write!(f, "invalid JSON playlist, {err}"),
+            ImportError::Csv(reason) => write!(f, "invalid CSV playlist, {reason}"),
+            ImportError::Xspf(reason) => write!(f, "invalid XSPF playlist, {reason}"),
+        }
+    }
+}
+
+impl PartialEq for ImportError {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (ImportError::Json(l_err), ImportError::Json(r_err)) => {
+                l_err.to_string() == r_err.to_string()
+            }
+            (ImportError::Csv(l), ImportError::Csv(r))
+            | (ImportError::Xspf(l), ImportError::Xspf(r)) => l == r,
+            (_self, _other) => core::mem::discriminant(self) == core::mem::discriminant(other),
+        }
+    }
+}
+
+impl ExportedPlaylist {
+    /// Exports the playlist as the `format`
+    pub fn export(&self, format: Format) -> String {
+        match format {
+            Format::Json => serde_json::to_string_pretty(self).expect("A serializable playlist"),
+            Format::Csv => self.to_csv(),
+            Format::M3u => self.to_m3u(),
+            Format::Xspf => self.to_xspf(),
+        }
+    }
+
+    /// Imports a playlist exported as the `format`, or from another service
+    ///
+    /// Spotify URIs are also recognised in `open.spotify.com` URLs,
+    /// while other locations, e.g. file paths, leave the [`ExportedTrack::uri`] unset.
+    pub fn import(format: Format, content: &str) -> Result<Self, ImportError> {
+        match format {
+            Format::Json => serde_json::from_str(content).map_err(ImportError::Json),
+            Format::Csv => Self::from_csv(content),
+            Format::M3u => Ok(Self::from_m3u(content)),
+            Format::Xspf => Self::from_xspf(content),
+        }
+    }
+
+    fn to_csv(&self) -> String {
+        let mut csv = CSV_HEADER.join(",") + "\r\n";
+        for track in &self.tracks {
+            let fields = [
+                track.uri.clone().unwrap_or_default(),
+                track.name.clone(),
+                track.artists.join(CSV_ARTIST_SEPARATOR),
+                track.album.clone().unwrap_or_default(),
+                track
+                    .duration_ms
+                    .map(|duration| duration.to_string())
+                    .unwrap_or_default(),
+                track.isrc.clone().unwrap_or_default(),
+            ];
+            let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+            csv += &(fields.join(",") + "\r\n");
+        }
+        csv
+    }
+
+    /// Parses CSV with a header row, recognising common column names for each field
+    fn from_csv(content: &str) -> Result<Self, ImportError> {
+        let mut rows = parse_csv(content)?.into_iter();
+        let header: Vec<String> = rows
+            .next()
+            .unwrap_or_default()
+            .iter()
+            .map(|column| column.trim().to_lowercase().replace(['_', '-'], " "))
+            .collect();
+        let column = |names: &[&str]| {
+            header
+                .iter()
+                .position(|column| names.contains(&column.as_str()))
+        };
+        let uri = column(&["uri", "spotify uri", "track uri", "url"]);
+        let name = column(&["name", "title", "track name", "track"])
+            .ok_or_else(|| ImportError::Csv("no track name column".to_owned()))?;
+        let artists = column(&[
+            "artists",
+            "artist",
+            "artist name",
+            "artist names",
+            "artist name(s)",
+        ]);
+        let album = column(&["album", "album name"]);
+        let duration_ms = column(&["duration ms", "duration (ms)", "duration"]);
+        let isrc = column(&["isrc"]);
+
+        let tracks = rows
+            .filter(|row| row.iter().any(|field| !field.is_empty()))
+            .map(|row| {
+                let field = |column: Option<usize>| {
+                    column
+                        .and_then(|column| row.get(column))
+                        .map(|field| field.trim())
+                        .filter(|field| !field.is_empty())
+                };
+                ExportedTrack {
+                    uri: field(uri).and_then(spotify_uri),
+                    name: field(Some(name)).unwrap_or_default().to_owned(),
+                    artists: field(artists)
+                        .map(|artists| split_artists(artists, CSV_ARTIST_SEPARATOR.trim()))
+                        .unwrap_or_default(),
+                    album: field(album).map(str::to_owned),
+                    duration_ms: field(duration_ms).and_then(|duration| duration.parse().ok()),
+                    isrc: field(isrc).map(str::to_owned),
+                }
+            })
+            .collect();
+        Ok(Self {
+            tracks,
+            ..Default::default()
+        })
+    }
+
+    fn to_m3u(&self) -> String {
+        let mut m3u = "#EXTM3U\n".to_owned();
+        if !self.name.is_empty() {
+            m3u += &format!("#PLAYLIST:{}\n", single_line(&self.name));
+        }
+        for track in &self.tracks {
+            let duration = track
+                .duration_ms
+                .map_or(-1, |duration| i64::from(duration / 1000));
+            let title = single_line(&display_title(track));
+            m3u += &format!("#EXTINF:{duration},{title}\n");
+            if let Some(album) = &track.album {
+                m3u += &format!("#EXTALB:{}\n", single_line(album));
+            }
+            // Tracks without a URI keep their title as the location, so the entry isn't lost
+            m3u += &(track.uri.clone().unwrap_or(title) + "\n");
+        }
+        m3u
+    }
+
+    /// Parses extended M3U leniently, ignoring unknown directives
+    fn from_m3u(content: &str) -> Self {
+        let mut playlist = Self::default();
+        let mut track = ExportedTrack::default();
+        for line in content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+        {
+            if let Some(name) = line.strip_prefix("#PLAYLIST:") {
+                playlist.name = name.trim().to_owned();
+            } else if let Some(info) = line.strip_prefix("#EXTINF:") {
+                let (duration, title) = info.split_once(',').unwrap_or((info, ""));
+                // The duration may be followed by attributes, e.g. `#EXTINF:123 tvg-id="1",Title`
+                let duration = duration.split_whitespace().next().unwrap_or_default();
+                track.duration_ms = duration
+                    .parse::<u32>()
+                    .ok()
+                    .and_then(|duration| duration.checked_mul(1000));
+                match title.split_once(" - ") {
+                    Some((artists, name)) => {
+                        track.artists = split_artists(artists, ARTIST_SEPARATOR.trim());
+                        track.name = name.trim().to_owned();
+                    }
+                    None => track.name = title.trim().to_owned(),
+                }
+            } else if let Some(album) = line.strip_prefix("#EXTALB:") {
+                track.album = Some(album.trim().to_owned());
+            } else if !line.starts_with('#') {
+                track.uri = spotify_uri(line);
+                if track.name.is_empty() && track.uri.is_none() {
+                    track.name = line.to_owned();
+                }
+                playlist.tracks.push(std::mem::take(&mut track));
+            }
+        }
+        playlist
+    }
+
+    fn to_xspf(&self) -> String {
+        let mut xspf = r#"<?xml version="1.0" encoding="UTF-8"?>
+<playlist version="1" xmlns="http://xspf.org/ns/0/">
+"#
+        .to_owned();
+        xspf += &xml_element(1, "title", &self.name);
+        if let Some(description) = &self.description {
+            xspf += &xml_element(1, "annotation", description);
+        }
+        if let Some(uri) = &self.uri {
+            xspf += &xml_element(1, "identifier", uri);
+        }
+        xspf += "  <trackList>\n";
+        for track in &self.tracks {
+            xspf += "    <track>\n";
+            if let Some(uri) = &track.uri {
+                xspf += &xml_element(3, "identifier", uri);
+            }
+            if let Some(isrc) = &track.isrc {
+                xspf += &xml_element(3, "identifier", &(ISRC_URN.to_owned() + isrc));
+            }
+            xspf += &xml_element(3, "title", &track.name);
+            if !track.artists.is_empty() {
+                xspf += &xml_element(3, "creator", &track.artists.join(ARTIST_SEPARATOR));
+            }
+            if let Some(album) = &track.album {
+                xspf += &xml_element(3, "album", album);
+            }
+            if let Some(duration_ms) = track.duration_ms {
+                xspf += &xml_element(3, "duration", &duration_ms.to_string());
+            }
+            xspf += "    </track>\n";
+        }
+        xspf + "  </trackList>\n</playlist>\n"
+    }
+
+    /// Parses the XSPF elements used by [`Format::Xspf`], along with `location`s of tracks
+    fn from_xspf(content: &str) -> Result<Self, ImportError> {
+        let playlist = xml_elements(content, "playlist")
+            .into_iter()
+            .next()
+            .ok_or_else(|| ImportError::Xspf("no playlist element".to_owned()))?;
+        let (metadata, track_list) = match playlist.find("<trackList") {
+            Some(start) => playlist.split_at(start),
+            None => (playlist, ""),
+        };
+        let first = |xml: &str, tag: &str| {
+            xml_elements(xml, tag)
+                .into_iter()
+                .next()
+                .map(xml_text)
+                .filter(|text| !text.is_empty())
+        };
+
+        let tracks = xml_elements(track_list, "track")
+            .into_iter()
+            .map(|track| {
+                let identifiers: Vec<String> = xml_elements(track, "identifier")
+                    .into_iter()
+                    .chain(xml_elements(track, "location"))
+                    .map(xml_text)
+                    .collect();
+                ExportedTrack {
+                    uri: identifiers
+                        .iter()
+                        .find_map(|identifier| spotify_uri(identifier)),
+                    name: first(track, "title").unwrap_or_default(),
+                    artists: first(track, "creator")
+                        .map(|artists| split_artists(&artists, ARTIST_SEPARATOR.trim()))
+                        .unwrap_or_default(),
+                    album: first(track, "album"),
+                    duration_ms: first(track, "duration")
+                        .and_then(|duration| duration.parse().ok()),
+                    isrc: identifiers
+                        .iter()
+                        .find_map(|identifier| identifier.strip_prefix(ISRC_URN))
+                        .map(str::to_owned),
+                }
+            })
+            .collect();
+        Ok(Self {
+            name: first(metadata, "title").unwrap_or_default(),
+            description: first(metadata, "annotation"),
+            uri: first(metadata, "identifier")
+                .as_deref()
+                .and_then(spotify_uri),
+            tracks,
+        })
+    }
+}
+
+/// The `Artist, Artist - Name` title of a track
+fn display_title(track: &ExportedTrack) -> String {
+    if track.artists.is_empty() {
+        track.name.clone()
+    } else {
+        format!("{} - {}", track.artists.join(ARTIST_SEPARATOR), track.name)
+    }
+}
+
+/// Replaces line breaks in `text`, which would end a line based directive
+fn single_line(text: &str) -> String {
+    text.replace(['\r', '\n'], " ")
+}
+
+/// Splits and trims the `artists` separated by `separator`
+fn split_artists(artists: &str, separator: &str) -> Vec<String> {
+    artists
+        .split(separator)
+        .map(str::trim)
+        .filter(|artist| !artist.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Returns the Spotify URI of a `spotify:` URI or `open.spotify.com` URL `location`
+fn spotify_uri(location: &str) -> Option<String> {
+    let location = location.trim();
+    if location.starts_with("spotify:") {
+        return Some(location.to_owned());
+    }
+    let url = url::Url::parse(location).ok()?;
+    if url.host_str() != Some("open.spotify.com") {
+        return None;
+    }
+    // Localised URLs have a leading segment such as `intl-de`
+    let segments: Vec<&str> = url
+        .path_segments()?
+        .filter(|segment| !segment.starts_with("intl-"))
+        .collect();
+    match segments[..] {
+        [item_type, id] if !id.is_empty() => Some(format!("spotify:{item_type}:{id}")),
+        _ => None,
+    }
+}
+
+/// Quotes a CSV `field` if needed
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+/// Parses the rows of [RFC 4180](https://www.rfc-editor.org/rfc/rfc4180) CSV
+fn parse_csv(content: &str) -> Result<Vec<Vec<String>>, ImportError> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = content.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            (true, '"') => quoted = false,
+            (true, c) => field.push(c),
+            (false, '"') if field.is_empty() => quoted = true,
+            (false, ',') => row.push(std::mem::take(&mut field)),
+            (false, '\r') if chars.peek() == Some(&'\n') => {}
+            (false, '\n' | '\r') => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            (false, c) => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(ImportError::Csv("unterminated quoted field".to_owned()));
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+/// Formats an XML element with the escaped `text`, indented by `depth`
+fn xml_element(depth: usize, tag: &str, text: &str) -> String {
+    let text = text
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;");
+    format!("{}<{tag}>{text}</{tag}>\n", "  ".repeat(depth))
+}
+
+/// Returns the content of each `tag` element in `xml`, which mustn't contain nested `tag` elements
+fn xml_elements<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+    let open = format!("<{tag}");
+    let close = format!("</{tag}>");
+    let mut elements = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        rest = &rest[start + open.len()..];
+        // Skip longer tag names with the same prefix, e.g. `<trackList>` for `<track`
+        if !rest.starts_with(['>', '/', ' ', '\t', '\r', '\n']) {
+            continue;
+        }
+        let Some(end) = rest.find('>') else {
+            break;
+        };
+        if rest[..end].ends_with('/') {
+            elements.push("");
+            rest = &rest[end + 1..];
+            continue;
+        }
+        rest = &rest[end + 1..];
+        let Some(end) = rest.find(&close) else {
+            break;
+        };
+        elements.push(&rest[..end]);
+        rest = &rest[end + close.len()..];
+    }
+    elements
+}
+
+/// Returns the unescaped, trimmed text content of an XML element
+fn xml_text(content: &str) -> String {
+    let content = content.trim();
+    if let Some(cdata) = content
+        .strip_prefix("<![CDATA[")
+        .and_then(|content| content.strip_suffix("]]>"))
+    {
+        return cdata.to_owned();
+    }
+    let mut text = String::with_capacity(content.len());
+    let mut rest = content;
+    while let Some(start) = rest.find('&') {
+        text += &rest[..start];
+        rest = &rest[start..];
+        let entity = rest.find(';').map(|end| (&rest[1..end], end));
+        let unescaped = entity.and_then(|(entity, _)| match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => match entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+            {
+                Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                None => entity.strip_prefix('#').and_then(|dec| dec.parse().ok()),
+            }
+            .and_then(char::from_u32),
+        });
+        match (unescaped, entity) {
+            (Some(c), Some((_, end))) => {
+                text.push(c);
+                rest = &rest[end + 1..];
+            }
+            _ => {
+                text.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    text + rest
+}
+
+impl From<&Playlist> for ExportedPlaylist {
+    /// The playlist with only its first page of items, see [`Client::export_playlist()`]
+    fn from(playlist: &Playlist) -> Self {
+        Self {
+            name: playlist.name.clone(),
+            description: playlist
+                .description
+                .clone()
+                .filter(|description| !description.is_empty()),
+            uri: Some(playlist.uri.clone()),
+            tracks: playlist
+                .tracks
+                .items
+                .iter()
+                .filter_map(|item| item.track.as_ref().map(ExportedTrack::from))
+                .collect(),
+        }
+    }
+}
+
+impl Client {
+    /// Exports the playlist with all its items, unavailable items are skipped
+    pub async fn export_playlist(
+        &mut self,
+        id: &PlaylistId,
+        market: Option<&str>,
+    ) -> utils::request::Result<ExportedPlaylist> {
+        let playlist = self.playlist(id, market).await?;
+        let mut exported = ExportedPlaylist::from(&playlist);
+        let mut page = playlist.tracks;
+        while page.next.is_some() {
+            let offset = page.offset + page.items.len() as u32;
+            page = self
+                .playlist_items(id, market, Some(50), Some(offset))
+                .await?;
+            if page.items.is_empty() {
+                break;
+            }
+            exported.tracks.extend(
+                page.items
+                    .iter()
+                    .filter_map(|item| item.track.as_ref().map(ExportedTrack::from)),
+            );
+        }
+        Ok(exported)
+    }
+
+    /// Resolves the `tracks` to Spotify track IDs, in order,
+    /// by their Spotify URI, then ISRC, then by searching for their name and first artist
+    ///
+    /// Tracks that can't be found, and episodes, are `None`.
+    pub async fn resolve_exported_tracks(
+        &mut self,
+        tracks: &[ExportedTrack],
+        market: Option<&str>,
+    ) -> utils::request::Result<Vec<Option<TrackId>>> {
+        let mut ids = Vec::with_capacity(tracks.len());
+        for track in tracks {
+            ids.push(self.resolve_exported_track(track, market).await?);
+        }
+        Ok(ids)
+    }
+
+    async fn resolve_exported_track(
+        &mut self,
+        track: &ExportedTrack,
+        market: Option<&str>,
+    ) -> utils::request::Result<Option<TrackId>> {
+        if let Some(uri) = &track.uri {
+            if let Ok(id) = TrackId::from_uri(uri) {
+                return Ok(Some(id));
+            }
+        }
+        let queries = [
+            track.isrc.as_ref().map(|isrc| format!("isrc:{isrc}")),
+            Some(&track.name)
+                .filter(|name| !name.is_empty())
+                .map(|name| match track.artists.first() {
+                    Some(artist) => format!("track:{name} artist:{artist}"),
+                    None => format!("track:{name}"),
+                }),
+        ];
+        for query in queries.iter().flatten() {
+            let page = self.search_tracks(query, market, Some(1), None).await?;
+            if let Some(id) = page.items.into_iter().find_map(|track| track.id) {
+                return Ok(Some(id));
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    fn playlist() -> ExportedPlaylist {
+        let track = crate::tracks::tests::track(
+            "11dFghVXANMlKmJXsNCbNl",
+            "Cut To The Feeling",
+            "Carly Rae Jepsen",
+            "USUM71703861",
+        );
+        ExportedPlaylist {
+            name: "Songs, \"quoted\" & <escaped>".to_owned(),
+            description: Some("Line one\nline two".to_owned()),
+            uri: Some("spotify:playlist:37i9dQZF1DXcBWIGoYBM5M".to_owned()),
+            tracks: vec![
+                ExportedTrack::from(&track),
+                ExportedTrack {
+                    uri: None,
+                    name: "Hello, \"World\"".to_owned(),
+                    artists: vec!["First".to_owned(), "Second".to_owned()],
+                    album: None,
+                    duration_ms: Some(61000),
+                    isrc: Some("GBAYE0601498".to_owned()),
+                },
+            ],
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn test_exported_track() {
+        let track = &playlist().tracks[0];
+        assert_eq!(
+            track.uri.as_deref(),
+            Some("spotify:track:11dFghVXANMlKmJXsNCbNl")
+        );
+        assert_eq!(track.artists, ["Carly Rae Jepsen"]);
+        assert_eq!(track.album.as_deref(), Some("Cut To The Feeling Album"));
+        assert_eq!(track.duration_ms, Some(200000));
+        assert_eq!(track.isrc.as_deref(), Some("USUM71703861"));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_json() {
+        let playlist = playlist();
+        assert_eq!(
+            ExportedPlaylist::import(Format::Json, &playlist.export(Format::Json)),
+            Ok(playlist)
+        );
+        assert!(matches!(
+            ExportedPlaylist::import(Format::Json, "{"),
+            Err(ImportError::Json(_))
+        ));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_csv() {
+        let playlist = playlist();
+        let csv = playlist.export(Format::Csv);
+        assert_eq!(
+            csv,
+            "uri,name,artists,album,duration_ms,isrc\r\n\
+            spotify:track:11dFghVXANMlKmJXsNCbNl,Cut To The Feeling,Carly Rae Jepsen,Cut To The Feeling Album,200000,USUM71703861\r\n\
+            ,\"Hello, \"\"World\"\"\",First; Second,,61000,GBAYE0601498\r\n"
+        );
+        assert_eq!(
+            ExportedPlaylist::import(Format::Csv, &csv),
+            Ok(ExportedPlaylist {
+                tracks: playlist.tracks,
+                ..Default::default()
+            })
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_csv_from_other_services() {
+        let csv = "Track Name,Artist Name(s),Album Name,Track URI\n\
+            \"Multi\nline\",Artist,,https://open.spotify.com/intl-de/track/11dFghVXANMlKmJXsNCbNl?si=1\n\
+            \n\
+            Local,,,file.mp3";
+        let playlist = ExportedPlaylist::import(Format::Csv, csv).unwrap();
+        assert_eq!(playlist.tracks.len(), 2);
+        assert_eq!(playlist.tracks[0].name, "Multi\nline");
+        assert_eq!(playlist.tracks[0].artists, ["Artist"]);
+        assert_eq!(playlist.tracks[0].album, None);
+        assert_eq!(
+            playlist.tracks[0].uri.as_deref(),
+            Some("spotify:track:11dFghVXANMlKmJXsNCbNl")
+        );
+        assert_eq!(playlist.tracks[1].uri, None);
+
+        assert_eq!(
+            ExportedPlaylist::import(Format::Csv, "uri\nspotify:track:1"),
+            Err(ImportError::Csv("no track name column".to_owned()))
+        );
+        assert_eq!(
+            ExportedPlaylist::import(Format::Csv, "name\n\"unterminated"),
+            Err(ImportError::Csv("unterminated quoted field".to_owned()))
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_m3u() {
+        let playlist = playlist();
+        let m3u = playlist.export(Format::M3u);
+        assert_eq!(
+            m3u,
+            "#EXTM3U\n\
+            #PLAYLIST:Songs, \"quoted\" & <escaped>\n\
+            #EXTINF:200,Carly Rae Jepsen - Cut To The Feeling\n\
+            #EXTALB:Cut To The Feeling Album\n\
+            spotify:track:11dFghVXANMlKmJXsNCbNl\n\
+            #EXTINF:61,First, Second - Hello, \"World\"\n\
+            First, Second - Hello, \"World\"\n"
+        );
+
+        let imported = ExportedPlaylist::import(Format::M3u, &m3u).unwrap();
+        assert_eq!(imported.name, playlist.name);
+        assert_eq!(imported.description, None);
+        let tracks: Vec<ExportedTrack> = playlist
+            .tracks
+            .into_iter()
+            .map(|track| ExportedTrack {
+                isrc: None,
+                ..track
+            })
+            .collect();
+        assert_eq!(imported.tracks, tracks);
+
+        let imported = ExportedPlaylist::import(
+            Format::M3u,
+            "/music/song.mp3\r\n#EXTINF:-1,Title\r\nsong.flac",
+        )
+        .unwrap();
+        assert_eq!(
+            imported.tracks,
+            [
+                ExportedTrack {
+                    name: "/music/song.mp3".to_owned(),
+                    ..Default::default()
+                },
+                ExportedTrack {
+                    name: "Title".to_owned(),
+                    ..Default::default()
+                }
+            ]
+        );
+
+        // Durations too long to represent in milliseconds are dropped
+        let imported = ExportedPlaylist::import(
+            Format::M3u,
+            "#EXTINF:4294968,Title
+song.flac",
+        )
+        .unwrap();
+        assert_eq!(imported.tracks[0].duration_ms, None);
+        let imported = ExportedPlaylist::import(
+            Format::M3u,
+            "#EXTINF:4294967,Title
+song.flac",
+        )
+        .unwrap();
+        assert_eq!(imported.tracks[0].duration_ms, Some(4294967000));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_xspf() {
+        let playlist = playlist();
+        let xspf = playlist.export(Format::Xspf);
+        assert!(xspf.contains("<title>Songs, &quot;quoted&quot; &amp; &lt;escaped&gt;</title>"));
+        assert!(xspf.contains("<identifier>urn:isrc:USUM71703861</identifier>"));
+        assert_eq!(ExportedPlaylist::import(Format::Xspf, &xspf), Ok(playlist));
+
+        let imported = ExportedPlaylist::import(
+            Format::Xspf,
+            r#"<?xml version="1.0"?><playlist version="1"><trackList><track>
+                <location>https://open.spotify.com/track/11dFghVXANMlKmJXsNCbNl</location>
+                <title><![CDATA[A & B]]></title><creator>&#x41;rtist &#38; Co</creator><album/>
+            </track></trackList></playlist>"#,
+        )
+        .unwrap();
+        assert_eq!(
+            imported.tracks,
+            [ExportedTrack {
+                uri: Some("spotify:track:11dFghVXANMlKmJXsNCbNl".to_owned()),
+                name: "A & B".to_owned(),
+                artists: vec!["Artist & Co".to_owned()],
+                ..Default::default()
+            }]
+        );
+        assert_eq!(
+            ExportedPlaylist::import(Format::Xspf, "<html></html>"),
+            Err(ImportError::Xspf("no playlist element".to_owned()))
+        );
+    }
+}
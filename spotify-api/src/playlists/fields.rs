@@ -0,0 +1,152 @@
+//! Field projection of playlist responses with the `fields` query parameter
+
+/// A field of a [`Fields`] selection
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+enum Field {
+    Include(String),
+    Exclude(String),
+    Nested(String, Fields),
+}
+
+/// A selection of the fields to return from a playlist endpoint, formatted as the `fields` query parameter
+///
+/// ```ignore
+/// let mut fields = Fields::new();
+/// fields
+///     .field("total")
+///     .nested("items", |item| {
+///         item.field("added_at")
+///             .nested("track", |track| {
+///                 track.field("name").field("uri");
+///             });
+///     });
+/// assert_eq!(fields.to_string(), "total,items(added_at,track(name,uri))");
+/// ```
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct Fields(Vec<Field>);
+
+impl Fields {
+    /// Creates an empty selection, which returns all fields
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Includes the field `name`
+    pub fn field(&mut self, name: &str) -> &mut Self {
+        self.0.push(Field::Include(name.to_owned()));
+        self
+    }
+
+    /// Excludes the field `name`
+    pub fn exclude(&mut self, name: &str) -> &mut Self {
+        self.0.push(Field::Exclude(name.to_owned()));
+        self
+    }
+
+    /// Includes the fields of the object `name` selected by `select`
+    pub fn nested<F: FnOnce(&mut Fields)>(&mut self, name: &str, select: F) -> &mut Self {
+        let mut fields = Fields::new();
+        select(&mut fields);
+        self.0.push(Field::Nested(name.to_owned(), fields));
+        self
+    }
+
+    /// Returns if no fields are selected
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl std::fmt::Display for Fields {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, field) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            match field {
+                Field::Include(name) => write!(f, "{name}")?,
+                Field::Exclude(name) => write!(f, "!{name}")?,
+                Field::Nested(name, fields) => write!(f, "{name}({fields})")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A partial response type with the [`Fields`] it requires, for example
+///
+/// ```ignore
+/// #[derive(serde::Deserialize)]
+/// struct ItemUri {
+///     track: Option<TrackUri>,
+/// }
+///
+/// #[derive(serde::Deserialize)]
+/// struct TrackUri {
+///     uri: String,
+/// }
+///
+/// impl Projection for ItemUri {
+///     fn fields() -> Fields {
+///         let mut fields = Fields::new();
+///         fields.nested("track", |track| {
+///             track.field("uri");
+///         });
+///         fields
+///     }
+/// }
+///
+/// let uris: Page<ItemUri> = client.playlist_items_projection(&id, None, None, None).await?;
+/// ```
+pub trait Projection: serde::de::DeserializeOwned {
+    /// The fields required to deserialize the projection
+    fn fields() -> Fields;
+}
+
+/// The fields of a [`Page`](crate::model::Page) of projected `items`
+pub(crate) fn page_fields(items: Fields) -> Fields {
+    let mut fields = Fields::new();
+    for field in ["href", "limit", "next", "offset", "previous", "total"] {
+        fields.field(field);
+    }
+    fields.0.push(Field::Nested("items".to_owned(), items));
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    fn test_fields_display() {
+        assert_eq!(Fields::new().to_string(), "");
+        assert!(Fields::new().is_empty());
+
+        let mut fields = Fields::new();
+        fields
+            .field("total")
+            .nested("items", |item| {
+                item.field("added_at").nested("track", |track| {
+                    track.field("name").field("uri");
+                });
+            })
+            .exclude("href");
+        assert_eq!(
+            fields.to_string(),
+            "total,items(added_at,track(name,uri)),!href"
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_page_fields() {
+        let mut items = Fields::new();
+        items.field("added_at");
+        assert_eq!(
+            page_fields(items).to_string(),
+            "href,limit,next,offset,previous,total,items(added_at)"
+        );
+    }
+}
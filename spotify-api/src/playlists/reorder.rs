@@ -0,0 +1,588 @@
+//! Persistently sorting and shuffling the items of a playlist
+//!
+//! Spotify's clients only sort playlists for display, so [`Client::reorder_playlist()`] computes the new order locally
+//! with [`reorder()`] and moves the items into it with [`reorder_moves()`],
+//! leaving when and by whom each item was added intact.
+
+use super::{
+    sync::longest_increasing, Fields, PlayableItem, PlaylistItem, SyncError, MAX_SYNC_ATTEMPTS,
+};
+use crate::{audio::AudioFeatures, model::*, Client};
+use std::{
+    cmp::Ordering,
+    collections::{BTreeSet, HashMap},
+};
+
+/// What the items of a playlist are sorted by
+///
+/// Ties keep their current order, and items without a value, e.g. unavailable items, are last.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum OrderBy {
+    /// The first artist, then album and track number, or the show of episodes
+    Artist,
+    /// The album and track number, or the show of episodes
+    Album,
+    /// The release date of the album or episode, then album and track number
+    ReleaseDate,
+    /// When the item was added to the playlist
+    AddedAt,
+    /// The popularity of tracks
+    Popularity,
+    /// The tempo of tracks
+    Tempo,
+    /// The key of tracks in [Camelot wheel](AudioFeatures::camelot()) order, so neighbouring tracks mix harmonically
+    Camelot,
+}
+
+/// A new order for the items of a playlist
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum Reordering {
+    /// Sorts the items
+    Sort {
+        /// What the items are sorted by
+        by: OrderBy,
+        /// If the items are sorted from the greatest to the least
+        descending: bool,
+    },
+    /// Shuffles the items, avoiding consecutive items by the same artist or show where possible
+    SmartShuffle {
+        /// The seed of the shuffle, e.g. the current time, the same seed giving the same shuffle of the same items
+        seed: u64,
+    },
+    /// Reverses the items
+    Reverse,
+}
+
+impl Reordering {
+    /// Returns if the reordering needs the audio features of the tracks
+    pub fn needs_features(&self) -> bool {
+        matches!(
+            self,
+            Reordering::Sort {
+                by: OrderBy::Tempo | OrderBy::Camelot,
+                ..
+            }
+        )
+    }
+}
+
+/// A move of the `range_length` items from `range_start` to before the item at `insert_before`,
+/// both positions being before the move
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct RangeMove {
+    /// The position of the first item to move
+    pub range_start: u32,
+    /// The number of items to move
+    pub range_length: u32,
+    /// The position of the item to move them before
+    pub insert_before: u32,
+}
+
+/// The outcome of [`Client::reorder_playlist()`]
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct ReorderReport {
+    /// The snapshot ID of the reordered playlist
+    pub snapshot_id: String,
+    /// The moves applied, in order
+    pub moves: Vec<RangeMove>,
+    /// The number of times moves were computed, more than once if the playlist was concurrently modified
+    pub attempts: u32,
+}
+
+/// A value items are sorted by, compared in order of the variants between different variants
+#[derive(PartialEq, PartialOrd)]
+enum SortValue {
+    Number(f64),
+    Text(String),
+}
+
+/// Returns the new order of the `items` as their current positions
+///
+/// `features` has the audio features of each item if the reordering [needs them](Reordering::needs_features()).
+pub fn reorder(
+    items: &[PlaylistItem],
+    features: &[Option<AudioFeatures>],
+    reordering: &Reordering,
+) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..items.len()).collect();
+    match *reordering {
+        Reordering::Sort { by, descending } => {
+            let values: Vec<Option<Vec<SortValue>>> = items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| sort_value(item, features.get(i).and_then(Option::as_ref), by))
+                .collect();
+            order.sort_by(|lhs, rhs| match (&values[*lhs], &values[*rhs]) {
+                (Some(lhs), Some(rhs)) => {
+                    let ordering = lhs.partial_cmp(rhs).unwrap_or(Ordering::Equal);
+                    if descending {
+                        ordering.reverse()
+                    } else {
+                        ordering
+                    }
+                }
+                (lhs, rhs) => rhs.is_some().cmp(&lhs.is_some()),
+            });
+        }
+        Reordering::SmartShuffle { seed } => {
+            let mut random = SplitMix64(seed);
+            for i in (1..order.len()).rev() {
+                order.swap(i, (random.next() % (i as u64 + 1)) as usize);
+            }
+            let artists: Vec<Option<&str>> = items.iter().map(artist).collect();
+            order = spread_artists(order, &artists);
+        }
+        Reordering::Reverse => order.reverse(),
+    }
+    order
+}
+
+/// The value of the `item` to sort by for `by`, if it has one
+fn sort_value(
+    item: &PlaylistItem,
+    features: Option<&AudioFeatures>,
+    by: OrderBy,
+) -> Option<Vec<SortValue>> {
+    use SortValue::*;
+    let text = |text: &str| Text(text.to_lowercase());
+    let number = |number: u32| Number(number.into());
+    Some(match (by, item.track.as_ref()?) {
+        (OrderBy::AddedAt, _) => vec![Number(
+            parse_timestamp(item.added_at.as_deref()?)?.as_secs_f64(),
+        )],
+        (OrderBy::Artist, PlayableItem::Track(track)) => vec![
+            text(&track.artists.first()?.name),
+            text(&track.album.name),
+            number(track.disc_number),
+            number(track.track_number),
+        ],
+        (OrderBy::Album, PlayableItem::Track(track)) => vec![
+            text(&track.album.name),
+            number(track.disc_number),
+            number(track.track_number),
+        ],
+        (OrderBy::ReleaseDate, PlayableItem::Track(track)) => vec![
+            Text(track.album.release_date.clone()?),
+            text(&track.album.name),
+            number(track.disc_number),
+            number(track.track_number),
+        ],
+        (OrderBy::Artist | OrderBy::Album, PlayableItem::Episode(episode)) => {
+            vec![text(&episode.show.name), Text(episode.release_date.clone())]
+        }
+        (OrderBy::ReleaseDate, PlayableItem::Episode(episode)) => {
+            vec![Text(episode.release_date.clone())]
+        }
+        (OrderBy::Popularity, PlayableItem::Track(track)) => vec![number(track.popularity)],
+        (OrderBy::Tempo, PlayableItem::Track(_)) => vec![Number(features?.tempo)],
+        (OrderBy::Camelot, PlayableItem::Track(_)) => {
+            let (number, letter) = features?.camelot()?;
+            vec![Number(number.into()), Text(letter.into())]
+        }
+        (OrderBy::Popularity | OrderBy::Tempo | OrderBy::Camelot, PlayableItem::Episode(_)) => {
+            return None
+        }
+    })
+}
+
+/// The artist, or show of episodes, the `item` is by
+fn artist(item: &PlaylistItem) -> Option<&str> {
+    match item.track.as_ref()? {
+        PlayableItem::Track(track) => {
+            let artist = track.artists.first()?;
+            Some(artist.id.as_ref().map_or(&artist.name, |id| id.as_str()))
+        }
+        PlayableItem::Episode(episode) => Some(episode.show.id.as_str()),
+    }
+}
+
+/// Reorders the positions in `order` so no two consecutive items have the same artist where possible,
+/// otherwise keeping to `order`
+///
+/// At each step the earliest remaining item is taken that isn't by the previous artist,
+/// and leaves few enough items by any one artist for the rest to be spread.
+fn spread_artists(order: Vec<usize>, artists: &[Option<&str>]) -> Vec<usize> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for artist in order.iter().filter_map(|i| artists[*i]) {
+        *counts.entry(artist).or_default() += 1;
+    }
+    // The artists by how many items of theirs remain, so the most remaining is the last
+    let mut by_count: BTreeSet<(usize, &str)> = counts
+        .iter()
+        .map(|(artist, count)| (*count, *artist))
+        .collect();
+
+    let mut remaining = order;
+    let mut spread = Vec::with_capacity(remaining.len());
+    let mut previous: Option<&str> = None;
+    while !remaining.is_empty() {
+        let rest = remaining.len() - 1;
+        // The rest can follow an item by `artist` if none of their artists has more than every other of them,
+        // and `artist` itself not enough to also be first
+        let spreadable = |artist: Option<&str>| {
+            let mut most = by_count.iter().rev();
+            let most = match most.next() {
+                Some((count, most_artist)) if Some(*most_artist) == artist => {
+                    (count - 1).max(most.next().map_or(0, |(count, _)| *count))
+                }
+                Some((count, _)) => *count,
+                None => 0,
+            };
+            let own = artist.map_or(0, |artist| counts[artist] - 1);
+            most <= rest.div_ceil(2) && own <= rest / 2
+        };
+        let differs = |i: &usize| artists[*i].is_none() || artists[*i] != previous;
+        let next = remaining
+            .iter()
+            .position(|i| differs(i) && spreadable(artists[*i]))
+            .or_else(|| remaining.iter().position(differs))
+            .unwrap_or(0);
+
+        let i = remaining.remove(next);
+        if let Some(artist) = artists[i] {
+            let count = counts.get_mut(artist).expect("A counted artist");
+            by_count.remove(&(*count, artist));
+            *count -= 1;
+            if *count > 0 {
+                by_count.insert((*count, artist));
+            }
+        }
+        previous = artists[i];
+        spread.push(i);
+    }
+    spread
+}
+
+/// A small [SplitMix64](https://prng.di.unimi.it/splitmix64.c) pseudorandom generator for seeded shuffles
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+}
+
+/// Computes the moves that reorder the items of a playlist into `order`,
+/// the current position of the item at each new position
+///
+/// Runs of items that stay consecutive are moved together,
+/// and the longest sequence of runs already in order is kept in place,
+/// so a run is moved at most once.
+pub fn reorder_moves(order: &[usize]) -> Vec<RangeMove> {
+    // The new position of the item at each current position
+    let mut targets = vec![0; order.len()];
+    for (target, current) in order.iter().enumerate() {
+        targets[*current] = target;
+    }
+
+    // Runs of consecutive targets in current order, as their first target and length
+    let mut runs: Vec<(usize, usize)> = Vec::new();
+    for target in targets {
+        match runs.last_mut() {
+            Some((first, len)) if *first + *len == target => *len += 1,
+            _ => runs.push((target, 1)),
+        }
+    }
+
+    let firsts: Vec<usize> = runs.iter().map(|(first, _)| *first).collect();
+    let settled: BTreeSet<usize> = longest_increasing(&firsts).into_iter().collect();
+    let mut unsettled: Vec<usize> = firsts
+        .into_iter()
+        .filter(|first| !settled.contains(first))
+        .collect();
+    unsettled.sort_unstable();
+
+    // Every target before an unsettled run's is in place once the runs are moved in order of their targets,
+    // so each is moved to after the item with the target before its first
+    let items = |runs: &[(usize, usize)]| runs.iter().map(|(_, len)| *len).sum::<usize>();
+    let mut moves = Vec::new();
+    for first in unsettled {
+        let from = runs
+            .iter()
+            .position(|(run, _)| *run == first)
+            .expect("The run to be in the order");
+        let from_item = items(&runs[..from]);
+        let (_, len) = runs.remove(from);
+        let to = match first.checked_sub(1) {
+            Some(previous) => {
+                runs.iter()
+                    .position(|(run, len)| (*run..*run + *len).contains(&previous))
+                    .expect("The previous target to be in the order")
+                    + 1
+            }
+            None => 0,
+        };
+        let to_item = items(&runs[..to]);
+        runs.insert(to, (first, len));
+        if to_item != from_item {
+            moves.push(RangeMove {
+                range_start: from_item as u32,
+                range_length: len as u32,
+                insert_before: if to_item > from_item {
+                    to_item + len
+                } else {
+                    to_item
+                } as u32,
+            });
+        }
+    }
+    moves
+}
+
+impl Client {
+    /// Sorts, shuffles or reverses the items of the playlist with [`reorder()`],
+    /// persisting the order with the moves from [`reorder_moves()`]
+    ///
+    /// Requires [`Scopes::PlaylistModifyPublic`](crate::authorisation::Scopes::PlaylistModifyPublic)
+    /// or [`Scopes::PlaylistModifyPrivate`](crate::authorisation::Scopes::PlaylistModifyPrivate).
+    /// Each move is made against the snapshot it was computed from,
+    /// and the order is computed again if the playlist was modified concurrently.
+    pub async fn reorder_playlist(
+        &mut self,
+        id: &PlaylistId,
+        reordering: &Reordering,
+    ) -> Result<ReorderReport, SyncError> {
+        let mut report = ReorderReport::default();
+        loop {
+            if report.attempts == MAX_SYNC_ATTEMPTS {
+                return Err(SyncError::Conflict);
+            }
+            report.attempts += 1;
+            let res = match self.playlist_snapshot_items(id, &Fields::new()).await {
+                Ok((snapshot_id, items)) => {
+                    let features = match reordering.needs_features() {
+                        true => self.playlist_items_features(&items).await?,
+                        false => Vec::new(),
+                    };
+                    let moves = reorder_moves(&reorder(&items, &features, reordering));
+                    self.apply_range_moves(id, snapshot_id, moves, &mut report)
+                        .await
+                }
+                Err(err) => Err(err),
+            };
+            match res {
+                Ok(()) => return Ok(report),
+                Err(SyncError::Conflict) => log::debug!("playlist {id} modified while reordering"),
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Returns the audio features of each of the `items`, `None` for items that aren't tracks
+    async fn playlist_items_features(
+        &mut self,
+        items: &[PlaylistItem],
+    ) -> Result<Vec<Option<AudioFeatures>>, SyncError> {
+        let ids: Vec<Option<TrackId>> = items
+            .iter()
+            .map(|item| match &item.track {
+                Some(PlayableItem::Track(track)) => track.id.clone(),
+                _ => None,
+            })
+            .collect();
+        Ok(self.audio_features_batched(&ids).await?)
+    }
+
+    /// Applies the `moves` in order against the playlist's version `snapshot_id`,
+    /// adding each to the `report` once it succeeds
+    async fn apply_range_moves(
+        &mut self,
+        id: &PlaylistId,
+        mut snapshot_id: String,
+        moves: Vec<RangeMove>,
+        report: &mut ReorderReport,
+    ) -> Result<(), SyncError> {
+        for range_move in moves {
+            snapshot_id = self
+                .reorder_playlist_items(
+                    id,
+                    range_move.range_start,
+                    range_move.insert_before,
+                    range_move.range_length,
+                    Some(&snapshot_id),
+                )
+                .await?;
+            report.moves.push(range_move);
+        }
+        report.snapshot_id = snapshot_id;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{audio::tests::audio_features, tracks::tests::track};
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    /// Applies the `moves` to the `items` as the API would
+    fn apply(mut items: Vec<usize>, moves: &[RangeMove]) -> Vec<usize> {
+        for range_move in moves {
+            let start = range_move.range_start as usize;
+            let len = range_move.range_length as usize;
+            let before = range_move.insert_before as usize;
+            assert!(before < start || before > start + len, "{range_move:?}");
+            let moved: Vec<usize> = items.drain(start..start + len).collect();
+            let at = if before > start { before - len } else { before };
+            items.splice(at..at, moved);
+        }
+        items
+    }
+
+    fn assert_reorders(order: &[usize]) -> Vec<RangeMove> {
+        let moves = reorder_moves(order);
+        assert_eq!(
+            apply((0..order.len()).collect(), &moves),
+            order,
+            "{moves:?}"
+        );
+        moves
+    }
+
+    fn item(id: &str, artist: &str, added_at: Option<&str>) -> PlaylistItem {
+        let mut track = track(id, id, artist, "USUM71703861");
+        // Artists are told apart by name
+        track.artists[0].id = None;
+        PlaylistItem {
+            added_at: added_at.map(str::to_owned),
+            added_by: None,
+            is_local: false,
+            track: Some(PlayableItem::Track(track)),
+        }
+    }
+
+    fn unavailable() -> PlaylistItem {
+        PlaylistItem {
+            added_at: None,
+            added_by: None,
+            is_local: false,
+            track: None,
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn test_reorder_moves() {
+        assert!(assert_reorders(&[]).is_empty());
+        assert!(assert_reorders(&[0, 1, 2]).is_empty());
+        // Reversing moves every item but one
+        assert_eq!(assert_reorders(&[4, 3, 2, 1, 0]).len(), 4);
+        // Runs that stay together are moved at once
+        assert_eq!(
+            assert_reorders(&[3, 4, 5, 0, 1, 2]),
+            [RangeMove {
+                range_start: 0,
+                range_length: 3,
+                insert_before: 6
+            }]
+        );
+        assert_eq!(assert_reorders(&[2, 3, 0, 1, 6, 7, 4, 5]).len(), 2);
+        assert_eq!(assert_reorders(&[1, 2, 3, 4, 0]).len(), 1);
+
+        let mut random = SplitMix64(7);
+        for len in 0..40 {
+            let mut order: Vec<usize> = (0..len).collect();
+            for i in (1..len).rev() {
+                order.swap(i, (random.next() % (i as u64 + 1)) as usize);
+            }
+            let moves = assert_reorders(&order);
+            assert!(moves.len() <= len.saturating_sub(1));
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn test_sort() {
+        let items = [
+            item("c", "Band", Some("2024-01-03T00:00:00Z")),
+            unavailable(),
+            item("a", "artist", Some("2024-01-01T00:00:00Z")),
+            item("b", "Band", None),
+        ];
+        let sort = |by, descending| reorder(&items, &[], &Reordering::Sort { by, descending });
+        // Then by album, named after the track
+        assert_eq!(sort(OrderBy::Artist, false), [2, 3, 0, 1]);
+        // Unavailable items stay last and ties keep their order when descending
+        assert_eq!(sort(OrderBy::Artist, true), [0, 3, 2, 1]);
+        assert_eq!(sort(OrderBy::AddedAt, false), [2, 0, 1, 3]);
+        assert_eq!(sort(OrderBy::AddedAt, true), [0, 2, 1, 3]);
+        // Audio features are needed for tempo
+        assert_eq!(sort(OrderBy::Tempo, false), [0, 1, 2, 3]);
+        assert_eq!(reorder(&items, &[], &Reordering::Reverse), [3, 2, 1, 0]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_sort_camelot() {
+        let items = [
+            item("c", "Band", None),
+            item("g", "Band", None),
+            item("am", "Band", None),
+            item("b", "Band", None),
+            item("none", "Band", None),
+        ];
+        let features = [
+            Some(audio_features("c", 0.5, 120.0, 0, 1)),
+            Some(audio_features("g", 0.5, 100.0, 7, 1)),
+            Some(audio_features("am", 0.5, 140.0, 9, 0)),
+            Some(audio_features("b", 0.5, 90.0, 11, 1)),
+            Some(audio_features("none", 0.5, 80.0, -1, 1)),
+        ];
+        let reordering = Reordering::Sort {
+            by: OrderBy::Camelot,
+            descending: false,
+        };
+        assert!(reordering.needs_features());
+        // 1B, 8A, 8B, 9B, then without a key
+        assert_eq!(reorder(&items, &features, &reordering), [3, 2, 0, 1, 4]);
+        let reordering = Reordering::Sort {
+            by: OrderBy::Tempo,
+            descending: true,
+        };
+        assert_eq!(reorder(&items, &features, &reordering), [2, 0, 1, 3, 4]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_smart_shuffle() {
+        let artists = ["a", "a", "a", "a", "b", "b", "c", "c", "d"];
+        let items: Vec<PlaylistItem> = artists
+            .iter()
+            .enumerate()
+            .map(|(i, artist)| item(&i.to_string(), artist, None))
+            .collect();
+
+        for seed in 0..50 {
+            let order = reorder(&items, &[], &Reordering::SmartShuffle { seed });
+            let mut sorted = order.clone();
+            sorted.sort_unstable();
+            assert_eq!(sorted, (0..artists.len()).collect::<Vec<_>>());
+            let order = by_artist(&order, &artists);
+            assert!(order.windows(2).all(|pair| pair[0] != pair[1]), "{order:?}");
+        }
+        assert_eq!(
+            reorder(&items, &[], &Reordering::SmartShuffle { seed: 1 }),
+            reorder(&items, &[], &Reordering::SmartShuffle { seed: 1 })
+        );
+        assert_ne!(
+            reorder(&items, &[], &Reordering::SmartShuffle { seed: 1 }),
+            reorder(&items, &[], &Reordering::SmartShuffle { seed: 2 })
+        );
+
+        // Too many items by one artist to spread them all
+        let order = spread_artists(
+            vec![0, 1, 2, 3],
+            &[Some("a"), Some("a"), Some("a"), Some("b")],
+        );
+        assert_eq!(
+            by_artist(&order, &["a", "a", "a", "b"]),
+            ["a", "b", "a", "a"]
+        );
+    }
+
+    fn by_artist<'a>(order: &[usize], artists: &[&'a str]) -> Vec<&'a str> {
+        order.iter().map(|i| artists[*i]).collect()
+    }
+}
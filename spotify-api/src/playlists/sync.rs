@@ -0,0 +1,574 @@
+//! Syncing the items of a playlist to a desired list with minimal changes
+//!
+//! Replacing every item of a playlist resets when each item was added,
+//! instead [`diff()`] computes the removals, moves and additions needed to turn the current items into the desired ones,
+//! leaving items already in the playlist in place where possible.
+
+use super::{fields::page_fields, Fields, Projection, MAX_MODIFIED_ITEMS};
+use crate::{model::*, Client};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+
+/// The maximum number of times [`Client::sync_playlist()`] modifies a playlist,
+/// before giving up on a playlist being concurrently modified
+pub const MAX_SYNC_ATTEMPTS: u32 = 3;
+
+/// A modification of the items of a playlist
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum Operation {
+    /// Removes every occurrence of each of the `uris`
+    Remove {
+        /// The URIs to remove, at most [`MAX_MODIFIED_ITEMS`]
+        uris: Vec<String>,
+    },
+    /// Moves the item at `range_start` to before the item at `insert_before`, both positions being before the move
+    Move {
+        /// The position of the item to move
+        range_start: u32,
+        /// The position of the item to move it before
+        insert_before: u32,
+    },
+    /// Inserts the `uris` at `position`
+    Add {
+        /// The position to insert the items at
+        position: u32,
+        /// The URIs to add, at most [`MAX_MODIFIED_ITEMS`]
+        uris: Vec<String>,
+    },
+}
+
+/// Computes the operations that turn the `current` item URIs of a playlist into the `desired` ones, applied in order
+///
+/// Removing a URI removes every occurrence of it,
+/// so a URI occurring more often than desired is removed and its desired occurrences added again.
+/// Otherwise the most items possible are kept in place, the rest are moved, then the missing items are added.
+pub fn diff<C: AsRef<str>, D: AsRef<str>>(current: &[C], desired: &[D]) -> Vec<Operation> {
+    let mut desired_positions: HashMap<&str, VecDeque<usize>> = HashMap::new();
+    for (i, uri) in desired.iter().enumerate() {
+        desired_positions
+            .entry(uri.as_ref())
+            .or_default()
+            .push_back(i);
+    }
+    let mut current_counts: HashMap<&str, usize> = HashMap::new();
+    for uri in current {
+        *current_counts.entry(uri.as_ref()).or_default() += 1;
+    }
+
+    let mut operations = Vec::new();
+    let mut removed = HashSet::new();
+    let removals: Vec<String> = current
+        .iter()
+        .map(AsRef::as_ref)
+        .filter(|uri| {
+            current_counts[uri] > desired_positions.get(uri).map_or(0, VecDeque::len)
+                && removed.insert(*uri)
+        })
+        .map(str::to_owned)
+        .collect();
+    for uris in removals.chunks(MAX_MODIFIED_ITEMS) {
+        operations.push(Operation::Remove {
+            uris: uris.to_vec(),
+        });
+    }
+
+    // The n-th kept occurrence of a URI ends up as its n-th desired occurrence
+    let mut order: Vec<usize> = current
+        .iter()
+        .map(AsRef::as_ref)
+        .filter(|uri| !removed.contains(uri))
+        .map(|uri| {
+            desired_positions
+                .get_mut(uri)
+                .and_then(VecDeque::pop_front)
+                .expect("A kept URI to be desired")
+        })
+        .collect();
+    let kept: BTreeSet<usize> = order.iter().copied().collect();
+
+    let mut settled: BTreeSet<usize> = longest_increasing(&order).into_iter().collect();
+    let unsettled: BTreeSet<usize> = kept.difference(&settled).copied().collect();
+    for target in unsettled {
+        let from = position_of(&order, target);
+        let to = settled
+            .range(..target)
+            .next_back()
+            .map_or(0, |previous| position_of(&order, *previous) + 1);
+        settled.insert(target);
+        if to == from || to == from + 1 {
+            continue;
+        }
+        order.remove(from);
+        order.insert(if to > from { to - 1 } else { to }, target);
+        operations.push(Operation::Move {
+            range_start: from as u32,
+            insert_before: to as u32,
+        });
+    }
+
+    // Every earlier desired item is in place once the additions are made in order
+    let mut additions = (0..desired.len()).filter(|i| !kept.contains(i)).peekable();
+    while let Some(position) = additions.next() {
+        let mut uris = vec![desired[position].as_ref().to_owned()];
+        while uris.len() < MAX_MODIFIED_ITEMS && additions.peek() == Some(&(position + uris.len()))
+        {
+            let next = additions.next().expect("A peeked addition");
+            uris.push(desired[next].as_ref().to_owned());
+        }
+        operations.push(Operation::Add {
+            position: position as u32,
+            uris,
+        });
+    }
+    operations
+}
+
+/// Returns the position of `target` in `order`
+fn position_of(order: &[usize], target: usize) -> usize {
+    order
+        .iter()
+        .position(|t| *t == target)
+        .expect("The target to be in the order")
+}
+
+/// Returns a longest strictly increasing subsequence of the distinct `values`
+pub(super) fn longest_increasing(values: &[usize]) -> Vec<usize> {
+    // The index into `values` of the smallest tail of an increasing subsequence of each length
+    let mut tails: Vec<usize> = Vec::new();
+    let mut previous: Vec<Option<usize>> = vec![None; values.len()];
+    for (i, value) in values.iter().enumerate() {
+        let len = tails.partition_point(|tail| values[*tail] < *value);
+        previous[i] = len.checked_sub(1).map(|len| tails[len]);
+        if len == tails.len() {
+            tails.push(i);
+        } else {
+            tails[len] = i;
+        }
+    }
+
+    let mut subsequence = Vec::with_capacity(tails.len());
+    let mut next = tails.last().copied();
+    while let Some(i) = next {
+        subsequence.push(values[i]);
+        next = previous[i];
+    }
+    subsequence.reverse();
+    subsequence
+}
+
+/// The outcome of [`Client::sync_playlist()`]
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct SyncReport {
+    /// The snapshot ID of the synced playlist
+    pub snapshot_id: String,
+    /// The operations applied, in order
+    pub operations: Vec<Operation>,
+    /// The number of times the playlist was modified, more than once if it was concurrently modified
+    pub attempts: u32,
+}
+
+/// [`Client::sync_playlist()`] Errors
+#[derive(Debug)]
+pub enum SyncError {
+    /// The playlist kept being modified concurrently, see [`MAX_SYNC_ATTEMPTS`],
+    /// or was modified since the version a change was planned against
+    Conflict,
+    /// The playlist has the given number of items without a track, which can't be addressed by URI
+    UnavailableItems(usize),
+    /// Errors reading or modifying the playlist
+    Request(utils::request::Error),
+}
+impl std::error::Error for SyncError {}
+impl std::fmt::Display for SyncError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SyncError::Conflict => write!(f, "the playlist was modified concurrently"),
+            SyncError::UnavailableItems(count) => write!(
+                f,
+                "the playlist has {count} unavailable items that can't be synced"
+            ),
+            SyncError::Request(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl PartialEq for SyncError {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (SyncError::UnavailableItems(l), SyncError::UnavailableItems(r)) => l == r,
+            (SyncError::Request(l_req), SyncError::Request(r_req)) => {
+                l_req.to_string() == r_req.to_string()
+            }
+            (_self, _other) => core::mem::discriminant(self) == core::mem::discriminant(other),
+        }
+    }
+}
+
+impl From<utils::request::Error> for SyncError {
+    fn from(err: utils::request::Error) -> Self {
+        match err {
+            utils::request::Error::Status(ref status)
+                if status.status == reqwest::StatusCode::CONFLICT
+                    || status.status == reqwest::StatusCode::PRECONDITION_FAILED =>
+            {
+                SyncError::Conflict
+            }
+            err => SyncError::Request(err),
+        }
+    }
+}
+
+/// The snapshot ID of a playlist, identifying the version of its items
+#[derive(serde::Deserialize)]
+struct Snapshot {
+    snapshot_id: String,
+}
+
+impl Projection for Snapshot {
+    fn fields() -> Fields {
+        let mut fields = Fields::new();
+        fields.field("snapshot_id");
+        fields
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct ItemUri {
+    track: Option<TrackUri>,
+}
+
+#[derive(serde::Deserialize)]
+struct TrackUri {
+    uri: String,
+    #[serde(default)]
+    linked_from: Option<LinkedUri>,
+}
+
+#[derive(serde::Deserialize)]
+struct LinkedUri {
+    uri: String,
+}
+
+impl TrackUri {
+    /// The URI stored in the playlist, the original track's if it was relinked to another in the market
+    fn into_uri(self) -> String {
+        self.linked_from
+            .map_or(self.uri, |linked_from| linked_from.uri)
+    }
+}
+
+impl Projection for ItemUri {
+    fn fields() -> Fields {
+        let mut fields = Fields::new();
+        fields.nested("track", |track| {
+            track.field("uri").nested("linked_from", |linked_from| {
+                linked_from.field("uri");
+            });
+        });
+        fields
+    }
+}
+
+impl Client {
+    /// Syncs the items of the playlist to the `desired` track or episode URIs, in order,
+    /// with the minimal operations from [`diff()`]
+    ///
+    /// Requires [`Scopes::PlaylistModifyPublic`](crate::authorisation::Scopes::PlaylistModifyPublic)
+    /// or [`Scopes::PlaylistModifyPrivate`](crate::authorisation::Scopes::PlaylistModifyPrivate).
+    /// Each operation is made against the snapshot it was computed from,
+    /// and the playlist is diffed again after syncing in case it was modified concurrently.
+    pub async fn sync_playlist<U: AsRef<str>>(
+        &mut self,
+        id: &PlaylistId,
+        desired: &[U],
+    ) -> Result<SyncReport, SyncError> {
+        let mut report = SyncReport::default();
+        loop {
+            let res = match self.playlist_uris(id).await {
+                Ok((snapshot_id, current)) => {
+                    let operations = diff(&current, desired);
+                    if operations.is_empty() {
+                        report.snapshot_id = snapshot_id;
+                        return Ok(report);
+                    }
+                    if report.attempts == MAX_SYNC_ATTEMPTS {
+                        return Err(SyncError::Conflict);
+                    }
+                    report.attempts += 1;
+                    self.apply_playlist_operations(
+                        id,
+                        snapshot_id,
+                        operations,
+                        &mut report.operations,
+                    )
+                    .await
+                }
+                Err(SyncError::Conflict) if report.attempts < MAX_SYNC_ATTEMPTS => {
+                    report.attempts += 1;
+                    Err(SyncError::Conflict)
+                }
+                Err(err) => Err(err),
+            };
+            match res {
+                Ok(()) => {}
+                Err(SyncError::Conflict) => log::debug!("playlist {id} modified while syncing"),
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Syncs the items of the playlist's version `snapshot_id` to the `desired` URIs once,
+    /// failing with [`SyncError::Conflict`] instead of diffing again if the playlist is at another version
+    pub(crate) async fn sync_playlist_snapshot<U: AsRef<str>>(
+        &mut self,
+        id: &PlaylistId,
+        snapshot_id: &str,
+        desired: &[U],
+    ) -> Result<(), SyncError> {
+        let (current_id, current) = self.playlist_uris(id).await?;
+        if current_id != snapshot_id {
+            return Err(SyncError::Conflict);
+        }
+        let operations = diff(&current, desired);
+        self.apply_playlist_operations(id, current_id, operations, &mut Vec::new())
+            .await
+    }
+
+    /// Returns the snapshot ID and item URIs of the playlist
+    async fn playlist_uris(&mut self, id: &PlaylistId) -> Result<(String, Vec<String>), SyncError> {
+        let (snapshot_id, items): (String, Vec<ItemUri>) = self
+            .playlist_snapshot_items(id, &page_fields(ItemUri::fields()))
+            .await?;
+        let mut uris = Vec::with_capacity(items.len());
+        let mut unavailable = 0;
+        for item in items {
+            match item.track {
+                Some(track) => uris.push(track.into_uri()),
+                None => unavailable += 1,
+            }
+        }
+        if unavailable > 0 {
+            return Err(SyncError::UnavailableItems(unavailable));
+        }
+        Ok((snapshot_id, uris))
+    }
+
+    /// Returns the snapshot ID and every item of the playlist, with only the `fields` of each page selected
+    pub(super) async fn playlist_snapshot_items<T: serde::de::DeserializeOwned>(
+        &mut self,
+        id: &PlaylistId,
+        fields: &Fields,
+    ) -> Result<(String, Vec<T>), SyncError> {
+        let snapshot: Snapshot = self.playlist_projection(id, None).await?;
+        let mut items = Vec::new();
+        loop {
+            let page: Page<T> = self
+                .playlist_items_fields(id, None, fields, Some(50), Some(items.len() as u32))
+                .await?;
+            let done = page.next.is_none() || page.items.is_empty();
+            items.extend(page.items);
+            if done {
+                break;
+            }
+        }
+        // The pages are only consistent if the playlist wasn't modified while reading them
+        let after: Snapshot = self.playlist_projection(id, None).await?;
+        if after.snapshot_id != snapshot.snapshot_id {
+            return Err(SyncError::Conflict);
+        }
+        Ok((snapshot.snapshot_id, items))
+    }
+
+    /// Applies the `operations` in order against the playlist's version `snapshot_id`,
+    /// adding each to `applied` once it succeeds
+    async fn apply_playlist_operations(
+        &mut self,
+        id: &PlaylistId,
+        mut snapshot_id: String,
+        operations: Vec<Operation>,
+        applied: &mut Vec<Operation>,
+    ) -> Result<(), SyncError> {
+        for operation in operations {
+            snapshot_id = match &operation {
+                Operation::Remove { uris } => {
+                    self.remove_playlist_items(id, uris, Some(&snapshot_id))
+                        .await?
+                }
+                Operation::Move {
+                    range_start,
+                    insert_before,
+                } => {
+                    self.reorder_playlist_items(
+                        id,
+                        *range_start,
+                        *insert_before,
+                        1,
+                        Some(&snapshot_id),
+                    )
+                    .await?
+                }
+                Operation::Add { position, uris } => {
+                    self.add_playlist_items(id, uris, Some(*position)).await?
+                }
+            };
+            applied.push(operation);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    /// Applies the `operations` to the `items` as the API would
+    fn apply(items: &[&str], operations: &[Operation]) -> Vec<String> {
+        let mut items: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+        for operation in operations {
+            match operation {
+                Operation::Remove { uris } => items.retain(|item| !uris.contains(item)),
+                Operation::Move {
+                    range_start,
+                    insert_before,
+                } => {
+                    let (from, to) = (*range_start as usize, *insert_before as usize);
+                    let item = items.remove(from);
+                    items.insert(if to > from { to - 1 } else { to }, item);
+                }
+                Operation::Add { position, uris } => {
+                    let position = *position as usize;
+                    items.splice(position..position, uris.iter().cloned());
+                }
+            }
+        }
+        items
+    }
+
+    fn assert_syncs(current: &[&str], desired: &[&str]) -> Vec<Operation> {
+        let operations = diff(current, desired);
+        assert_eq!(apply(current, &operations), desired, "{operations:?}");
+        operations
+    }
+
+    #[wasm_bindgen_test]
+    fn test_diff_unchanged() {
+        assert!(assert_syncs(&[], &[]).is_empty());
+        assert!(assert_syncs(&["a", "b", "a"], &["a", "b", "a"]).is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_diff_add_remove() {
+        assert_eq!(
+            assert_syncs(&["a", "b", "c"], &["x", "a", "c", "y", "z"]),
+            [
+                Operation::Remove {
+                    uris: vec!["b".to_owned()]
+                },
+                Operation::Add {
+                    position: 0,
+                    uris: vec!["x".to_owned()]
+                },
+                Operation::Add {
+                    position: 3,
+                    uris: vec!["y".to_owned(), "z".to_owned()]
+                },
+            ]
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_diff_moves() {
+        // Only the item out of place is moved
+        assert_eq!(
+            assert_syncs(&["e", "a", "b", "c", "d"], &["a", "b", "c", "d", "e"]),
+            [Operation::Move {
+                range_start: 0,
+                insert_before: 5
+            }]
+        );
+        assert_eq!(
+            assert_syncs(&["a", "b", "c", "d"], &["d", "c", "b", "a"]).len(),
+            3
+        );
+        assert_syncs(&["a", "b", "c", "d", "e"], &["c", "e", "a", "x", "d", "b"]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_diff_duplicates() {
+        // An extra occurrence can only be removed along with the rest
+        assert_eq!(
+            assert_syncs(&["a", "b", "a"], &["b", "a"]),
+            [
+                Operation::Remove {
+                    uris: vec!["a".to_owned()]
+                },
+                Operation::Add {
+                    position: 1,
+                    uris: vec!["a".to_owned()]
+                },
+            ]
+        );
+        assert_syncs(&["a", "b"], &["a", "b", "a", "a"]);
+        assert_syncs(&["b", "a", "c", "a"], &["a", "c", "a", "b"]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_diff_batches() {
+        let uris: Vec<String> = (0..250).map(|i| format!("spotify:track:{i}")).collect();
+        let uris: Vec<&str> = uris.iter().map(String::as_str).collect();
+        let operations = assert_syncs(&[], &uris);
+        assert_eq!(operations.len(), 3);
+        assert!(
+            matches!(&operations[2], Operation::Add { position: 200, uris } if uris.len() == 50)
+        );
+
+        let operations = assert_syncs(&uris, &[]);
+        assert_eq!(operations.len(), 3);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_longest_increasing() {
+        assert_eq!(longest_increasing(&[]), Vec::<usize>::new());
+        assert_eq!(longest_increasing(&[3, 0, 1, 4, 2]), [0, 1, 2]);
+        assert_eq!(longest_increasing(&[4, 3, 2, 1]).len(), 1);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_item_uri_relinked() {
+        assert_eq!(ItemUri::fields().to_string(), "track(uri,linked_from(uri))");
+        let page: Page<ItemUri> = serde_json::from_str(
+            r#"{"href":"","items":[{"track":{"uri":"spotify:track:6kLCHFM39wkFjOuyPGLGeQ","linked_from":{"uri":"spotify:track:6ozxplTAjWO0BlUxN8ia0A"}}},{"track":{"uri":"spotify:track:11dFghVXANMlKmJXsNCbNl"}}],"limit":50,"next":null,"offset":0,"previous":null,"total":2}"#,
+        )
+        .unwrap();
+        let uris: Vec<_> = page
+            .items
+            .into_iter()
+            .filter_map(|item| item.track.map(TrackUri::into_uri))
+            .collect();
+        assert_eq!(
+            uris,
+            vec![
+                "spotify:track:6ozxplTAjWO0BlUxN8ia0A",
+                "spotify:track:11dFghVXANMlKmJXsNCbNl"
+            ]
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_sync_error_from() {
+        let status = |status| {
+            SyncError::from(utils::request::Error::Status(utils::request::StatusError {
+                status,
+                body: None,
+                retry_after: None,
+            }))
+        };
+        assert_eq!(status(reqwest::StatusCode::CONFLICT), SyncError::Conflict);
+        assert!(matches!(
+            status(reqwest::StatusCode::FORBIDDEN),
+            SyncError::Request(_)
+        ));
+    }
+}
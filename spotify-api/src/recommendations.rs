@@ -0,0 +1,522 @@
+//! [Recommendations](https://developer.spotify.com/documentation/web-api/reference/get-recommendations) API
+//!
+//! ## Example
+//! ```ignore
+//!  let mut request = api::recommendations::RecommendationsRequest::new();
+//!  request
+//!     .seed_genre("synth-pop")
+//!     .seed_artist("4NHQUGzhtTLFvgF5SZesLK".parse()?)
+//!     .min(api::recommendations::TunableAttribute::Energy, 0.7)
+//!     .target(api::recommendations::TunableAttribute::Tempo, 120.0)
+//!     .limit(50);
+//!  let recommendations = client.recommendations(&request).await?;
+//! ```
+
+use crate::{model::*, tracks::Track, Client};
+use strum_macros::*;
+
+/// The maximum number of combined seeds in a [`RecommendationsRequest`]
+pub const MAX_SEEDS: usize = 5;
+
+/// A [tunable track attribute](https://developer.spotify.com/documentation/web-api/reference/get-recommendations)
+/// used to filter and rank recommendations
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    Debug,
+    AsRefStr,
+    EnumString,
+    Display,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum TunableAttribute {
+    /// Confidence from 0.0 to 1.0 of whether the track is acoustic
+    Acousticness,
+    /// Suitability from 0.0 to 1.0 of the track for dancing
+    Danceability,
+    /// The duration of the track in milliseconds
+    DurationMs,
+    /// Perceptual measure from 0.0 to 1.0 of intensity and activity
+    Energy,
+    /// Confidence from 0.0 to 1.0 that the track contains no vocals
+    Instrumentalness,
+    /// The [pitch class](https://en.wikipedia.org/wiki/Pitch_class) of the track from 0 (C) to 11 (B)
+    Key,
+    /// Confidence from 0.0 to 1.0 that the track was performed live
+    Liveness,
+    /// The overall loudness of the track in decibels
+    Loudness,
+    /// The modality of the track, 0 for minor and 1 for major
+    Mode,
+    /// The popularity of the track from 0 to 100
+    Popularity,
+    /// Presence from 0.0 to 1.0 of spoken words in the track
+    Speechiness,
+    /// The overall estimated tempo of the track in beats per minute
+    Tempo,
+    /// The estimated time signature of the track from 3 to 7, as beats per bar
+    TimeSignature,
+    /// Musical positiveness from 0.0 to 1.0 conveyed by the track
+    Valence,
+}
+
+impl TunableAttribute {
+    /// The inclusive range of valid values for the attribute
+    pub fn range(&self) -> std::ops::RangeInclusive<f64> {
+        use TunableAttribute::*;
+        match self {
+            Acousticness | Danceability | Energy | Instrumentalness | Liveness | Speechiness
+            | Valence => 0.0..=1.0,
+            DurationMs | Tempo => 0.0..=f64::MAX,
+            Key => 0.0..=11.0,
+            Loudness => f64::MIN..=f64::MAX,
+            Mode => 0.0..=1.0,
+            Popularity => 0.0..=100.0,
+            TimeSignature => 3.0..=7.0,
+        }
+    }
+
+    /// Returns if the attribute only accepts whole numbers
+    pub fn is_integer(&self) -> bool {
+        use TunableAttribute::*;
+        matches!(self, DurationMs | Key | Mode | Popularity | TimeSignature)
+    }
+
+    /// Returns if `value` is valid for the attribute
+    pub fn is_valid(&self, value: f64) -> bool {
+        self.range().contains(&value) && (!self.is_integer() || value.fract() == 0.0)
+    }
+}
+
+/// The `min_`, `max_` and `target_` values of a [`TunableAttribute`]
+#[derive(
+    Copy, Clone, PartialEq, PartialOrd, Debug, Default, serde::Serialize, serde::Deserialize,
+)]
+pub struct Tuning {
+    /// Hard floor on the selected attribute value
+    pub min: Option<f64>,
+    /// Hard ceiling on the selected attribute value
+    pub max: Option<f64>,
+    /// Tracks with the attribute value nearest to the target are preferred
+    pub target: Option<f64>,
+}
+
+/// [`Client::recommendations()`] Errors
+#[derive(Debug)]
+pub enum RecommendationsError {
+    /// At least one seed artist, genre or track is required
+    NoSeeds,
+    /// More than [`MAX_SEEDS`] seeds were given
+    TooManySeeds(usize),
+    /// The limit is outside of 1 to 100
+    InvalidLimit(u32),
+    /// A value is outside of the [`TunableAttribute::range()`] or isn't a whole number for an integer attribute
+    InvalidValue(TunableAttribute, f64),
+    /// The `min_` is greater than the `max_`, or the `target_` is outside of them
+    ConflictingTuning(TunableAttribute, Tuning),
+    /// Errors requesting the recommendations
+    Request(utils::request::Error),
+}
+use RecommendationsError::*;
+impl std::error::Error for RecommendationsError {}
+impl std::fmt::Display for RecommendationsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NoSeeds => write!(f, "at least one seed artist, genre or track is required"),
+            TooManySeeds(seeds) => write!(
+                f,
+                "{seeds} seeds were given but only up to {MAX_SEEDS} are allowed"
+            ),
+            InvalidLimit(limit) => write!(f, "the limit {limit} is outside of 1 to 100"),
+            InvalidValue(attribute, value) => {
+                write!(f, "{value} is not a valid value for {attribute}")
+            }
+            ConflictingTuning(attribute, tuning) => write!(
+                f,
+                "the tuning of {attribute} is conflicting, min: {:?}, max: {:?}, target: {:?}",
+                tuning.min, tuning.max, tuning.target
+            ),
+            Request(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl PartialEq for RecommendationsError {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (TooManySeeds(l), TooManySeeds(r)) => l == r,
+            (InvalidLimit(l), InvalidLimit(r)) => l == r,
+            (InvalidValue(l_attr, l_value), InvalidValue(r_attr, r_value)) => {
+                l_attr == r_attr && l_value == r_value
+            }
+            (ConflictingTuning(l_attr, l_tuning), ConflictingTuning(r_attr, r_tuning)) => {
+                l_attr == r_attr && l_tuning == r_tuning
+            }
+            (Request(l_req), Request(r_req)) => l_req.to_string() == r_req.to_string(),
+            (_self, _other) => core::mem::discriminant(self) == core::mem::discriminant(other),
+        }
+    }
+}
+
+/// Request parameters for [`Client::recommendations()`]
+#[derive(Clone, PartialEq, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct RecommendationsRequest {
+    seed_artists: Vec<ArtistId>,
+    seed_genres: Vec<String>,
+    seed_tracks: Vec<TrackId>,
+    limit: Option<u32>,
+    market: Option<String>,
+    tunings: std::collections::BTreeMap<TunableAttribute, Tuning>,
+}
+
+impl RecommendationsRequest {
+    /// Creates a [`RecommendationsRequest`] without seeds or tuning
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an artist seed
+    pub fn seed_artist(&mut self, id: ArtistId) -> &mut Self {
+        self.seed_artists.push(id);
+        self
+    }
+
+    /// Adds a genre seed, from [`Client::available_genre_seeds()`]
+    pub fn seed_genre(&mut self, genre: &str) -> &mut Self {
+        self.seed_genres.push(genre.to_owned());
+        self
+    }
+
+    /// Adds a track seed
+    pub fn seed_track(&mut self, id: TrackId) -> &mut Self {
+        self.seed_tracks.push(id);
+        self
+    }
+
+    /// Sets the target number of recommended tracks, between 1 and 100, defaulting to 20
+    pub fn limit(&mut self, limit: u32) -> &mut Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Only recommend tracks available in the `market`
+    pub fn market(&mut self, market: &str) -> &mut Self {
+        self.market = Some(market.to_owned());
+        self
+    }
+
+    /// Sets the hard floor of the `attribute`
+    pub fn min(&mut self, attribute: TunableAttribute, value: f64) -> &mut Self {
+        self.tunings.entry(attribute).or_default().min = Some(value);
+        self
+    }
+
+    /// Sets the hard ceiling of the `attribute`
+    pub fn max(&mut self, attribute: TunableAttribute, value: f64) -> &mut Self {
+        self.tunings.entry(attribute).or_default().max = Some(value);
+        self
+    }
+
+    /// Sets the preferred value of the `attribute`
+    pub fn target(&mut self, attribute: TunableAttribute, value: f64) -> &mut Self {
+        self.tunings.entry(attribute).or_default().target = Some(value);
+        self
+    }
+
+    /// Gets the tuning of the `attribute`
+    pub fn tuning(&self, attribute: TunableAttribute) -> Tuning {
+        self.tunings.get(&attribute).copied().unwrap_or_default()
+    }
+
+    /// The total number of seed artists, genres and tracks
+    pub fn seeds(&self) -> usize {
+        self.seed_artists.len() + self.seed_genres.len() + self.seed_tracks.len()
+    }
+
+    /// Checks the request would be accepted by the API
+    pub fn validate(&self) -> Result<(), RecommendationsError> {
+        match self.seeds() {
+            0 => Err(NoSeeds),
+            seeds if seeds > MAX_SEEDS => Err(TooManySeeds(seeds)),
+            _seeds => Ok(()),
+        }?;
+        if let Some(limit) = self.limit.filter(|limit| !(1..=100).contains(limit)) {
+            return Err(InvalidLimit(limit));
+        }
+        for (attribute, tuning) in &self.tunings {
+            for value in [tuning.min, tuning.max, tuning.target]
+                .into_iter()
+                .flatten()
+            {
+                if !attribute.is_valid(value) {
+                    return Err(InvalidValue(*attribute, value));
+                }
+            }
+            let min = tuning.min.unwrap_or(f64::MIN);
+            let max = tuning.max.unwrap_or(f64::MAX);
+            if min > max
+                || tuning
+                    .target
+                    .is_some_and(|target| !(min..=max).contains(&target))
+            {
+                return Err(ConflictingTuning(*attribute, *tuning));
+            }
+        }
+        Ok(())
+    }
+
+    /// The query parameters of the request
+    fn query(&self) -> Vec<(String, String)> {
+        let mut query = Vec::new();
+        for (key, seeds) in [
+            ("seed_artists", join_ids(&self.seed_artists)),
+            ("seed_genres", join_ids(&self.seed_genres)),
+            ("seed_tracks", join_ids(&self.seed_tracks)),
+        ] {
+            if !seeds.is_empty() {
+                query.push((key.to_owned(), seeds));
+            }
+        }
+        if let Some(limit) = self.limit {
+            query.push(("limit".to_owned(), limit.to_string()));
+        }
+        if let Some(market) = &self.market {
+            query.push(("market".to_owned(), market.to_owned()));
+        }
+        for (attribute, tuning) in &self.tunings {
+            for (prefix, value) in [
+                ("min", tuning.min),
+                ("max", tuning.max),
+                ("target", tuning.target),
+            ] {
+                if let Some(value) = value {
+                    query.push((format!("{prefix}_{attribute}"), value.to_string()));
+                }
+            }
+        }
+        query
+    }
+}
+
+/// The type of a [`RecommendationSeed`]
+#[derive(
+    Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum SeedType {
+    /// An artist seed
+    #[serde(alias = "ARTIST")]
+    Artist,
+    /// A genre seed
+    #[serde(alias = "GENRE")]
+    Genre,
+    /// A track seed
+    #[serde(alias = "TRACK")]
+    Track,
+}
+
+/// [Recommendation Seed Object](https://developer.spotify.com/documentation/web-api/reference/get-recommendations)
+#[derive(
+    Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, serde::Serialize, serde::Deserialize,
+)]
+pub struct RecommendationSeed {
+    /// The number of tracks available after min/max filters have been applied
+    #[serde(rename = "afterFilteringSize")]
+    pub after_filtering_size: u32,
+    /// The number of tracks available after relinking for regional availability
+    #[serde(rename = "afterRelinkingSize")]
+    pub after_relinking_size: u32,
+    /// A link to the full track or artist data for this seed, `None` for genre seeds
+    #[serde(default)]
+    pub href: Option<String>,
+    /// The ID used to select this seed, the genre name for genre seeds
+    pub id: String,
+    /// The number of recommended tracks available for this seed
+    #[serde(rename = "initialPoolSize")]
+    pub initial_pool_size: u32,
+    /// The type of the seed
+    #[serde(rename = "type")]
+    pub seed_type: SeedType,
+}
+
+/// [Recommendations Object](https://developer.spotify.com/documentation/web-api/reference/get-recommendations)
+#[derive(Clone, Eq, PartialEq, Hash, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Recommendations {
+    /// The seeds used to generate the recommendations
+    pub seeds: Vec<RecommendationSeed>,
+    /// The recommended tracks, ordered by their tuning
+    pub tracks: Vec<Track>,
+}
+
+impl Client {
+    /// [Get Recommendations](https://developer.spotify.com/documentation/web-api/reference/get-recommendations)
+    ///
+    /// The `request` is [validated](RecommendationsRequest::validate()) before any request is made,
+    /// the default [market](Client::set_market()) is used if the `request` has none.
+    pub async fn recommendations(
+        &mut self,
+        request: &RecommendationsRequest,
+    ) -> Result<Recommendations, RecommendationsError> {
+        request.validate()?;
+        let mut query = request.query();
+        if request.market.is_none() {
+            if let Some(market) = self.market() {
+                query.push(("market".to_owned(), market.to_owned()));
+            }
+        }
+        self.get(&["recommendations"], &query)
+            .await
+            .map_err(Request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    fn artist() -> ArtistId {
+        ArtistId::new("4NHQUGzhtTLFvgF5SZesLK").unwrap()
+    }
+
+    fn track() -> TrackId {
+        TrackId::new("0c6xIDDpzE81m2q797ordA").unwrap()
+    }
+
+    #[wasm_bindgen_test]
+    fn test_query() {
+        let mut request = RecommendationsRequest::new();
+        request
+            .seed_artist(artist())
+            .seed_genre("classical")
+            .seed_genre("country")
+            .seed_track(track())
+            .limit(10)
+            .market("AU")
+            .min(TunableAttribute::Energy, 0.4)
+            .max(TunableAttribute::Energy, 0.8)
+            .target(TunableAttribute::Key, 5.0)
+            .target(TunableAttribute::DurationMs, 180000.0);
+        assert_eq!(request.validate(), Ok(()));
+        assert_eq!(
+            request.query(),
+            [
+                ("seed_artists", "4NHQUGzhtTLFvgF5SZesLK"),
+                ("seed_genres", "classical,country"),
+                ("seed_tracks", "0c6xIDDpzE81m2q797ordA"),
+                ("limit", "10"),
+                ("market", "AU"),
+                ("target_duration_ms", "180000"),
+                ("min_energy", "0.4"),
+                ("max_energy", "0.8"),
+                ("target_key", "5"),
+            ]
+            .map(|(k, v)| (k.to_owned(), v.to_owned()))
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_seed_validation() {
+        let mut request = RecommendationsRequest::new();
+        assert_eq!(request.validate(), Err(NoSeeds));
+        request.seed_genre("a").seed_genre("b").seed_genre("c");
+        request.seed_artist(artist()).seed_track(track());
+        assert_eq!(request.validate(), Ok(()));
+        request.seed_genre("f");
+        assert_eq!(request.validate(), Err(TooManySeeds(6)));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_limit_validation() {
+        let mut request = RecommendationsRequest::new();
+        request.seed_track(track());
+        assert_eq!(request.limit(0).validate(), Err(InvalidLimit(0)));
+        assert_eq!(request.limit(101).validate(), Err(InvalidLimit(101)));
+        assert_eq!(request.limit(100).validate(), Ok(()));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_tuning_validation() {
+        let mut request = RecommendationsRequest::new();
+        request.seed_track(track());
+        assert_eq!(
+            request
+                .clone()
+                .target(TunableAttribute::Energy, 1.5)
+                .validate(),
+            Err(InvalidValue(TunableAttribute::Energy, 1.5))
+        );
+        assert_eq!(
+            request
+                .clone()
+                .target(TunableAttribute::Key, 1.5)
+                .validate(),
+            Err(InvalidValue(TunableAttribute::Key, 1.5))
+        );
+        assert_eq!(
+            request
+                .clone()
+                .min(TunableAttribute::Popularity, 101.0)
+                .validate(),
+            Err(InvalidValue(TunableAttribute::Popularity, 101.0))
+        );
+        assert_eq!(
+            request
+                .clone()
+                .max(TunableAttribute::Loudness, -6.0)
+                .validate(),
+            Ok(())
+        );
+        assert_eq!(
+            request
+                .clone()
+                .min(TunableAttribute::Tempo, 140.0)
+                .max(TunableAttribute::Tempo, 120.0)
+                .validate(),
+            Err(ConflictingTuning(
+                TunableAttribute::Tempo,
+                Tuning {
+                    min: Some(140.0),
+                    max: Some(120.0),
+                    target: None
+                }
+            ))
+        );
+        assert_eq!(
+            request
+                .clone()
+                .min(TunableAttribute::Valence, 0.5)
+                .target(TunableAttribute::Valence, 0.2)
+                .validate(),
+            Err(ConflictingTuning(
+                TunableAttribute::Valence,
+                Tuning {
+                    min: Some(0.5),
+                    max: None,
+                    target: Some(0.2)
+                }
+            ))
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_seed_deserialize() {
+        let seed: RecommendationSeed = serde_json::from_str(
+            r#"{"initialPoolSize":500,"afterFilteringSize":380,"afterRelinkingSize":365,"href":null,"id":"classical","type":"GENRE"}"#,
+        )
+        .unwrap();
+        assert_eq!(seed.seed_type, SeedType::Genre);
+        assert_eq!(seed.href, None);
+        assert_eq!(seed.after_filtering_size, 380);
+    }
+}
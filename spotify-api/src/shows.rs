@@ -0,0 +1,222 @@
+//! [Shows](https://developer.spotify.com/documentation/web-api/reference/get-a-show) (podcasts) API
+
+use crate::{client::Query, episodes::SimplifiedEpisode, model::*, Client};
+use utils::request::Result;
+
+/// [Show Object](https://developer.spotify.com/documentation/web-api/reference/get-a-show) with its first page of episodes
+#[derive(Clone, Eq, PartialEq, Hash, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Show {
+    /// The [ISO 3166-1 alpha-2](https://en.wikipedia.org/wiki/ISO_3166-1_alpha-2) country codes the show is available in
+    pub available_markets: Vec<String>,
+    /// The copyright statements of the show
+    pub copyrights: Vec<Copyright>,
+    /// A description of the show with HTML tags stripped
+    pub description: String,
+    /// A description of the show which may contain HTML tags
+    pub html_description: String,
+    /// Whether or not the show has explicit content
+    pub explicit: bool,
+    /// External URLs for this show
+    pub external_urls: ExternalUrls,
+    /// A link to the Web API endpoint providing full details of the show
+    pub href: String,
+    /// The Spotify ID for the show
+    pub id: ShowId,
+    /// The cover art for the show in various sizes, widest first
+    pub images: Vec<Image>,
+    /// True if all of the show's episodes are hosted outside of Spotify's CDN
+    pub is_externally_hosted: bool,
+    /// The [ISO 639](https://en.wikipedia.org/wiki/ISO_639) codes of the languages used in the show
+    pub languages: Vec<String>,
+    /// The media type of the show
+    pub media_type: String,
+    /// The name of the show
+    pub name: String,
+    /// The publisher of the show
+    pub publisher: String,
+    /// The Spotify URI for the show
+    pub uri: String,
+    /// The total number of episodes in the show
+    pub total_episodes: u32,
+    /// The first page of the show's episodes
+    pub episodes: Page<SimplifiedEpisode>,
+}
+
+/// [Simplified Show Object](https://developer.spotify.com/documentation/web-api/reference/get-multiple-shows)
+#[derive(Clone, Eq, PartialEq, Hash, Debug, serde::Serialize, serde::Deserialize)]
+pub struct SimplifiedShow {
+    /// The [ISO 3166-1 alpha-2](https://en.wikipedia.org/wiki/ISO_3166-1_alpha-2) country codes the show is available in
+    pub available_markets: Vec<String>,
+    /// The copyright statements of the show
+    pub copyrights: Vec<Copyright>,
+    /// A description of the show with HTML tags stripped
+    pub description: String,
+    /// A description of the show which may contain HTML tags
+    pub html_description: String,
+    /// Whether or not the show has explicit content
+    pub explicit: bool,
+    /// External URLs for this show
+    pub external_urls: ExternalUrls,
+    /// A link to the Web API endpoint providing full details of the show
+    pub href: String,
+    /// The Spotify ID for the show
+    pub id: ShowId,
+    /// The cover art for the show in various sizes, widest first
+    pub images: Vec<Image>,
+    /// True if all of the show's episodes are hosted outside of Spotify's CDN
+    pub is_externally_hosted: bool,
+    /// The [ISO 639](https://en.wikipedia.org/wiki/ISO_639) codes of the languages used in the show
+    pub languages: Vec<String>,
+    /// The media type of the show
+    pub media_type: String,
+    /// The name of the show
+    pub name: String,
+    /// The publisher of the show
+    pub publisher: String,
+    /// The Spotify URI for the show
+    pub uri: String,
+    /// The total number of episodes in the show
+    pub total_episodes: u32,
+}
+
+/// A show saved in the current user's library
+#[derive(Clone, Eq, PartialEq, Hash, Debug, serde::Serialize, serde::Deserialize)]
+pub struct SavedShow {
+    /// The [ISO 8601](https://en.wikipedia.org/wiki/ISO_8601) UTC timestamp of when the show was saved
+    pub added_at: String,
+    /// The saved show
+    pub show: SimplifiedShow,
+}
+
+impl Client {
+    /// [Get Show](https://developer.spotify.com/documentation/web-api/reference/get-a-show)
+    ///
+    /// If `market` is given, only content available in that market is returned.
+    /// Episode resume points are only included with [`Scopes::UserReadPlaybackPosition`](crate::authorisation::Scopes::UserReadPlaybackPosition).
+    pub async fn show(&mut self, id: &ShowId, market: Option<&str>) -> Result<Show> {
+        let market = self.resolve_market(market);
+        self.get(
+            &["shows", id.as_str()],
+            &Query {
+                market: market.as_deref(),
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// [Get Several Shows](https://developer.spotify.com/documentation/web-api/reference/get-multiple-shows)
+    ///
+    /// Accepts a maximum of 50 IDs, shows that can't be found are returned as `None`.
+    pub async fn shows(
+        &mut self,
+        ids: &[ShowId],
+        market: Option<&str>,
+    ) -> Result<Vec<Option<SimplifiedShow>>> {
+        #[derive(serde::Deserialize)]
+        struct Response {
+            shows: Vec<Option<SimplifiedShow>>,
+        }
+
+        let market = self.resolve_market(market);
+        let res: Response = self
+            .get(
+                &["shows"],
+                &Query {
+                    ids: Some(&join_ids(ids)),
+                    market: market.as_deref(),
+                    ..Default::default()
+                },
+            )
+            .await?;
+        Ok(res.shows)
+    }
+
+    /// [Get Show Episodes](https://developer.spotify.com/documentation/web-api/reference/get-a-shows-episodes)
+    ///
+    /// `limit` is between 1 and 50, defaulting to 20.
+    /// Episode resume points are only included with [`Scopes::UserReadPlaybackPosition`](crate::authorisation::Scopes::UserReadPlaybackPosition).
+    pub async fn show_episodes(
+        &mut self,
+        id: &ShowId,
+        market: Option<&str>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<Page<SimplifiedEpisode>> {
+        let market = self.resolve_market(market);
+        self.get(
+            &["shows", id.as_str(), "episodes"],
+            &Query {
+                market: market.as_deref(),
+                limit,
+                offset,
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// [Get User's Saved Shows](https://developer.spotify.com/documentation/web-api/reference/get-users-saved-shows)
+    ///
+    /// Requires [`Scopes::UserLibraryRead`](crate::authorisation::Scopes::UserLibraryRead),
+    /// `limit` is between 1 and 50, defaulting to 20.
+    pub async fn saved_shows(
+        &mut self,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<Page<SavedShow>> {
+        self.get(
+            &["me", "shows"],
+            &Query {
+                limit,
+                offset,
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// [Check User's Saved Shows](https://developer.spotify.com/documentation/web-api/reference/check-users-saved-shows)
+    ///
+    /// Requires [`Scopes::UserLibraryRead`](crate::authorisation::Scopes::UserLibraryRead),
+    /// accepts a maximum of 50 IDs and returns whether each show is saved in the same order.
+    pub async fn check_saved_shows(&mut self, ids: &[ShowId]) -> Result<Vec<bool>> {
+        self.get(
+            &["me", "shows", "contains"],
+            &Query {
+                ids: Some(&join_ids(ids)),
+                ..Default::default()
+            },
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    const SIMPLIFIED_SHOW: &str = r#"{"available_markets":["AU","US"],"copyrights":[],"description":"A show","html_description":"<p>A show</p>","explicit":false,"external_urls":{"spotify":"https://open.spotify.com/show/38bS44xjbVVZ3No3ByF1dJ"},"href":"https://api.spotify.com/v1/shows/38bS44xjbVVZ3No3ByF1dJ","id":"38bS44xjbVVZ3No3ByF1dJ","images":[{"url":"https://i.scdn.co/image/ab67","height":640,"width":640}],"is_externally_hosted":false,"languages":["en"],"media_type":"audio","name":"Show","publisher":"Publisher","type":"show","uri":"spotify:show:38bS44xjbVVZ3No3ByF1dJ","total_episodes":1}"#;
+
+    #[wasm_bindgen_test]
+    fn test_simplified_show_deserialize() {
+        let show: SimplifiedShow = serde_json::from_str(SIMPLIFIED_SHOW).unwrap();
+        assert_eq!(show.id, ShowId::new("38bS44xjbVVZ3No3ByF1dJ").unwrap());
+        assert_eq!(show.available_markets, ["AU", "US"]);
+        assert_eq!(show.images[0].height, Some(640));
+        assert_eq!(show.total_episodes, 1);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_saved_show_deserialize() {
+        let saved: Page<SavedShow> = serde_json::from_str(&format!(
+            r#"{{"href":"https://api.spotify.com/v1/me/shows","items":[{{"added_at":"2023-01-01T00:00:00Z","show":{SIMPLIFIED_SHOW}}}],"limit":20,"next":null,"offset":0,"previous":null,"total":1}}"#
+        ))
+        .unwrap();
+        assert_eq!(saved.items[0].added_at, "2023-01-01T00:00:00Z");
+        assert_eq!(saved.items[0].show.name, "Show");
+    }
+}
@@ -0,0 +1,830 @@
+//! Rule based smart playlists
+//!
+//! A [`SmartPlaylist`] gathers candidate tracks from the saved library and the user's top tracks,
+//! keeps those matching its [`Rule`], then sorts and limits them into the playlist's tracks.
+//! [`Client::run_smart_playlist()`] evaluates it and syncs the result to a real playlist, creating one on the first run.
+//! Smart playlists are serialisable, so they can be kept in any [`SeralizedStore`] with [`SmartPlaylistStore`]
+//! and re-run on demand.
+//!
+//! ```ignore
+//! let mut smart = SmartPlaylist::new("Fresh energy");
+//! smart
+//!     .rule(Rule::All {
+//!         rules: vec![
+//!             Rule::SavedWithin { days: 30 },
+//!             Rule::Attribute {
+//!                 attribute: TunableAttribute::Energy,
+//!                 comparison: Comparison::Greater,
+//!                 value: 0.7,
+//!             },
+//!             Rule::Not {
+//!                 rule: Box::new(Rule::Artist { artist: "Artist".to_owned() }),
+//!             },
+//!         ],
+//!     })
+//!     .sort(SortKey::Attribute(TunableAttribute::Tempo), false)
+//!     .limit(Some(100));
+//! client.run_smart_playlist(&mut smart).await?;
+//! store.save_smart_playlist(&smart)?;
+//! ```
+
+use crate::{
+    audio::AudioFeatures,
+    model::*,
+    playlists::{SyncError, SyncReport},
+    recommendations::TunableAttribute,
+    tracks::Track,
+    users::TimeRange,
+    Client,
+};
+use instant::Duration;
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+};
+use utils::{request::Result, SeralizedStore};
+
+/// The prefix of the keys smart playlists are stored under, followed by their name
+const KEY_PREFIX: &str = "spotify-api-smart-playlist:";
+/// The key the names of the stored smart playlists are stored under
+const NAMES_KEY: &str = "spotify-api-smart-playlists";
+
+/// Where the candidate tracks of a [`SmartPlaylist`] are gathered from
+#[derive(
+    Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, serde::Serialize, serde::Deserialize,
+)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Source {
+    /// The tracks saved in the user's library
+    Library,
+    /// The user's top tracks over the time range
+    TopTracks {
+        /// The period the top tracks are calculated over
+        time_range: TimeRange,
+    },
+}
+
+/// How an attribute's value is compared in a [`Rule::Attribute`]
+#[derive(
+    Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Comparison {
+    /// Less than the value
+    Less,
+    /// Less than or equal to the value
+    LessOrEqual,
+    /// Equal to the value
+    Equal,
+    /// Greater than or equal to the value
+    GreaterOrEqual,
+    /// Greater than the value
+    Greater,
+}
+
+impl Comparison {
+    /// Returns if `lhs` compares to `rhs`
+    pub fn compare(&self, lhs: f64, rhs: f64) -> bool {
+        match self {
+            Comparison::Less => lhs < rhs,
+            Comparison::LessOrEqual => lhs <= rhs,
+            Comparison::Equal => lhs == rhs,
+            Comparison::GreaterOrEqual => lhs >= rhs,
+            Comparison::Greater => lhs > rhs,
+        }
+    }
+}
+
+/// A condition candidate tracks must match to be in a [`SmartPlaylist`]
+///
+/// Conditions on data a track doesn't have, e.g. when it was saved for a track only in the top tracks,
+/// don't match.
+#[derive(Clone, PartialEq, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Rule {
+    /// Tracks saved to the library within the last `days`
+    SavedWithin {
+        /// The number of days
+        days: u32,
+    },
+    /// Tracks with an audio feature or popularity comparing to the `value`
+    Attribute {
+        /// The attribute compared
+        attribute: TunableAttribute,
+        /// How the track's value is compared to the `value`
+        comparison: Comparison,
+        /// The value compared against
+        value: f64,
+    },
+    /// Tracks by the artist, matched by artist ID or case insensitive name
+    Artist {
+        /// The ID or name of the artist
+        artist: String,
+    },
+    /// Tracks with explicit lyrics
+    Explicit,
+    /// Tracks among the user's top tracks over the time range
+    TopTrack {
+        /// The period the top tracks are calculated over
+        time_range: TimeRange,
+    },
+    /// Tracks matching every rule, or every track if there are none
+    All {
+        /// The rules to match
+        rules: Vec<Rule>,
+    },
+    /// Tracks matching any rule
+    Any {
+        /// The rules to match
+        rules: Vec<Rule>,
+    },
+    /// Tracks not matching the rule
+    Not {
+        /// The rule not to match
+        rule: Box<Rule>,
+    },
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Rule::All { rules: Vec::new() }
+    }
+}
+
+impl Rule {
+    /// Returns if the `candidate` matches the rule at `now`, the time since the Unix epoch
+    pub fn matches(&self, candidate: &Candidate, now: Duration) -> bool {
+        match self {
+            Rule::SavedWithin { days } => candidate
+                .added_at
+                .as_deref()
+                .and_then(parse_timestamp)
+                .is_some_and(|added_at| {
+                    now.saturating_sub(added_at) <= Duration::from_secs(u64::from(*days) * 86400)
+                }),
+            Rule::Attribute {
+                attribute,
+                comparison,
+                value,
+            } => candidate
+                .value(*attribute)
+                .is_some_and(|candidate_value| comparison.compare(candidate_value, *value)),
+            Rule::Artist { artist } => candidate.track.artists.iter().any(|track_artist| {
+                track_artist
+                    .id
+                    .as_ref()
+                    .is_some_and(|id| id.as_str() == artist)
+                    || track_artist.name.to_lowercase() == artist.to_lowercase()
+            }),
+            Rule::Explicit => candidate.track.explicit,
+            Rule::TopTrack { time_range } => candidate.top.contains(time_range),
+            Rule::All { rules } => rules.iter().all(|rule| rule.matches(candidate, now)),
+            Rule::Any { rules } => rules.iter().any(|rule| rule.matches(candidate, now)),
+            Rule::Not { rule } => !rule.matches(candidate, now),
+        }
+    }
+
+    /// Calls `f` with the rule and each rule nested within it
+    fn visit(&self, f: &mut impl FnMut(&Rule)) {
+        f(self);
+        match self {
+            Rule::All { rules } | Rule::Any { rules } => {
+                rules.iter().for_each(|rule| rule.visit(f))
+            }
+            Rule::Not { rule } => rule.visit(f),
+            _ => {}
+        }
+    }
+}
+
+/// What the tracks of a [`SmartPlaylist`] are sorted by
+#[derive(
+    Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum SortKey {
+    /// When the track was saved to the library
+    AddedAt,
+    /// The case insensitive name of the track
+    Name,
+    /// The case insensitive name of the track's first artist
+    Artist,
+    /// An audio feature or the popularity of the track
+    Attribute(TunableAttribute),
+}
+
+/// The order of the tracks of a [`SmartPlaylist`]
+#[derive(
+    Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, serde::Serialize, serde::Deserialize,
+)]
+pub struct Sort {
+    /// What the tracks are sorted by
+    pub key: SortKey,
+    /// If the tracks are sorted from the greatest to the least
+    #[serde(default)]
+    pub descending: bool,
+}
+
+/// A track that may be in a [`SmartPlaylist`], with the data its rules are evaluated against
+#[derive(Clone, PartialEq, Debug)]
+pub struct Candidate {
+    /// The track
+    pub track: Track,
+    /// The [ISO 8601](https://en.wikipedia.org/wiki/ISO_8601) UTC timestamp of when the track was saved,
+    /// if it's in the library
+    pub added_at: Option<String>,
+    /// The audio features of the track, if they were needed and are available
+    pub features: Option<AudioFeatures>,
+    /// The time ranges the track is among the user's top tracks for
+    pub top: BTreeSet<TimeRange>,
+}
+
+impl Candidate {
+    /// A candidate for the `track` without any other data
+    pub fn new(track: Track) -> Self {
+        Self {
+            track,
+            added_at: None,
+            features: None,
+            top: BTreeSet::new(),
+        }
+    }
+
+    /// The value of the `attribute` for the track, from its audio features or popularity
+    pub fn value(&self, attribute: TunableAttribute) -> Option<f64> {
+        match attribute {
+            TunableAttribute::Popularity => Some(self.track.popularity.into()),
+            attribute => self.features.as_ref()?.value(attribute),
+        }
+    }
+
+    /// The value of the track to sort by for the `key`, if it has one
+    fn sort_value(&self, key: SortKey) -> Option<SortValue> {
+        match key {
+            SortKey::AddedAt => self
+                .added_at
+                .as_deref()
+                .and_then(parse_timestamp)
+                .map(SortValue::Time),
+            SortKey::Name => Some(SortValue::Text(self.track.name.to_lowercase())),
+            SortKey::Artist => self
+                .track
+                .artists
+                .first()
+                .map(|artist| SortValue::Text(artist.name.to_lowercase())),
+            SortKey::Attribute(attribute) => self.value(attribute).map(SortValue::Number),
+        }
+    }
+}
+
+/// A value candidates are sorted by, of the same variant for every candidate for a [`SortKey`]
+#[derive(PartialEq, PartialOrd)]
+enum SortValue {
+    Time(Duration),
+    Text(String),
+    Number(f64),
+}
+
+/// A playlist of the candidate tracks matching a [`Rule`], sorted and limited
+#[derive(Clone, PartialEq, Debug, serde::Serialize, serde::Deserialize)]
+pub struct SmartPlaylist {
+    /// The name of the smart playlist, and of the playlist created for it
+    pub name: String,
+    /// The description of the playlist created for it
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Where the candidate tracks are gathered from
+    pub sources: Vec<Source>,
+    /// The rule tracks must match
+    #[serde(default)]
+    pub rule: Rule,
+    /// The order of the tracks, the order of the sources if `None`
+    #[serde(default)]
+    pub sort: Option<Sort>,
+    /// The maximum number of tracks
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// The playlist the tracks are written to, created on the first run if `None`
+    #[serde(default)]
+    pub playlist: Option<PlaylistId>,
+}
+
+impl SmartPlaylist {
+    /// A smart playlist of every track in the library
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            description: None,
+            sources: vec![Source::Library],
+            rule: Rule::default(),
+            sort: None,
+            limit: None,
+            playlist: None,
+        }
+    }
+
+    /// Adds a source of candidate tracks
+    pub fn source(&mut self, source: Source) -> &mut Self {
+        if !self.sources.contains(&source) {
+            self.sources.push(source);
+        }
+        self
+    }
+
+    /// Sets the rule tracks must match
+    pub fn rule(&mut self, rule: Rule) -> &mut Self {
+        self.rule = rule;
+        self
+    }
+
+    /// Sorts the tracks by the `key`
+    pub fn sort(&mut self, key: SortKey, descending: bool) -> &mut Self {
+        self.sort = Some(Sort { key, descending });
+        self
+    }
+
+    /// Sets the maximum number of tracks
+    pub fn limit(&mut self, limit: Option<usize>) -> &mut Self {
+        self.limit = limit;
+        self
+    }
+
+    /// The time ranges of top tracks needed by the sources and rule
+    fn time_ranges(&self) -> BTreeSet<TimeRange> {
+        let mut time_ranges: BTreeSet<TimeRange> = self
+            .sources
+            .iter()
+            .filter_map(|source| match source {
+                Source::TopTracks { time_range } => Some(*time_range),
+                Source::Library => None,
+            })
+            .collect();
+        self.rule.visit(&mut |rule| {
+            if let Rule::TopTrack { time_range } = rule {
+                time_ranges.insert(*time_range);
+            }
+        });
+        time_ranges
+    }
+
+    /// Returns if the rule or sort need the audio features of the candidates
+    fn needs_features(&self) -> bool {
+        let is_feature = |attribute: TunableAttribute| attribute != TunableAttribute::Popularity;
+        let mut needs_features = self.sort.is_some_and(|sort| match sort.key {
+            SortKey::Attribute(attribute) => is_feature(attribute),
+            _ => false,
+        });
+        self.rule.visit(&mut |rule| {
+            if let Rule::Attribute { attribute, .. } = rule {
+                needs_features |= is_feature(*attribute);
+            }
+        });
+        needs_features
+    }
+
+    /// The URIs of the `candidates` in the playlist at `now`, the time since the Unix epoch
+    ///
+    /// Candidates are kept once each, in order unless sorted,
+    /// and local tracks are skipped since they can't be added to playlists.
+    pub fn evaluate(&self, candidates: &[Candidate], now: Duration) -> Vec<String> {
+        let mut seen = HashSet::new();
+        let mut matching: Vec<(Option<SortValue>, &Candidate)> = candidates
+            .iter()
+            .filter(|candidate| !candidate.track.is_local && seen.insert(&candidate.track.uri))
+            .filter(|candidate| self.rule.matches(candidate, now))
+            .map(|candidate| {
+                (
+                    self.sort.and_then(|sort| candidate.sort_value(sort.key)),
+                    candidate,
+                )
+            })
+            .collect();
+        if let Some(sort) = self.sort {
+            matching.sort_by(|(lhs, _), (rhs, _)| match (lhs, rhs) {
+                (Some(lhs), Some(rhs)) => {
+                    let ordering = lhs.partial_cmp(rhs).unwrap_or(Ordering::Equal);
+                    if sort.descending {
+                        ordering.reverse()
+                    } else {
+                        ordering
+                    }
+                }
+                // Tracks without a value for the key are always last
+                (lhs, rhs) => rhs.is_some().cmp(&lhs.is_some()),
+            });
+        }
+        matching
+            .into_iter()
+            .take(self.limit.unwrap_or(usize::MAX))
+            .map(|(_, candidate)| candidate.track.uri.clone())
+            .collect()
+    }
+}
+
+/// Storage for smart playlists keyed by name, implemented for every [`SeralizedStore`]
+pub trait SmartPlaylistStore {
+    /// Error from using the store
+    type Error;
+
+    /// The names of the stored smart playlists, in the order they were first saved
+    fn smart_playlist_names(&self) -> std::result::Result<Vec<String>, Self::Error>;
+
+    /// Gets the smart playlist stored under the `name`
+    fn smart_playlist(&self, name: &str)
+        -> std::result::Result<Option<SmartPlaylist>, Self::Error>;
+
+    /// Stores the smart playlist under its name, replacing any previous one
+    fn save_smart_playlist(
+        &mut self,
+        smart: &SmartPlaylist,
+    ) -> std::result::Result<(), Self::Error>;
+
+    /// Removes the smart playlist stored under the `name`, returning it if there was one
+    fn remove_smart_playlist(
+        &mut self,
+        name: &str,
+    ) -> std::result::Result<Option<SmartPlaylist>, Self::Error>;
+}
+
+impl<S: SeralizedStore> SmartPlaylistStore for S {
+    type Error = S::Error;
+
+    fn smart_playlist_names(&self) -> std::result::Result<Vec<String>, Self::Error> {
+        Ok(SeralizedStore::get(self, &NAMES_KEY)?.unwrap_or_default())
+    }
+
+    fn smart_playlist(
+        &self,
+        name: &str,
+    ) -> std::result::Result<Option<SmartPlaylist>, Self::Error> {
+        SeralizedStore::get(self, &(KEY_PREFIX.to_owned() + name))
+    }
+
+    fn save_smart_playlist(
+        &mut self,
+        smart: &SmartPlaylist,
+    ) -> std::result::Result<(), Self::Error> {
+        SeralizedStore::insert(self, KEY_PREFIX.to_owned() + &smart.name, smart.clone())?;
+        let mut names = self.smart_playlist_names()?;
+        if !names.contains(&smart.name) {
+            names.push(smart.name.clone());
+            SeralizedStore::insert(self, NAMES_KEY, names)?;
+        }
+        Ok(())
+    }
+
+    fn remove_smart_playlist(
+        &mut self,
+        name: &str,
+    ) -> std::result::Result<Option<SmartPlaylist>, Self::Error> {
+        let mut names = self.smart_playlist_names()?;
+        if let Some(index) = names.iter().position(|stored| stored == name) {
+            names.remove(index);
+            SeralizedStore::insert(self, NAMES_KEY, names)?;
+        }
+        SeralizedStore::remove(self, &(KEY_PREFIX.to_owned() + name))
+    }
+}
+
+impl Client {
+    /// Gathers the candidate tracks of the smart playlist from its sources,
+    /// with the data needed to evaluate it
+    ///
+    /// Requires [`Scopes::UserLibraryRead`](crate::authorisation::Scopes::UserLibraryRead) for the library
+    /// and [`Scopes::UserTopRead`](crate::authorisation::Scopes::UserTopRead) for top tracks.
+    /// Audio features are only requested if the rule or sort needs them.
+    pub async fn smart_playlist_candidates(
+        &mut self,
+        smart: &SmartPlaylist,
+    ) -> Result<Vec<Candidate>> {
+        let mut top_tracks = BTreeMap::new();
+        for time_range in smart.time_ranges() {
+            top_tracks.insert(time_range, self.all_top_tracks(time_range).await?);
+        }
+
+        let mut candidates = Vec::new();
+        let mut indices = HashMap::new();
+        for source in &smart.sources {
+            match source {
+                Source::Library => {
+                    for saved in self.all_saved_tracks().await? {
+                        if !indices.contains_key(&saved.track.uri) {
+                            indices.insert(saved.track.uri.clone(), candidates.len());
+                            candidates.push(Candidate {
+                                added_at: Some(saved.added_at),
+                                ..Candidate::new(saved.track)
+                            });
+                        }
+                    }
+                }
+                Source::TopTracks { time_range } => {
+                    for track in &top_tracks[time_range] {
+                        if !indices.contains_key(&track.uri) {
+                            indices.insert(track.uri.clone(), candidates.len());
+                            candidates.push(Candidate::new(track.clone()));
+                        }
+                    }
+                }
+            }
+        }
+        for (time_range, tracks) in &top_tracks {
+            for track in tracks {
+                if let Some(&index) = indices.get(&track.uri) {
+                    candidates[index].top.insert(*time_range);
+                }
+            }
+        }
+
+        if smart.needs_features() {
+            let ids: Vec<Option<TrackId>> = candidates
+                .iter()
+                .map(|candidate| candidate.track.id.clone())
+                .collect();
+            let features = self.audio_features_batched(&ids).await?;
+            for (candidate, features) in candidates.iter_mut().zip(features) {
+                candidate.features = features;
+            }
+        }
+        Ok(candidates)
+    }
+
+    /// Evaluates the smart playlist and syncs its playlist to the result
+    ///
+    /// Requires the scopes of [`Client::smart_playlist_candidates()`] and [`Client::sync_playlist()`].
+    /// If the smart playlist has no playlist yet, a private one is created for the current user
+    /// and set on `smart`, which should then be stored again.
+    pub async fn run_smart_playlist(
+        &mut self,
+        smart: &mut SmartPlaylist,
+    ) -> std::result::Result<SyncReport, SyncError> {
+        let candidates = self.smart_playlist_candidates(smart).await?;
+        let now = instant::SystemTime::now()
+            .duration_since(instant::SystemTime::UNIX_EPOCH)
+            .unwrap_or_default();
+        let uris = smart.evaluate(&candidates, now);
+
+        let id = match &smart.playlist {
+            Some(id) => id.clone(),
+            None => {
+                let user = self.current_user().await?;
+                let playlist = self
+                    .create_playlist(
+                        &user.id,
+                        &smart.name,
+                        Some(false),
+                        smart.description.as_deref(),
+                    )
+                    .await?;
+                smart.playlist.insert(playlist.id).clone()
+            }
+        };
+        self.sync_playlist(&id, &uris).await
+    }
+
+    /// Every track saved in the library, by paging through them
+    async fn all_saved_tracks(&mut self) -> Result<Vec<crate::tracks::SavedTrack>> {
+        let mut saved = Vec::new();
+        loop {
+            let page = self
+                .saved_tracks(None, Some(50), Some(saved.len() as u32))
+                .await?;
+            let done = page.next.is_none() || page.items.is_empty();
+            saved.extend(page.items);
+            if done {
+                return Ok(saved);
+            }
+        }
+    }
+
+    /// Every top track over the `time_range`, by paging through them
+    async fn all_top_tracks(&mut self, time_range: TimeRange) -> Result<Vec<Track>> {
+        let mut tracks = Vec::new();
+        loop {
+            let page = self
+                .top_tracks(Some(time_range), Some(50), Some(tracks.len() as u32))
+                .await?;
+            let done = page.next.is_none() || page.items.is_empty();
+            tracks.extend(page.items);
+            if done {
+                return Ok(tracks);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{audio::tests::audio_features, tracks::tests::track};
+    use std::collections::HashMap;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    /// 2024-03-31T00:00:00Z
+    const NOW: Duration = Duration::from_secs(1711843200);
+
+    fn candidate(id: &str, artist: &str, added_at: Option<&str>, energy: Option<f64>) -> Candidate {
+        Candidate {
+            added_at: added_at.map(str::to_owned),
+            features: energy.map(|energy| audio_features(id, energy, 120.0, 0, 1)),
+            ..Candidate::new(track(id, id, artist, "USUM71703861"))
+        }
+    }
+
+    fn fresh_energy() -> SmartPlaylist {
+        let mut smart = SmartPlaylist::new("Fresh energy");
+        smart.rule(Rule::All {
+            rules: vec![
+                Rule::SavedWithin { days: 30 },
+                Rule::Attribute {
+                    attribute: TunableAttribute::Energy,
+                    comparison: Comparison::Greater,
+                    value: 0.7,
+                },
+                Rule::Not {
+                    rule: Box::new(Rule::Artist {
+                        artist: "skipped".to_owned(),
+                    }),
+                },
+            ],
+        });
+        smart
+    }
+
+    #[wasm_bindgen_test]
+    fn test_rule_matches() {
+        let smart = fresh_energy();
+        let matches = |candidate: &Candidate| smart.rule.matches(candidate, NOW);
+        assert!(matches(&candidate(
+            "a",
+            "Band",
+            Some("2024-03-10T12:00:00Z"),
+            Some(0.8)
+        )));
+        // Saved too long ago
+        assert!(!matches(&candidate(
+            "b",
+            "Band",
+            Some("2024-02-28T12:00:00Z"),
+            Some(0.8)
+        )));
+        // Not energetic, or without audio features
+        assert!(!matches(&candidate(
+            "c",
+            "Band",
+            Some("2024-03-10T12:00:00Z"),
+            Some(0.7)
+        )));
+        assert!(!matches(&candidate(
+            "d",
+            "Band",
+            Some("2024-03-10T12:00:00Z"),
+            None
+        )));
+        // Excluded artist, matched by case insensitive name
+        assert!(!matches(&candidate(
+            "e",
+            "Skipped",
+            Some("2024-03-10T12:00:00Z"),
+            Some(0.8)
+        )));
+        // Not in the library
+        assert!(!matches(&candidate("f", "Band", None, Some(0.8))));
+
+        let mut top = candidate("g", "Band", None, None);
+        top.top.insert(TimeRange::ShortTerm);
+        let rule = Rule::Any {
+            rules: vec![
+                Rule::TopTrack {
+                    time_range: TimeRange::ShortTerm,
+                },
+                Rule::Attribute {
+                    attribute: TunableAttribute::Popularity,
+                    comparison: Comparison::GreaterOrEqual,
+                    value: 101.0,
+                },
+            ],
+        };
+        assert!(rule.matches(&top, NOW));
+        assert!(!rule.matches(&candidate("h", "Band", None, None), NOW));
+        assert!(Rule::default().matches(&top, NOW));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_evaluate() {
+        let mut local = candidate("local", "Band", None, Some(0.1));
+        local.track.is_local = true;
+        let candidates = [
+            candidate("a", "Band", None, Some(0.5)),
+            candidate("b", "Band", None, None),
+            candidate("c", "Band", None, Some(0.9)),
+            candidate("a", "Band", None, Some(0.5)),
+            local,
+            candidate("d", "Band", None, Some(0.2)),
+        ];
+        let uris = |smart: &SmartPlaylist| -> Vec<String> {
+            smart
+                .evaluate(&candidates, NOW)
+                .into_iter()
+                .map(|uri| uri.trim_start_matches("spotify:track:").to_owned())
+                .collect()
+        };
+
+        let mut smart = SmartPlaylist::new("All");
+        assert_eq!(uris(&smart), ["a", "b", "c", "d"]);
+
+        // Tracks without a value are last in either direction
+        smart.sort(SortKey::Attribute(TunableAttribute::Energy), false);
+        assert_eq!(uris(&smart), ["d", "a", "c", "b"]);
+        smart.sort(SortKey::Attribute(TunableAttribute::Energy), true);
+        assert_eq!(uris(&smart), ["c", "a", "d", "b"]);
+
+        smart.limit(Some(2));
+        assert_eq!(uris(&smart), ["c", "a"]);
+
+        smart.rule(Rule::Attribute {
+            attribute: TunableAttribute::Energy,
+            comparison: Comparison::Less,
+            value: 0.6,
+        });
+        assert_eq!(uris(&smart), ["a", "d"]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_requirements() {
+        let mut smart = fresh_energy();
+        assert!(smart.needs_features());
+        assert!(smart.time_ranges().is_empty());
+
+        smart
+            .rule(Rule::Not {
+                rule: Box::new(Rule::TopTrack {
+                    time_range: TimeRange::LongTerm,
+                }),
+            })
+            .source(Source::TopTracks {
+                time_range: TimeRange::ShortTerm,
+            })
+            .sort(SortKey::Attribute(TunableAttribute::Popularity), true);
+        assert!(!smart.needs_features());
+        assert_eq!(
+            smart.time_ranges(),
+            BTreeSet::from([TimeRange::ShortTerm, TimeRange::LongTerm])
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_serialize() {
+        let mut smart = fresh_energy();
+        smart
+            .sort(SortKey::Attribute(TunableAttribute::Tempo), false)
+            .limit(Some(100));
+        let json = serde_json::to_value(&smart).unwrap();
+        assert_eq!(json["rule"]["type"], "all");
+        assert_eq!(json["rule"]["rules"][1]["attribute"], "energy");
+        assert_eq!(json["sort"]["key"]["attribute"], "tempo");
+        assert_eq!(json["sources"][0]["type"], "library");
+        assert_eq!(
+            serde_json::from_value::<SmartPlaylist>(json).unwrap(),
+            smart
+        );
+
+        // Only the name and sources are required
+        assert_eq!(
+            serde_json::from_str::<SmartPlaylist>(
+                r#"{"name":"Fresh energy","sources":[{"type":"library"}]}"#
+            )
+            .unwrap(),
+            SmartPlaylist::new("Fresh energy")
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_store() {
+        let mut store = HashMap::<String, String>::new();
+        let mut smart = fresh_energy();
+        let other = SmartPlaylist::new("Other");
+
+        assert_eq!(store.smart_playlist(&smart.name).unwrap(), None);
+        store.save_smart_playlist(&smart).unwrap();
+        store.save_smart_playlist(&other).unwrap();
+        smart.playlist = Some(PlaylistId::new("37i9dQZF1DXcBWIGoYBM5M").unwrap());
+        store.save_smart_playlist(&smart).unwrap();
+        assert_eq!(
+            store.smart_playlist_names().unwrap(),
+            ["Fresh energy", "Other"]
+        );
+        assert_eq!(
+            store.smart_playlist(&smart.name).unwrap(),
+            Some(smart.clone())
+        );
+
+        assert_eq!(
+            store.remove_smart_playlist(&smart.name).unwrap(),
+            Some(smart)
+        );
+        assert_eq!(store.smart_playlist_names().unwrap(), ["Other"]);
+        assert_eq!(store.remove_smart_playlist("Missing").unwrap(), None);
+    }
+}
@@ -0,0 +1,530 @@
+//! Listening statistics from the recently played tracks
+//!
+//! Spotify only returns the 50 most recent plays,
+//! so [`Client::update_listening_history()`] merges them into a rolling [`ListeningHistory`],
+//! kept across sessions in any [`SeralizedStore`], or periodically by a [`HistoryCollector`].
+//! [`ListeningStats`] are then computed from the history,
+//! with genres from the artists of the plays and the user's top artists.
+//!
+//! ```ignore
+//! let mut history = ListeningHistory::load(&store)?;
+//! client.update_listening_history(&mut history).await?;
+//! history.save(&mut store)?;
+//! let stats = client.listening_stats(&history, -60).await?;
+//! ```
+
+use crate::{batch::MAX_IDS_50, model::*, player::PlayHistory, users::TimeRange, Client};
+use instant::Duration;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    ops::{Bound, RangeBounds},
+};
+use utils::{request::Result, SeralizedStore};
+
+mod collector;
+pub use collector::*;
+
+/// The key the listening history is stored under
+const KEY: &str = "spotify-api-listening-history";
+
+/// The number of requests for artists in flight at a time
+const ARTISTS_CONCURRENCY: usize = 2;
+
+/// The number of milliseconds in a day
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// An artist of a [`Play`]
+#[derive(
+    Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, serde::Serialize, serde::Deserialize,
+)]
+pub struct PlayedArtist {
+    /// The Spotify ID for the artist, `None` for local files
+    pub id: Option<ArtistId>,
+    /// The name of the artist
+    pub name: String,
+}
+
+/// A play of a track, keeping only what's needed for statistics
+#[derive(
+    Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, serde::Serialize, serde::Deserialize,
+)]
+pub struct Play {
+    /// When the track was played, in milliseconds since the Unix epoch
+    pub played_at: u64,
+    /// The Spotify URI for the track
+    pub uri: String,
+    /// The name of the track
+    pub name: String,
+    /// The artists of the track
+    pub artists: Vec<PlayedArtist>,
+    /// The duration of the track in milliseconds
+    pub duration_ms: u32,
+    /// The Spotify URI for the context the track was played from
+    pub context_uri: Option<String>,
+}
+
+impl Play {
+    /// The play of the `history`, `None` if when it was played can't be parsed
+    pub fn from_history(history: &PlayHistory) -> Option<Self> {
+        Some(Self {
+            played_at: parse_timestamp(&history.played_at)?.as_millis() as u64,
+            uri: history.track.uri.clone(),
+            name: history.track.name.clone(),
+            artists: history
+                .track
+                .artists
+                .iter()
+                .map(|artist| PlayedArtist {
+                    id: artist.id.clone(),
+                    name: artist.name.clone(),
+                })
+                .collect(),
+            duration_ms: history.track.duration_ms,
+            context_uri: history.context.as_ref().map(|context| context.uri.clone()),
+        })
+    }
+}
+
+/// The plays of the user, oldest first, with at most one play at any time
+#[derive(
+    Clone,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    Debug,
+    Default,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[serde(transparent)]
+pub struct ListeningHistory {
+    plays: Vec<Play>,
+}
+
+impl ListeningHistory {
+    /// An empty history
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The plays, oldest first
+    pub fn plays(&self) -> &[Play] {
+        &self.plays
+    }
+
+    /// The most recent play
+    pub fn latest(&self) -> Option<&Play> {
+        self.plays.last()
+    }
+
+    /// The plays within the `range` of times in milliseconds since the Unix epoch, oldest first
+    pub fn range(&self, range: impl RangeBounds<u64>) -> &[Play] {
+        let start = match range.start_bound() {
+            Bound::Included(start) => self.plays.partition_point(|play| play.played_at < *start),
+            Bound::Excluded(start) => self.plays.partition_point(|play| play.played_at <= *start),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => self.plays.partition_point(|play| play.played_at <= *end),
+            Bound::Excluded(end) => self.plays.partition_point(|play| play.played_at < *end),
+            Bound::Unbounded => self.plays.len(),
+        };
+        &self.plays[start..end.max(start)]
+    }
+
+    /// Merges the `plays` into the history, skipping plays at the same time as one already in it,
+    /// and returns how many were added
+    pub fn merge(&mut self, plays: impl IntoIterator<Item = Play>) -> usize {
+        let len = self.plays.len();
+        let mut seen: HashSet<u64> = self.plays.iter().map(|play| play.played_at).collect();
+        self.plays
+            .extend(plays.into_iter().filter(|play| seen.insert(play.played_at)));
+        self.plays.sort_by_key(|play| play.played_at);
+        self.plays.len() - len
+    }
+
+    /// Removes the plays before `since`, in milliseconds since the Unix epoch
+    pub fn retain_since(&mut self, since: u64) {
+        self.plays.retain(|play| play.played_at >= since);
+    }
+
+    /// Loads the history from the `store`, empty if none was saved
+    pub fn load<S: SeralizedStore>(store: &S) -> std::result::Result<Self, S::Error> {
+        Ok(store.get(&KEY)?.unwrap_or_default())
+    }
+
+    /// Saves the history to the `store`, replacing any saved before
+    pub fn save<S: SeralizedStore>(&self, store: &mut S) -> std::result::Result<(), S::Error> {
+        store.insert(KEY, self.clone())?;
+        Ok(())
+    }
+}
+
+/// Statistics of a [`ListeningHistory`]
+///
+/// Days and hours are in the local time of the UTC offset they were computed with.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct ListeningStats {
+    /// The number of plays
+    pub plays: usize,
+    /// The estimated listening time in milliseconds,
+    /// each play counting for its track's duration capped by the time until the next play
+    pub listening_ms: u64,
+    /// The listening time in milliseconds per day with plays, by `YYYY-MM-DD` date
+    pub per_day: BTreeMap<String, u64>,
+    /// The listening time in milliseconds per hour of the day
+    pub per_hour: [u64; 24],
+    /// The genres of the artists played and how many plays had each, most played first
+    pub top_genres: Vec<(String, usize)>,
+    /// The number of distinct artists played
+    pub unique_artists: usize,
+    /// How evenly plays are spread over the first artist of each track, from 0 for a single artist to 1 for an even spread
+    pub artist_diversity: f64,
+    /// The number of plays of tracks not played earlier in the history
+    pub new_plays: usize,
+    /// The number of plays of tracks played earlier in the history
+    pub repeat_plays: usize,
+    /// The number of consecutive days with plays up to today, or yesterday if there are none today yet
+    pub current_streak: u32,
+    /// The most consecutive days with plays
+    pub longest_streak: u32,
+}
+
+impl ListeningStats {
+    /// Computes the statistics of the `plays`, oldest first
+    ///
+    /// `genres` are the genres of each artist, `utc_offset` is the offset of local time from UTC in minutes,
+    /// and `now` is the time since the Unix epoch.
+    pub fn compute(
+        plays: &[Play],
+        genres: &HashMap<ArtistId, Vec<String>>,
+        utc_offset: i32,
+        now: Duration,
+    ) -> Self {
+        let offset_ms = i64::from(utc_offset) * 60 * 1000;
+        let local_day = |time: i64| (time + offset_ms).div_euclid(DAY_MS);
+
+        let mut stats = Self {
+            plays: plays.len(),
+            ..Self::default()
+        };
+        let mut days = BTreeSet::new();
+        let mut genre_plays: HashMap<&str, usize> = HashMap::new();
+        let mut artist_plays: HashMap<&str, usize> = HashMap::new();
+        let mut played = HashSet::new();
+        for (i, play) in plays.iter().enumerate() {
+            let listened = plays
+                .get(i + 1)
+                .map_or(u64::from(play.duration_ms), |next| {
+                    u64::from(play.duration_ms).min(next.played_at.saturating_sub(play.played_at))
+                });
+            let time = play.played_at as i64;
+            let day = local_day(time);
+            days.insert(day);
+            stats.listening_ms += listened;
+            *stats.per_day.entry(format_date(day)).or_default() += listened;
+            stats.per_hour[((time + offset_ms).rem_euclid(DAY_MS) / (60 * 60 * 1000)) as usize] +=
+                listened;
+
+            let play_genres: BTreeSet<&str> = play
+                .artists
+                .iter()
+                .filter_map(|artist| genres.get(artist.id.as_ref()?))
+                .flatten()
+                .map(String::as_str)
+                .collect();
+            for genre in play_genres {
+                *genre_plays.entry(genre).or_default() += 1;
+            }
+            if let Some(artist) = play.artists.first() {
+                let key = artist
+                    .id
+                    .as_ref()
+                    .map_or(artist.name.as_str(), |id| id.as_str());
+                *artist_plays.entry(key).or_default() += 1;
+            }
+            if played.insert(&play.uri) {
+                stats.new_plays += 1;
+            } else {
+                stats.repeat_plays += 1;
+            }
+        }
+
+        stats.top_genres = genre_plays
+            .into_iter()
+            .map(|(genre, plays)| (genre.to_owned(), plays))
+            .collect();
+        stats
+            .top_genres
+            .sort_by(|(l_genre, l_plays), (r_genre, r_plays)| {
+                r_plays.cmp(l_plays).then_with(|| l_genre.cmp(r_genre))
+            });
+
+        let unique_artists: BTreeSet<&str> = plays
+            .iter()
+            .flat_map(|play| &play.artists)
+            .map(|artist| {
+                artist
+                    .id
+                    .as_ref()
+                    .map_or(artist.name.as_str(), |id| id.as_str())
+            })
+            .collect();
+        stats.unique_artists = unique_artists.len();
+        stats.artist_diversity = diversity(artist_plays.values().copied());
+
+        let mut streak = 0;
+        let mut previous = None;
+        for day in &days {
+            streak = match previous {
+                Some(previous) if previous + 1 == *day => streak + 1,
+                _ => 1,
+            };
+            stats.longest_streak = stats.longest_streak.max(streak);
+            previous = Some(*day);
+        }
+        let today = local_day(now.as_millis() as i64);
+        if previous.is_some_and(|last| last == today || last + 1 == today) {
+            stats.current_streak = streak;
+        }
+        stats
+    }
+
+    /// The share of plays of tracks not played earlier in the history, from 0 to 1
+    pub fn new_ratio(&self) -> f64 {
+        match self.plays {
+            0 => 0.0,
+            plays => self.new_plays as f64 / plays as f64,
+        }
+    }
+}
+
+/// The [Shannon entropy](https://en.wikipedia.org/wiki/Entropy_(information_theory)) of the `counts`
+/// normalised from 0 to 1, 0 if there are less than two
+fn diversity(counts: impl Iterator<Item = usize> + Clone) -> f64 {
+    let total: usize = counts.clone().sum();
+    let len = counts.clone().filter(|count| *count > 0).count();
+    if len < 2 {
+        return 0.0;
+    }
+    let entropy: f64 = counts
+        .filter(|count| *count > 0)
+        .map(|count| {
+            let p = count as f64 / total as f64;
+            -p * p.ln()
+        })
+        .sum();
+    entropy / (len as f64).ln()
+}
+
+/// Formats the `days` since the Unix epoch as a `YYYY-MM-DD` date
+fn format_date(days: i64) -> String {
+    // The inverse of the days since the epoch in `parse_timestamp()`
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+impl Client {
+    /// Merges the recently played tracks after the latest play of the `history` into it,
+    /// returning how many plays were added
+    ///
+    /// Requires [`Scopes::UserReadRecentlyPlayed`](crate::authorisation::Scopes::UserReadRecentlyPlayed).
+    /// Only the 50 most recent plays are available, so plays are lost if not updated often enough.
+    pub async fn update_listening_history(
+        &mut self,
+        history: &mut ListeningHistory,
+    ) -> Result<usize> {
+        let after = history.latest().map(|play| play.played_at);
+        let page = self.recently_played(Some(50), after, None).await?;
+        Ok(history.merge(page.items.iter().filter_map(Play::from_history)))
+    }
+
+    /// Computes the [`ListeningStats`] of the `history` now, with `utc_offset` the offset of local time from UTC in minutes
+    ///
+    /// Requires [`Scopes::UserTopRead`](crate::authorisation::Scopes::UserTopRead).
+    /// Genres of the user's top artists are used where known, those of other artists are requested.
+    pub async fn listening_stats(
+        &mut self,
+        history: &ListeningHistory,
+        utc_offset: i32,
+    ) -> Result<ListeningStats> {
+        let mut genres: HashMap<ArtistId, Vec<String>> = self
+            .top_artists(Some(TimeRange::LongTerm), Some(50), None)
+            .await?
+            .items
+            .into_iter()
+            .map(|artist| (artist.id, artist.genres))
+            .collect();
+
+        let unknown: Vec<ArtistId> = history
+            .plays()
+            .iter()
+            .flat_map(|play| &play.artists)
+            .filter_map(|artist| artist.id.clone())
+            .filter(|id| !genres.contains_key(id))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let artists = self
+            .batch(
+                &unknown,
+                MAX_IDS_50,
+                ARTISTS_CONCURRENCY,
+                |mut client, ids| async move { client.artists(ids).await },
+            )
+            .await
+            .into_result()?;
+        genres.extend(
+            artists
+                .into_iter()
+                .flatten()
+                .map(|artist| (artist.id, artist.genres)),
+        );
+
+        let now = instant::SystemTime::now()
+            .duration_since(instant::SystemTime::UNIX_EPOCH)
+            .unwrap_or_default();
+        Ok(ListeningStats::compute(
+            history.plays(),
+            &genres,
+            utc_offset,
+            now,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    const ARTIST: &str = "0TnOYISbd1XYRBk9myaseg";
+    const OTHER: &str = "4NHQUGzhtTLFvgF5SZesLK";
+
+    fn play(played_at: &str, track: &str, artist: &str, duration_ms: u32) -> Play {
+        Play {
+            played_at: parse_timestamp(played_at).unwrap().as_millis() as u64,
+            uri: format!("spotify:track:{track}"),
+            name: track.to_owned(),
+            artists: vec![PlayedArtist {
+                id: Some(ArtistId::new(artist).unwrap()),
+                name: artist.to_owned(),
+            }],
+            duration_ms,
+            context_uri: None,
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn test_format_date() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(-1), "1969-12-31");
+        assert_eq!(format_date(19782), "2024-02-29");
+        assert_eq!(format_date(11017), "2000-03-01");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_history() {
+        let mut history = ListeningHistory::new();
+        let first = play("2024-03-01T10:00:00Z", "a", ARTIST, 200000);
+        let second = play("2024-03-01T10:05:00Z", "b", ARTIST, 200000);
+        assert_eq!(history.merge([second.clone(), first.clone()]), 2);
+        assert_eq!(history.merge([first.clone(), second.clone()]), 0);
+        assert_eq!(history.plays(), [first.clone(), second.clone()]);
+        assert_eq!(history.latest(), Some(&second));
+
+        let mut store = HashMap::<String, String>::new();
+        assert_eq!(
+            ListeningHistory::load(&store).unwrap(),
+            ListeningHistory::new()
+        );
+        history.save(&mut store).unwrap();
+        assert_eq!(ListeningHistory::load(&store).unwrap(), history);
+
+        assert_eq!(history.range(..), [first.clone(), second.clone()]);
+        assert_eq!(history.range(first.played_at + 1..).len(), 1);
+        assert_eq!(history.range(..=first.played_at)[0], first);
+        assert_eq!(history.range(..first.played_at), []);
+        assert_eq!(history.range(second.played_at..first.played_at), []);
+
+        history.retain_since(second.played_at);
+        assert_eq!(history.plays(), [second]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_stats() {
+        let plays = [
+            play("2024-02-28T23:30:00Z", "a", ARTIST, 200000),
+            // Skipped after a minute
+            play("2024-02-29T09:00:00Z", "b", ARTIST, 200000),
+            play("2024-02-29T09:01:00Z", "a", OTHER, 200000),
+            play("2024-03-02T09:00:00Z", "c", ARTIST, 100000),
+        ];
+        let genres = HashMap::from([
+            (
+                ArtistId::new(ARTIST).unwrap(),
+                vec!["pop".to_owned(), "dance pop".to_owned()],
+            ),
+            (ArtistId::new(OTHER).unwrap(), vec!["pop".to_owned()]),
+        ]);
+        // 2024-03-02T12:00:00Z
+        let now = Duration::from_secs(1709380800);
+
+        let stats = ListeningStats::compute(&plays, &genres, 0, now);
+        assert_eq!(stats.plays, 4);
+        assert_eq!(stats.listening_ms, 200000 + 60000 + 200000 + 100000);
+        assert_eq!(
+            stats.per_day,
+            BTreeMap::from([
+                ("2024-02-28".to_owned(), 200000),
+                ("2024-02-29".to_owned(), 260000),
+                ("2024-03-02".to_owned(), 100000),
+            ])
+        );
+        assert_eq!(stats.per_hour[23], 200000);
+        assert_eq!(stats.per_hour[9], 360000);
+        assert_eq!(
+            stats.top_genres,
+            [("pop".to_owned(), 4), ("dance pop".to_owned(), 3)]
+        );
+        assert_eq!(stats.unique_artists, 2);
+        assert_eq!((stats.new_plays, stats.repeat_plays), (3, 1));
+        assert_eq!(stats.new_ratio(), 0.75);
+        assert_eq!((stats.current_streak, stats.longest_streak), (1, 2));
+
+        // An hour ahead of UTC the first play is on the next day, breaking the streak
+        let stats = ListeningStats::compute(&plays, &genres, 60, now);
+        assert_eq!(stats.per_day.len(), 2);
+        assert_eq!(stats.per_hour[0], 200000);
+        assert_eq!((stats.current_streak, stats.longest_streak), (1, 1));
+
+        let empty = ListeningStats::compute(&[], &genres, 0, now);
+        assert_eq!(empty, ListeningStats::default());
+        assert_eq!(empty.new_ratio(), 0.0);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_diversity() {
+        assert_eq!(diversity([].into_iter()), 0.0);
+        assert_eq!(diversity([5].into_iter()), 0.0);
+        assert!((diversity([2, 2, 2].into_iter()) - 1.0).abs() < 1e-9);
+        assert!(diversity([9, 1].into_iter()) < 0.5);
+    }
+}
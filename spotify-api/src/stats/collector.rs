@@ -0,0 +1,272 @@
+//! Periodically collecting the recently played tracks into a persistent [`ListeningHistory`]
+
+use super::ListeningHistory;
+use crate::Client;
+use instant::Duration;
+use utils::{
+    abort::{abortable, AbortHandle},
+    SeralizedStore,
+};
+
+/// The default time between polls of the recently played tracks,
+/// short enough that fewer than the 50 available plays are made in between
+pub const DEFAULT_COLLECT_INTERVAL: Duration = Duration::from_secs(30 * 60);
+
+/// The default age of the oldest plays kept in the history
+pub const DEFAULT_RETENTION: Duration = Duration::from_secs(365 * 24 * 60 * 60);
+
+/// The most plays returned by a poll, more plays may have been missed if a poll returns as many new plays
+const RECENTLY_PLAYED_LIMIT: usize = 50;
+
+/// [`HistoryCollector`] Errors
+#[derive(Debug)]
+pub enum CollectError<E> {
+    /// Errors requesting the recently played tracks
+    Request(utils::request::Error),
+    /// Errors saving the history to the store
+    Store(E),
+}
+impl<E: std::fmt::Debug + std::fmt::Display> std::error::Error for CollectError<E> {}
+impl<E: std::fmt::Display> std::fmt::Display for CollectError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CollectError::Request(err) => write!(f, "{err}"),
+            CollectError::Store(err) => write!(f, "saving the listening history failed, {err}"),
+        }
+    }
+}
+
+impl<E: std::fmt::Display> PartialEq for CollectError<E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_string() == other.to_string()
+            && core::mem::discriminant(self) == core::mem::discriminant(other)
+    }
+}
+
+/// Polls the recently played tracks into a [`ListeningHistory`] saved in a [`SeralizedStore`],
+/// such as [`utils::LocalStore`] so the history survives reloads
+///
+/// Each poll only requests plays after the latest one in the history, with the `after` cursor,
+/// and the history is saved whenever it changes.
+pub struct HistoryCollector<S> {
+    client: Client,
+    store: S,
+    history: ListeningHistory,
+    interval: Duration,
+    retention: Option<Duration>,
+}
+
+impl<S: SeralizedStore> HistoryCollector<S> {
+    /// Creates a collector making requests with the `client`, continuing the history saved in the `store`
+    pub fn new(client: Client, store: S) -> Result<Self, S::Error> {
+        let history = ListeningHistory::load(&store)?;
+        Ok(Self {
+            client,
+            store,
+            history,
+            interval: DEFAULT_COLLECT_INTERVAL,
+            retention: Some(DEFAULT_RETENTION),
+        })
+    }
+
+    /// Sets the time between polls when [running](Self::run())
+    pub fn set_interval(&mut self, interval: Duration) -> &mut Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets the age of the oldest plays kept, or keeps every play if `None`
+    pub fn set_retention(&mut self, retention: Option<Duration>) -> &mut Self {
+        self.retention = retention;
+        self
+    }
+
+    /// The collected history
+    pub fn history(&self) -> &ListeningHistory {
+        &self.history
+    }
+
+    /// Polls the recently played tracks once, saving the history if it changed,
+    /// and returns how many plays were added
+    ///
+    /// Requires [`Scopes::UserReadRecentlyPlayed`](crate::authorisation::Scopes::UserReadRecentlyPlayed).
+    pub async fn collect(&mut self) -> Result<usize, CollectError<S::Error>> {
+        let had_plays = self.history.latest().is_some();
+        let added = self
+            .client
+            .update_listening_history(&mut self.history)
+            .await
+            .map_err(CollectError::Request)?;
+        if missed_plays(had_plays, added) {
+            log::warn!("{added} plays since the last poll, earlier plays may have been missed");
+        }
+
+        let now = instant::SystemTime::now()
+            .duration_since(instant::SystemTime::UNIX_EPOCH)
+            .unwrap_or_default();
+        self.retain_and_save(added, now)
+            .map_err(CollectError::Store)?;
+        Ok(added)
+    }
+
+    /// Removes the plays older than the retention at `now`,
+    /// then saves the history if any were removed or `added`
+    fn retain_and_save(&mut self, added: usize, now: Duration) -> Result<(), S::Error> {
+        let len = self.history.plays().len();
+        if let Some(retention) = self.retention {
+            self.history
+                .retain_since(now.saturating_sub(retention).as_millis() as u64);
+        }
+        if added > 0 || self.history.plays().len() != len {
+            self.history.save(&mut self.store)?;
+        }
+        Ok(())
+    }
+
+    /// Polls every [interval](Self::set_interval()) until `abort` is aborted,
+    /// calling `on_collect` with the history and the number of plays added after each successful poll
+    ///
+    /// Failed polls are logged and retried at the next interval.
+    /// Aborting also cancels a poll in flight,
+    /// the client's own [abort handle](Client::set_abort_handle()) is restored once it returns.
+    pub async fn run(
+        &mut self,
+        abort: &AbortHandle,
+        mut on_collect: impl FnMut(&ListeningHistory, usize),
+    ) where
+        S::Error: std::fmt::Display,
+    {
+        let previous = self.client.abort_handle().cloned();
+        self.client.set_abort_handle(Some(abort.clone()));
+        while !abort.is_aborted() {
+            match self.collect().await {
+                Ok(added) => on_collect(&self.history, added),
+                Err(CollectError::Request(utils::request::Error::Cancelled)) => break,
+                Err(err) => log::warn!("collecting the listening history failed, {err}"),
+            }
+            let interval = self.interval;
+            let wait = async move {
+                utils::delay(interval).await;
+                Ok(())
+            };
+            if abortable(wait, Some(abort.clone()), None).await.is_err() {
+                break;
+            }
+        }
+        self.client.set_abort_handle(previous);
+    }
+}
+
+/// Returns if earlier plays may have been missed when a poll `added` plays,
+/// as it returned as many plays as it could since the latest play of a history that `had_plays`
+fn missed_plays(had_plays: bool, added: usize) -> bool {
+    had_plays && added >= RECENTLY_PLAYED_LIMIT
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{authorisation::AccessToken, stats::Play};
+    use std::collections::HashMap;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    fn test_collector_continues_saved_history() {
+        let mut history = ListeningHistory::new();
+        history.merge([play(1709296496789)]);
+        let mut store = HashMap::<String, String>::new();
+        history.save(&mut store).unwrap();
+
+        let mut collector =
+            HistoryCollector::new(Client::new(AccessToken::default()), store).unwrap();
+        collector
+            .set_interval(Duration::from_secs(60))
+            .set_retention(None);
+        assert_eq!(collector.history(), &history);
+        assert_eq!(collector.interval, Duration::from_secs(60));
+    }
+
+    fn play(played_at: u64) -> Play {
+        Play {
+            played_at,
+            uri: "spotify:track:11dFghVXANMlKmJXsNCbNl".to_owned(),
+            name: "Track".to_owned(),
+            artists: Vec::new(),
+            duration_ms: 200000,
+            context_uri: None,
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn test_retain_and_save() {
+        const DAY: u64 = 24 * 60 * 60 * 1000;
+        let now = Duration::from_millis(1709296496789);
+        let now_ms = now.as_millis() as u64;
+        let mut collector = HistoryCollector::new(
+            Client::new(AccessToken::default()),
+            HashMap::<String, String>::new(),
+        )
+        .unwrap();
+        collector.set_retention(Some(Duration::from_secs(7 * 24 * 60 * 60)));
+
+        // Nothing changed so nothing is saved
+        collector.retain_and_save(0, now).unwrap();
+        assert!(collector.store.is_empty());
+
+        // Plays older than the retention are removed
+        let plays = [play(now_ms - 8 * DAY), play(now_ms - DAY), play(now_ms)];
+        let added = collector.history.merge(plays);
+        collector.retain_and_save(added, now).unwrap();
+        let saved = ListeningHistory::load(&collector.store).unwrap();
+        assert_eq!(saved.plays(), [play(now_ms - DAY), play(now_ms)]);
+        assert_eq!(collector.history(), &saved);
+
+        // Pruning alone saves the history
+        collector.store.clear();
+        let later = now + Duration::from_millis(7 * DAY);
+        collector.retain_and_save(0, later).unwrap();
+        assert_eq!(
+            ListeningHistory::load(&collector.store).unwrap().plays(),
+            [play(now_ms)]
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_missed_plays() {
+        assert!(!missed_plays(true, RECENTLY_PLAYED_LIMIT - 1));
+        // A full poll after earlier plays may have missed some
+        assert!(missed_plays(true, RECENTLY_PLAYED_LIMIT));
+        // Unless there were no earlier plays to continue from
+        assert!(!missed_plays(false, RECENTLY_PLAYED_LIMIT));
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_run_restores_abort_handle() {
+        let mut client = Client::new(AccessToken::default());
+        client.set_abort_handle(Some(AbortHandle::new()));
+        let mut collector =
+            HistoryCollector::new(client, HashMap::<String, String>::new()).unwrap();
+
+        let abort = AbortHandle::new();
+        abort.abort();
+        collector.run(&abort, |_, _| {}).await;
+        let restored = collector.client.abort_handle().unwrap();
+        assert!(!restored.is_aborted());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_collect_error() {
+        let err: CollectError<String> = CollectError::Store("quota exceeded".to_owned());
+        assert_eq!(
+            err.to_string(),
+            "saving the listening history failed, quota exceeded"
+        );
+        assert_eq!(err, CollectError::Store("quota exceeded".to_owned()));
+        assert_ne!(
+            CollectError::<String>::Request(utils::request::Error::Cancelled),
+            CollectError::Request(utils::request::Error::Timeout)
+        );
+    }
+}
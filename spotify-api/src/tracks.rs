@@ -0,0 +1,289 @@
+//! [Tracks](https://developer.spotify.com/documentation/web-api/reference/get-track) API
+//!
+//! With a `market`, tracks may be [relinked](https://developer.spotify.com/documentation/web-api/concepts/track-relinking)
+//! to a playable version, the originally requested track is then described by `linked_from`.
+
+use crate::{albums::SimplifiedAlbum, artists::SimplifiedArtist, client::Query, model::*, Client};
+use utils::request::Result;
+
+/// Known external IDs for a track
+#[derive(
+    Clone,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    Debug,
+    Default,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub struct ExternalIds {
+    /// [International Standard Recording Code](https://en.wikipedia.org/wiki/International_Standard_Recording_Code)
+    #[serde(default)]
+    pub isrc: Option<String>,
+    /// [International Article Number](https://en.wikipedia.org/wiki/International_Article_Number)
+    #[serde(default)]
+    pub ean: Option<String>,
+    /// [Universal Product Code](https://en.wikipedia.org/wiki/Universal_Product_Code)
+    #[serde(default)]
+    pub upc: Option<String>,
+}
+
+/// The originally requested track of a relinked track
+#[derive(
+    Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, serde::Serialize, serde::Deserialize,
+)]
+pub struct LinkedTrack {
+    /// Known external URLs for this track
+    #[serde(default)]
+    pub external_urls: ExternalUrls,
+    /// A link to the Web API endpoint providing full details of the track
+    pub href: String,
+    /// The Spotify ID for the track
+    pub id: TrackId,
+    /// The Spotify URI for the track
+    pub uri: String,
+}
+
+/// [Track Object](https://developer.spotify.com/documentation/web-api/reference/get-track)
+///
+/// Local files have no `id` and most of their details are empty.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Track {
+    /// The album on which the track appears
+    pub album: SimplifiedAlbum,
+    /// The artists who performed the track
+    pub artists: Vec<SimplifiedArtist>,
+    /// The [ISO 3166-1 alpha-2](https://en.wikipedia.org/wiki/ISO_3166-1_alpha-2) country codes the track is available in
+    #[serde(default)]
+    pub available_markets: Vec<String>,
+    /// The disc number, usually 1 unless the album consists of more than one disc
+    pub disc_number: u32,
+    /// The track length in milliseconds
+    pub duration_ms: u32,
+    /// Whether or not the track has explicit lyrics
+    pub explicit: bool,
+    /// Known external IDs for the track
+    #[serde(default)]
+    pub external_ids: ExternalIds,
+    /// Known external URLs for this track
+    #[serde(default)]
+    pub external_urls: ExternalUrls,
+    /// A link to the Web API endpoint providing full details of the track
+    #[serde(default)]
+    pub href: Option<String>,
+    /// The Spotify ID for the track
+    #[serde(default)]
+    pub id: Option<TrackId>,
+    /// True if the track is playable in the given market
+    #[serde(default)]
+    pub is_playable: Option<bool>,
+    /// The originally requested track if the track was relinked
+    #[serde(default)]
+    pub linked_from: Option<LinkedTrack>,
+    /// Included when a content restriction is applied
+    #[serde(default)]
+    pub restrictions: Option<Restrictions>,
+    /// The name of the track
+    pub name: String,
+    /// The popularity of the track between 0 and 100, with 100 being the most popular
+    #[serde(default)]
+    pub popularity: u32,
+    /// A URL to a 30 second preview of the track, if available
+    #[serde(default)]
+    pub preview_url: Option<String>,
+    /// The number of the track on its disc
+    pub track_number: u32,
+    /// The Spotify URI for the track
+    pub uri: String,
+    /// Whether or not the track is from a local file
+    #[serde(default)]
+    pub is_local: bool,
+}
+
+/// [Simplified Track Object](https://developer.spotify.com/documentation/web-api/reference/get-an-albums-tracks)
+#[derive(Clone, Eq, PartialEq, Hash, Debug, serde::Serialize, serde::Deserialize)]
+pub struct SimplifiedTrack {
+    /// The artists who performed the track
+    pub artists: Vec<SimplifiedArtist>,
+    /// The [ISO 3166-1 alpha-2](https://en.wikipedia.org/wiki/ISO_3166-1_alpha-2) country codes the track is available in
+    #[serde(default)]
+    pub available_markets: Vec<String>,
+    /// The disc number, usually 1 unless the album consists of more than one disc
+    pub disc_number: u32,
+    /// The track length in milliseconds
+    pub duration_ms: u32,
+    /// Whether or not the track has explicit lyrics
+    pub explicit: bool,
+    /// Known external URLs for this track
+    #[serde(default)]
+    pub external_urls: ExternalUrls,
+    /// A link to the Web API endpoint providing full details of the track
+    #[serde(default)]
+    pub href: Option<String>,
+    /// The Spotify ID for the track
+    #[serde(default)]
+    pub id: Option<TrackId>,
+    /// True if the track is playable in the given market
+    #[serde(default)]
+    pub is_playable: Option<bool>,
+    /// The originally requested track if the track was relinked
+    #[serde(default)]
+    pub linked_from: Option<LinkedTrack>,
+    /// Included when a content restriction is applied
+    #[serde(default)]
+    pub restrictions: Option<Restrictions>,
+    /// The name of the track
+    pub name: String,
+    /// A URL to a 30 second preview of the track, if available
+    #[serde(default)]
+    pub preview_url: Option<String>,
+    /// The number of the track on its disc
+    pub track_number: u32,
+    /// The Spotify URI for the track
+    pub uri: String,
+    /// Whether or not the track is from a local file
+    #[serde(default)]
+    pub is_local: bool,
+}
+
+/// A track saved in the current user's library
+#[derive(Clone, Eq, PartialEq, Hash, Debug, serde::Serialize, serde::Deserialize)]
+pub struct SavedTrack {
+    /// The [ISO 8601](https://en.wikipedia.org/wiki/ISO_8601) UTC timestamp of when the track was saved
+    pub added_at: String,
+    /// The saved track
+    pub track: Track,
+}
+
+impl Client {
+    /// [Get User's Saved Tracks](https://developer.spotify.com/documentation/web-api/reference/get-users-saved-tracks)
+    ///
+    /// Requires [`Scopes::UserLibraryRead`](crate::authorisation::Scopes::UserLibraryRead),
+    /// `limit` is between 1 and 50, defaulting to 20.
+    pub async fn saved_tracks(
+        &mut self,
+        market: Option<&str>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<Page<SavedTrack>> {
+        let market = self.resolve_market(market);
+        self.get(
+            &["me", "tracks"],
+            &Query {
+                market: market.as_deref(),
+                limit,
+                offset,
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// [Remove User's Saved Tracks](https://developer.spotify.com/documentation/web-api/reference/remove-tracks-user)
+    ///
+    /// Requires [`Scopes::UserLibraryModify`](crate::authorisation::Scopes::UserLibraryModify),
+    /// accepts a maximum of 50 IDs.
+    pub async fn remove_saved_tracks(&mut self, ids: &[TrackId]) -> Result<()> {
+        self.send_no_content(
+            reqwest::Method::DELETE,
+            &["me", "tracks"],
+            &Query {
+                ids: Some(&join_ids(ids)),
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// [Search for Item](https://developer.spotify.com/documentation/web-api/reference/search) restricted to tracks
+    ///
+    /// The query `q` may use field filters, e.g. `isrc:USUM71703861` or `track:Sunflower artist:Post Malone`.
+    /// `limit` is between 0 and 50, defaulting to 20.
+    pub async fn search_tracks(
+        &mut self,
+        q: &str,
+        market: Option<&str>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<Page<Track>> {
+        #[derive(serde::Deserialize)]
+        struct Response {
+            tracks: Page<Track>,
+        }
+
+        let market = self.resolve_market(market);
+        let res: Response = self
+            .get(
+                &["search"],
+                &Query {
+                    q: Some(q),
+                    item_type: Some("track"),
+                    market: market.as_deref(),
+                    limit,
+                    offset,
+                    ..Default::default()
+                },
+            )
+            .await?;
+        Ok(res.tracks)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    /// A track with the given `id`, `name`, artist `artist` and `isrc` for tests of track based features
+    pub(crate) fn track(id: &str, name: &str, artist: &str, isrc: &str) -> Track {
+        serde_json::from_str(&format!(
+            r#"{{"album":{{"album_type":"album","total_tracks":1,"id":"2up3OPMp9Tb4dAKM2erWXQ","images":[],"name":"{name} Album","release_date":"2020-01-01","release_date_precision":"day","uri":"spotify:album:2up3OPMp9Tb4dAKM2erWXQ","artists":[]}},"artists":[{{"name":"{artist}","id":"0TnOYISbd1XYRBk9myaseg","uri":"spotify:artist:0TnOYISbd1XYRBk9myaseg"}}],"disc_number":1,"duration_ms":200000,"explicit":false,"external_ids":{{"isrc":"{isrc}"}},"id":"{id}","name":"{name}","popularity":50,"track_number":1,"uri":"spotify:track:{id}","is_local":false}}"#
+        ))
+        .expect("A valid test track")
+    }
+
+    #[wasm_bindgen_test]
+    fn test_track_deserialize() {
+        let track = track(
+            "11dFghVXANMlKmJXsNCbNl",
+            "Cut To The Feeling",
+            "Carly Rae Jepsen",
+            "USUM71703861",
+        );
+        assert_eq!(
+            track.id,
+            Some(TrackId::new("11dFghVXANMlKmJXsNCbNl").unwrap())
+        );
+        assert_eq!(track.external_ids.isrc.as_deref(), Some("USUM71703861"));
+        assert_eq!(track.artists[0].name, "Carly Rae Jepsen");
+        assert_eq!(track.linked_from, None);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_relinked_track_deserialize() {
+        let track: SimplifiedTrack = serde_json::from_str(
+            r#"{"artists":[],"disc_number":1,"duration_ms":1000,"explicit":false,"href":"https://api.spotify.com/v1/tracks/6kLCHFM39wkFjOuyPGLGeQ","id":"6kLCHFM39wkFjOuyPGLGeQ","is_playable":true,"linked_from":{"external_urls":{"spotify":"https://open.spotify.com/track/6ozxplTAjWO0BlUxN8ia0A"},"href":"https://api.spotify.com/v1/tracks/6ozxplTAjWO0BlUxN8ia0A","id":"6ozxplTAjWO0BlUxN8ia0A","type":"track","uri":"spotify:track:6ozxplTAjWO0BlUxN8ia0A"},"name":"Heaven and Hell","track_number":1,"uri":"spotify:track:6kLCHFM39wkFjOuyPGLGeQ"}"#,
+        )
+        .unwrap();
+        assert_eq!(track.is_playable, Some(true));
+        assert_eq!(
+            track.linked_from.map(|linked| linked.id),
+            Some(TrackId::new("6ozxplTAjWO0BlUxN8ia0A").unwrap())
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_local_track_deserialize() {
+        let track: Track = serde_json::from_str(
+            r#"{"album":{"album_type":null,"available_markets":[],"external_urls":{},"href":null,"id":null,"images":[],"name":"","release_date":null,"release_date_precision":null,"type":"album","uri":null,"artists":[]},"artists":[{"external_urls":{},"href":null,"id":null,"name":"Artist","type":"artist","uri":null}],"available_markets":[],"disc_number":0,"duration_ms":166000,"explicit":false,"external_ids":{},"external_urls":{},"href":null,"id":null,"is_local":true,"name":"Local","popularity":0,"preview_url":null,"track_number":0,"type":"track","uri":"spotify:local:Artist::Local:166"}"#,
+        )
+        .unwrap();
+        assert!(track.is_local);
+        assert_eq!(track.id, None);
+    }
+}
@@ -0,0 +1,216 @@
+//! [Users](https://developer.spotify.com/documentation/web-api/reference/get-users-profile) API
+
+use crate::{artists::Artist, client::Query, model::*, tracks::Track, Client};
+use strum_macros::*;
+use utils::request::Result;
+
+/// [Public User Object](https://developer.spotify.com/documentation/web-api/reference/get-users-profile)
+#[derive(Clone, Eq, PartialEq, Hash, Debug, serde::Serialize, serde::Deserialize)]
+pub struct PublicUser {
+    /// The name displayed on the user's profile
+    #[serde(default)]
+    pub display_name: Option<String>,
+    /// Known public external URLs for this user
+    pub external_urls: ExternalUrls,
+    /// Information about the followers of this user
+    #[serde(default)]
+    pub followers: Option<Followers>,
+    /// A link to the Web API endpoint for this user
+    pub href: String,
+    /// The Spotify user ID for this user
+    pub id: UserId,
+    /// The user's profile image
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub images: Vec<Image>,
+    /// The Spotify URI for this user
+    pub uri: String,
+}
+
+/// The explicit content settings of a user
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    Debug,
+    Default,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub struct ExplicitContent {
+    /// When true, the user can't play explicit content
+    pub filter_enabled: bool,
+    /// When true, the explicit content setting is locked and can't be changed by the user
+    pub filter_locked: bool,
+}
+
+/// [Private User Object](https://developer.spotify.com/documentation/web-api/reference/get-current-users-profile)
+///
+/// `country` and `product` require [`Scopes::UserReadPrivate`](crate::authorisation::Scopes::UserReadPrivate),
+/// `email` requires [`Scopes::UserReadEmail`](crate::authorisation::Scopes::UserReadEmail).
+#[derive(Clone, Eq, PartialEq, Hash, Debug, serde::Serialize, serde::Deserialize)]
+pub struct PrivateUser {
+    /// The [ISO 3166-1 alpha-2](https://en.wikipedia.org/wiki/ISO_3166-1_alpha-2) country code of the user's account
+    #[serde(default)]
+    pub country: Option<String>,
+    /// The name displayed on the user's profile
+    #[serde(default)]
+    pub display_name: Option<String>,
+    /// The user's email address, which may be unverified
+    #[serde(default)]
+    pub email: Option<String>,
+    /// The user's explicit content settings
+    #[serde(default)]
+    pub explicit_content: Option<ExplicitContent>,
+    /// Known public external URLs for this user
+    pub external_urls: ExternalUrls,
+    /// Information about the followers of this user
+    #[serde(default)]
+    pub followers: Option<Followers>,
+    /// A link to the Web API endpoint for this user
+    pub href: String,
+    /// The Spotify user ID for this user
+    pub id: UserId,
+    /// The user's profile image
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub images: Vec<Image>,
+    /// The user's Spotify subscription level, e.g. `premium` or `free`
+    #[serde(default)]
+    pub product: Option<String>,
+    /// The Spotify URI for this user
+    pub uri: String,
+}
+
+/// The period a user's [top items](Client::top_tracks()) are calculated over
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    Debug,
+    Default,
+    AsRefStr,
+    EnumString,
+    Display,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum TimeRange {
+    /// Approximately the last 4 weeks
+    ShortTerm,
+    /// Approximately the last 6 months
+    #[default]
+    MediumTerm,
+    /// Approximately the last year
+    LongTerm,
+}
+
+impl Client {
+    /// [Get Current User's Profile](https://developer.spotify.com/documentation/web-api/reference/get-current-users-profile)
+    pub async fn current_user(&mut self) -> Result<PrivateUser> {
+        self.get(&["me"], &Query::default()).await
+    }
+
+    /// [Get User's Profile](https://developer.spotify.com/documentation/web-api/reference/get-users-profile)
+    pub async fn user(&mut self, id: &UserId) -> Result<PublicUser> {
+        self.get(&["users", id.as_str()], &Query::default()).await
+    }
+
+    /// [Get User's Top Items](https://developer.spotify.com/documentation/web-api/reference/get-users-top-artists-and-tracks)
+    /// restricted to artists
+    ///
+    /// Requires [`Scopes::UserTopRead`](crate::authorisation::Scopes::UserTopRead),
+    /// `limit` is between 1 and 50, defaulting to 20.
+    pub async fn top_artists(
+        &mut self,
+        time_range: Option<TimeRange>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<Page<Artist>> {
+        self.get(
+            &["me", "top", "artists"],
+            &Query {
+                time_range: time_range.as_ref().map(AsRef::as_ref),
+                limit,
+                offset,
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// [Get User's Top Items](https://developer.spotify.com/documentation/web-api/reference/get-users-top-artists-and-tracks)
+    /// restricted to tracks
+    ///
+    /// Requires [`Scopes::UserTopRead`](crate::authorisation::Scopes::UserTopRead),
+    /// `limit` is between 1 and 50, defaulting to 20.
+    pub async fn top_tracks(
+        &mut self,
+        time_range: Option<TimeRange>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<Page<Track>> {
+        self.get(
+            &["me", "top", "tracks"],
+            &Query {
+                time_range: time_range.as_ref().map(AsRef::as_ref),
+                limit,
+                offset,
+                ..Default::default()
+            },
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    fn test_public_user_deserialize() {
+        let user: PublicUser = serde_json::from_str(
+            r#"{"display_name":"Spotify","external_urls":{"spotify":"https://open.spotify.com/user/spotify"},"href":"https://api.spotify.com/v1/users/spotify","id":"spotify","type":"user","uri":"spotify:user:spotify"}"#,
+        )
+        .unwrap();
+        assert_eq!(user.id, UserId::new("spotify").unwrap());
+        assert_eq!(user.display_name.as_deref(), Some("Spotify"));
+        assert_eq!(user.followers, None);
+        assert!(user.images.is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_private_user_deserialize() {
+        let user: PrivateUser = serde_json::from_str(
+            r#"{"country":"AU","display_name":"User","explicit_content":{"filter_enabled":false,"filter_locked":false},"external_urls":{"spotify":"https://open.spotify.com/user/user"},"followers":{"href":null,"total":3},"href":"https://api.spotify.com/v1/users/user","id":"user","images":[],"product":"premium","type":"user","uri":"spotify:user:user"}"#,
+        )
+        .unwrap();
+        assert_eq!(user.country.as_deref(), Some("AU"));
+        assert_eq!(user.email, None);
+        assert_eq!(
+            user.explicit_content.map(|content| content.filter_enabled),
+            Some(false)
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_time_range() {
+        assert_eq!(TimeRange::ShortTerm.as_ref(), "short_term");
+        assert_eq!("long_term".parse(), Ok(TimeRange::LongTerm));
+        assert_eq!(
+            serde_json::to_string(&TimeRange::default()).unwrap(),
+            r#""medium_term""#
+        );
+    }
+}
@@ -0,0 +1,286 @@
+//! Cancellation and timeouts for requests
+//!
+//! Dropping a [`reqwest`] request future on wasm aborts the underlying `fetch()` with its `AbortController`,
+//! so racing a request against an [`AbortHandle`] and a timeout stops it from completing in the background.
+
+use super::*;
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+#[derive(Debug, Default)]
+struct AbortState {
+    aborted: bool,
+    next_key: u64,
+    /// The wakers of the pending requests, by the key of their [`Abortable`]
+    wakers: BTreeMap<u64, Waker>,
+}
+
+/// A handle to cancel every request [made abortable](abortable()) with it, clones share the same state
+#[derive(Clone, Debug, Default)]
+pub struct AbortHandle(Rc<RefCell<AbortState>>);
+
+impl AbortHandle {
+    /// Creates a handle that hasn't been aborted
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels every request made abortable with this handle, including those made later
+    pub fn abort(&self) {
+        let wakers = {
+            let mut state = self.0.borrow_mut();
+            state.aborted = true;
+            std::mem::take(&mut state.wakers)
+        };
+        wakers.into_values().for_each(Waker::wake);
+    }
+
+    /// Returns if the handle has been aborted
+    pub fn is_aborted(&self) -> bool {
+        self.0.borrow().aborted
+    }
+
+    /// Returns a new key to register a request's waker with
+    fn new_key(&self) -> u64 {
+        let mut state = self.0.borrow_mut();
+        state.next_key += 1;
+        state.next_key
+    }
+
+    fn register(&self, key: u64, waker: &Waker) {
+        let mut state = self.0.borrow_mut();
+        if !state.wakers.get(&key).is_some_and(|w| w.will_wake(waker)) {
+            state.wakers.insert(key, waker.clone());
+        }
+    }
+
+    fn unregister(&self, key: u64) {
+        self.0.borrow_mut().wakers.remove(&key);
+    }
+}
+
+impl PartialEq for AbortHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for AbortHandle {}
+
+#[derive(Debug, Default)]
+struct TimerState {
+    elapsed: bool,
+    waker: Option<Waker>,
+}
+
+/// A timer that wakes its task once elapsed and stops on drop
+#[derive(Debug)]
+struct Timer {
+    state: Rc<RefCell<TimerState>>,
+    delayed_fn: DelayedFn,
+}
+
+impl Timer {
+    fn new(duration: std::time::Duration) -> Self {
+        let state = Rc::new(RefCell::new(TimerState::default()));
+        let timer_state = Rc::clone(&state);
+        let delayed_fn = DelayedFn::new_once(
+            move || {
+                let waker = {
+                    let mut state = timer_state.borrow_mut();
+                    state.elapsed = true;
+                    state.waker.take()
+                };
+                if let Some(waker) = waker {
+                    waker.wake();
+                }
+            },
+            duration,
+        );
+        Self { state, delayed_fn }
+    }
+
+    fn poll_elapsed(&self, cx: &mut Context<'_>) -> bool {
+        let mut state = self.state.borrow_mut();
+        if !state.elapsed {
+            state.waker = Some(cx.waker().clone());
+        }
+        state.elapsed
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        self.delayed_fn.stop();
+    }
+}
+
+/// Future of a request that may be cancelled or time out, see [`abortable()`]
+pub struct Abortable<F> {
+    future: Pin<Box<F>>,
+    abort: Option<(AbortHandle, u64)>,
+    timer: Option<Timer>,
+}
+
+impl<T, F: Future<Output = request::Result<T>>> Future for Abortable<F> {
+    type Output = request::Result<T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let res = self.poll_inner(cx);
+        if res.is_ready() {
+            if let Some((abort, key)) = &self.abort {
+                abort.unregister(*key);
+            }
+        }
+        res
+    }
+}
+
+impl<T, F: Future<Output = request::Result<T>>> Abortable<F> {
+    fn poll_inner(&mut self, cx: &mut Context<'_>) -> Poll<request::Result<T>> {
+        if let Some((abort, key)) = &self.abort {
+            if abort.is_aborted() {
+                return Poll::Ready(Err(request::Error::Cancelled));
+            }
+            abort.register(*key, cx.waker());
+        }
+        if let Some(timer) = &self.timer {
+            if timer.poll_elapsed(cx) {
+                return Poll::Ready(Err(request::Error::Timeout));
+            }
+        }
+        self.future.as_mut().poll(cx)
+    }
+}
+
+impl<F> Drop for Abortable<F> {
+    fn drop(&mut self) {
+        if let Some((abort, key)) = &self.abort {
+            abort.unregister(*key);
+        }
+    }
+}
+
+/// Races the request `future` against the `abort` handle and `timeout`,
+/// failing with [`request::Error::Cancelled`] or [`request::Error::Timeout`] respectively
+pub fn abortable<T, F>(
+    future: F,
+    abort: Option<AbortHandle>,
+    timeout: Option<std::time::Duration>,
+) -> Abortable<F>
+where
+    F: Future<Output = request::Result<T>>,
+{
+    Abortable {
+        future: Box::pin(future),
+        abort: abort.map(|abort| {
+            let key = abort.new_key();
+            (abort, key)
+        }),
+        timer: timeout.map(Timer::new),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    fn poll<F: Future>(future: &mut Pin<Box<F>>) -> Poll<F::Output> {
+        future
+            .as_mut()
+            .poll(&mut Context::from_waker(Waker::noop()))
+    }
+
+    #[wasm_bindgen_test]
+    fn test_abort_handle() {
+        let handle = AbortHandle::new();
+        let mut pending = Box::pin(abortable(
+            std::future::pending::<request::Result<()>>(),
+            Some(handle.clone()),
+            None,
+        ));
+        let mut ready = Box::pin(abortable(
+            std::future::ready(Ok(1)),
+            Some(handle.clone()),
+            None,
+        ));
+        assert!(poll(&mut pending).is_pending());
+        assert!(matches!(poll(&mut ready), Poll::Ready(Ok(1))));
+        assert!(!handle.0.borrow().wakers.is_empty());
+
+        handle.clone().abort();
+        assert!(handle.is_aborted());
+        assert!(handle.0.borrow().wakers.is_empty());
+        assert!(matches!(
+            poll(&mut pending),
+            Poll::Ready(Err(request::Error::Cancelled))
+        ));
+
+        let mut later = Box::pin(abortable(std::future::ready(Ok(())), Some(handle), None));
+        assert!(matches!(
+            poll(&mut later),
+            Poll::Ready(Err(request::Error::Cancelled))
+        ));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_wakers_removed() {
+        let handle = AbortHandle::new();
+        let mut ready = Box::pin(abortable(
+            std::future::ready(Ok(())),
+            Some(handle.clone()),
+            None,
+        ));
+        assert!(matches!(poll(&mut ready), Poll::Ready(Ok(()))));
+        assert!(handle.0.borrow().wakers.is_empty());
+
+        let mut pending = Box::pin(abortable(
+            std::future::pending::<request::Result<()>>(),
+            Some(handle.clone()),
+            None,
+        ));
+        assert!(poll(&mut pending).is_pending());
+        assert!(poll(&mut pending).is_pending());
+        assert_eq!(handle.0.borrow().wakers.len(), 1);
+        drop(pending);
+        assert!(handle.0.borrow().wakers.is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_handle_eq() {
+        let handle = AbortHandle::new();
+        assert_eq!(handle, handle.clone());
+        assert_ne!(handle, AbortHandle::new());
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_timeout() {
+        let res = abortable(
+            async {
+                delay(std::time::Duration::from_millis(200)).await;
+                Ok(())
+            },
+            None,
+            Some(std::time::Duration::from_millis(10)),
+        )
+        .await;
+        assert!(matches!(res, Err(request::Error::Timeout)));
+
+        let res = abortable(
+            async { Ok(1) },
+            None,
+            Some(std::time::Duration::from_millis(10)),
+        )
+        .await;
+        assert!(matches!(res, Ok(1)));
+    }
+}
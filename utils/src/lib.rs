@@ -1,6 +1,7 @@
 #![warn(missing_docs)]
 //! [`web_sys`] helper operations
 
+pub mod abort;
 mod store;
 use base64::Engine;
 pub use store::*;
@@ -8,6 +8,8 @@ pub struct StatusError {
     pub status: req::StatusCode,
     /// The body of the response
     pub body: Option<String>,
+    /// The `Retry-After` header given in seconds, e.g. for `429 Too Many Requests`
+    pub retry_after: Option<std::time::Duration>,
 }
 impl std::error::Error for StatusError {}
 impl std::fmt::Display for StatusError {
@@ -54,6 +56,10 @@ pub enum Error {
     Body(JSONError),
     /// Other reswest errors
     Reqwest(req::Error),
+    /// The request was cancelled before it completed
+    Cancelled,
+    /// The request didn't complete within its timeout
+    Timeout,
 }
 impl std::error::Error for Error {}
 impl std::fmt::Display for Error {
@@ -65,6 +71,8 @@ impl std::fmt::Display for Error {
                 Error::Status(status) => status.to_string(),
                 Error::Reqwest(req) => req.to_string(),
                 Error::Body(body) => body.to_string(),
+                Error::Cancelled => "the request was cancelled".to_owned(),
+                Error::Timeout => "the request timed out".to_owned(),
             }
         )
     }
@@ -73,30 +81,167 @@ impl std::fmt::Display for Error {
 /// HTTP request result
 pub type Result<R> = std::result::Result<R, Error>;
 
-/// [`reqwest`] wrapper for deserializing response and consistant error handling
-pub async fn request<R, F>(client: &req::Client, build_request: F) -> Result<R>
+/// A response body kind that can be read from the raw body of a successful response
+pub trait ResponseBody: Sized {
+    /// The `Accept` header sent for this kind of body, if any
+    const ACCEPT: Option<&'static str>;
+
+    /// Reads the body from the raw response `body`
+    fn from_body(body: Vec<u8>) -> Result<Self>;
+}
+
+/// No response body, e.g. for `202 Accepted` or `204 No Content` responses, any body received is ignored
+impl ResponseBody for () {
+    const ACCEPT: Option<&'static str> = None;
+
+    fn from_body(_body: Vec<u8>) -> Result<Self> {
+        Ok(())
+    }
+}
+
+/// The raw bytes of the response body
+impl ResponseBody for Vec<u8> {
+    const ACCEPT: Option<&'static str> = None;
+
+    fn from_body(body: Vec<u8>) -> Result<Self> {
+        Ok(body)
+    }
+}
+
+/// The response body as text, invalid UTF-8 is replaced with `U+FFFD`
+impl ResponseBody for String {
+    const ACCEPT: Option<&'static str> = None;
+
+    fn from_body(body: Vec<u8>) -> Result<Self> {
+        Ok(String::from_utf8_lossy(&body).into_owned())
+    }
+}
+
+/// A JSON response body deserialized into `T`
+///
+/// An empty body is deserialized as `null` so it can be received as `()` or an `Option`.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct Json<T>(pub T);
+
+impl<T: serde::de::DeserializeOwned> ResponseBody for Json<T> {
+    const ACCEPT: Option<&'static str> = Some("application/json");
+
+    fn from_body(body: Vec<u8>) -> Result<Self> {
+        let body = String::from_utf8_lossy(&body).into_owned();
+        let json = if body.is_empty() { "null" } else { &body };
+        serde_json::from_str(json)
+            .map(Json)
+            .map_err(|error| Error::Body(JSONError { error, body }))
+    }
+}
+
+/// A successful response with its headers and body
+#[derive(Clone, Debug)]
+pub struct Response<B> {
+    /// The HTTP status code
+    pub status: req::StatusCode,
+    /// The response headers, e.g. `Location`, `Retry-After` or `ETag`
+    pub headers: req::header::HeaderMap,
+    /// The response body
+    pub body: B,
+}
+
+impl<B> Response<B> {
+    /// Returns the value of the header `name` if it's present and visible ASCII
+    pub fn header<K: req::header::AsHeaderName>(&self, name: K) -> Option<&str> {
+        self.headers.get(name)?.to_str().ok()
+    }
+
+    /// Returns the `Retry-After` header given in seconds
+    pub fn retry_after(&self) -> Option<instant::Duration> {
+        retry_after(&self.headers)
+    }
+
+    /// Maps the body of the response, keeping its status and headers
+    pub fn map<T, F: FnOnce(B) -> T>(self, f: F) -> Response<T> {
+        Response {
+            status: self.status,
+            headers: self.headers,
+            body: f(self.body),
+        }
+    }
+}
+
+/// Returns the `Retry-After` header given in seconds
+fn retry_after(headers: &req::header::HeaderMap) -> Option<instant::Duration> {
+    headers
+        .get(req::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+        .map(instant::Duration::from_secs)
+}
+
+/// [`reqwest`] wrapper for reading any [`ResponseBody`] kind along with the response headers
+/// with consistant error handling
+pub async fn fetch<B, F>(client: &req::Client, build_request: F) -> Result<Response<B>>
 where
-    R: serde::de::DeserializeOwned,
+    B: ResponseBody,
     F: Fn(&req::Client) -> req::RequestBuilder,
 {
-    let res = build_request(client)
-        .header(
-            req::header::ACCEPT,
-            req::header::HeaderValue::from_static("application/json"),
-        )
-        .send()
-        .await
-        .map_err(Error::Reqwest)?;
+    execute(
+        client,
+        build_request(client).build().map_err(Error::Reqwest)?,
+    )
+    .await
+}
+
+/// Sends an already built `request` and reads any [`ResponseBody`] kind along with the response headers
+/// with consistant error handling
+pub async fn execute<B: ResponseBody>(
+    client: &req::Client,
+    mut request: req::Request,
+) -> Result<Response<B>> {
+    if let Some(accept) = B::ACCEPT {
+        request
+            .headers_mut()
+            .entry(req::header::ACCEPT)
+            .or_insert(req::header::HeaderValue::from_static(accept));
+    }
+    let res = client.execute(request).await.map_err(Error::Reqwest)?;
     let status = res.status();
     if status.is_client_error() || status.is_server_error() {
-        Err(Error::Status(StatusError {
+        return Err(Error::Status(StatusError {
             status,
+            retry_after: retry_after(res.headers()),
             body: res.text().await.ok(),
-        }))
-    } else {
-        let body = res.text().await.map_err(Error::Reqwest)?;
-        Ok(serde_json::from_str(&body).map_err(|error| Error::Body(JSONError { error, body }))?)
+        }));
     }
+    let headers = res.headers().clone();
+    let body = res.bytes().await.map_err(Error::Reqwest)?;
+    Ok(Response {
+        status,
+        headers,
+        body: B::from_body(body.to_vec())?,
+    })
+}
+
+/// [`reqwest`] wrapper for deserializing response and consistant error handling
+pub async fn request<R, F>(client: &req::Client, build_request: F) -> Result<R>
+where
+    R: serde::de::DeserializeOwned,
+    F: Fn(&req::Client) -> req::RequestBuilder,
+{
+    fetch::<Json<R>, F>(client, build_request)
+        .await
+        .map(|res| res.body.0)
+}
+
+/// [`reqwest`] wrapper for requests without a response body, any body received is ignored
+pub async fn request_no_content<F>(client: &req::Client, build_request: F) -> Result<()>
+where
+    F: Fn(&req::Client) -> req::RequestBuilder,
+{
+    fetch::<(), F>(client, build_request)
+        .await
+        .map(|res| res.body)
 }
 
 #[cfg(test)]
@@ -133,6 +278,92 @@ mod tests {
         console_log!("{}", res.expect_err("An invalid response"));
     }
 
+    #[wasm_bindgen_test]
+    async fn test_request_empty_body() {
+        let res: Result<()> = request(&req::Client::new(), |client| {
+            client.get("https://httpstat.us/204")
+        })
+        .await;
+        res.expect("An empty response");
+
+        let res: Result<Option<String>> = request(&req::Client::new(), |client| {
+            client.get("https://httpstat.us/204")
+        })
+        .await;
+        assert_eq!(res.expect("An empty response"), None);
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_request_no_content() {
+        request_no_content(&req::Client::new(), |client| {
+            client.get("http://ip.jsontest.com/")
+        })
+        .await
+        .expect("A response with an ignored body");
+
+        let res = request_no_content(&req::Client::new(), |client| {
+            client.get("https://httpstat.us/404")
+        })
+        .await;
+        assert!(matches!(
+            res.expect_err("An invalid response"),
+            Error::Status(StatusError {
+                status: req::StatusCode::NOT_FOUND,
+                ..
+            })
+        ));
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_fetch_text_and_headers() {
+        let res: Response<String> = fetch(&req::Client::new(), |client| {
+            client.get("http://ip.jsontest.com/")
+        })
+        .await
+        .expect("A valid response");
+        assert!(res.body.contains("\"ip\""));
+        assert!(res.header(req::header::CONTENT_TYPE).is_some());
+
+        let res: Response<Vec<u8>> = fetch(&req::Client::new(), |client| {
+            client.get("https://httpstat.us/204")
+        })
+        .await
+        .expect("An empty response");
+        assert_eq!(res.status, req::StatusCode::NO_CONTENT);
+        assert!(res.body.is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_response_body_kinds() {
+        assert_eq!(<()>::from_body(b"ignored".to_vec()).unwrap(), ());
+        assert_eq!(Vec::<u8>::from_body(vec![0, 159]).unwrap(), vec![0, 159]);
+        assert_eq!(String::from_body(b"text".to_vec()).unwrap(), "text");
+        assert_eq!(String::from_body(vec![b'a', 159]).unwrap(), "a\u{FFFD}");
+
+        assert_eq!(Json::<u32>::from_body(b"42".to_vec()).unwrap(), Json(42));
+        assert_eq!(Json::<Option<u32>>::from_body(vec![]).unwrap(), Json(None));
+        assert!(matches!(
+            Json::<u32>::from_body(b"not json".to_vec()),
+            Err(Error::Body(JSONError { error: _, body })) if body == "not json"
+        ));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_response_headers() {
+        let mut headers = req::header::HeaderMap::new();
+        headers.insert(req::header::RETRY_AFTER, "30".parse().unwrap());
+        headers.insert(req::header::ETAG, "\"abc\"".parse().unwrap());
+        let res = Response {
+            status: req::StatusCode::OK,
+            headers,
+            body: (),
+        };
+        assert_eq!(res.retry_after(), Some(instant::Duration::from_secs(30)));
+        assert_eq!(res.header(req::header::ETAG), Some("\"abc\""));
+        assert_eq!(res.header("location"), None);
+        assert_eq!(res.map(|()| 1).body, 1);
+    }
+
     #[wasm_bindgen_test]
     async fn test_request_http_status() {
         let res: Result<()> = request(&req::Client::new(), |client| {
@@ -145,7 +376,7 @@ mod tests {
             err,
             Error::Status(StatusError {
                 status: req::StatusCode::IM_A_TEAPOT,
-                body: _,
+                ..
             })
         ));
 
@@ -159,7 +390,7 @@ mod tests {
             err,
             Error::Status(StatusError {
                 status: req::StatusCode::NOT_FOUND,
-                body: _,
+                ..
             })
         ));
     }